@@ -0,0 +1,11 @@
+#version 440
+
+in vec2 pos;
+
+uniform vec2 dims;
+uniform mat4 model;
+uniform mat4 view;
+
+void main() {
+    gl_Position = view * model * vec4(pos * dims, 0.0, 1.0);
+}
@@ -0,0 +1,21 @@
+#version 440
+
+uniform vec2 viewport_origin;
+uniform float viewport_height;
+uniform vec2 origin;
+uniform float scale;
+uniform vec4 colour;
+
+out vec4 frag_colour;
+
+void main() {
+    vec2 local = gl_FragCoord.xy - viewport_origin;
+    local.y = viewport_height - local.y;
+
+    vec2 cell = mod(local - origin, scale);
+    if (cell.x >= 1.0 && cell.y >= 1.0) {
+        discard;
+    }
+
+    frag_colour = colour;
+}
@@ -0,0 +1,7 @@
+#version 440
+
+in vec2 pos;
+
+void main() {
+    gl_Position = vec4(pos * 2.0, 0.0, 1.0);
+}
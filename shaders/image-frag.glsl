@@ -1,5 +1,12 @@
 #version 440
 
-void main() {
+in vec2 uv;
+
+uniform sampler2D image;
+uniform vec4 tint;
 
-}
\ No newline at end of file
+out vec4 frag_colour;
+
+void main() {
+    frag_colour = texture(image, uv) * tint;
+}
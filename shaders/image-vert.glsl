@@ -1,5 +1,15 @@
-#version 440 
+#version 440
 
-void main()  {
-    
-}
\ No newline at end of file
+in vec2 pos;
+
+uniform vec2 dims;
+uniform mat4 model;
+uniform mat4 view;
+uniform vec4 uv_rect;
+
+out vec2 uv;
+
+void main() {
+    uv = mix(uv_rect.xy, uv_rect.zw, pos + vec2(0.5));
+    gl_Position = view * model * vec4(pos * dims, 0.0, 1.0);
+}
@@ -0,0 +1,176 @@
+// Role of a `DataBox`, every box has exactly one
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum BoxCategory {
+    Hitbox,
+    Hurtbox,
+    Pushbox,
+    Throwbox,
+    GrabTech,
+    ProximityGuard,
+}
+
+pub const CATEGORY_COUNT: usize = 6;
+
+impl BoxCategory {
+    pub const ALL: [BoxCategory; CATEGORY_COUNT] = [
+        BoxCategory::Hitbox,
+        BoxCategory::Hurtbox,
+        BoxCategory::Pushbox,
+        BoxCategory::Throwbox,
+        BoxCategory::GrabTech,
+        BoxCategory::ProximityGuard,
+    ];
+
+    pub fn index(&self) -> usize {
+        match self {
+            BoxCategory::Hitbox => 0,
+            BoxCategory::Hurtbox => 1,
+            BoxCategory::Pushbox => 2,
+            BoxCategory::Throwbox => 3,
+            BoxCategory::GrabTech => 4,
+            BoxCategory::ProximityGuard => 5,
+        }
+    }
+
+    // Used as the key for the category in exported data
+    pub fn name(&self) -> &'static str {
+        match self {
+            BoxCategory::Hitbox => "hitbox",
+            BoxCategory::Hurtbox => "hurtbox",
+            BoxCategory::Pushbox => "pushbox",
+            BoxCategory::Throwbox => "throwbox",
+            BoxCategory::GrabTech => "grab_tech",
+            BoxCategory::ProximityGuard => "proximity_guard",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<BoxCategory> {
+        BoxCategory::ALL.iter().find(|x| x.name() == name).cloned()
+    }
+
+    pub fn default_colour(&self) -> [f32; 4] {
+        match self {
+            BoxCategory::Hitbox => [1.0, 0.1, 0.1, 0.3],
+            BoxCategory::Hurtbox => [0.1, 0.4, 1.0, 0.3],
+            BoxCategory::Pushbox => [1.0, 1.0, 0.1, 0.3],
+            BoxCategory::Throwbox => [1.0, 0.5, 0.0, 0.3],
+            BoxCategory::GrabTech => [0.7, 0.2, 1.0, 0.3],
+            BoxCategory::ProximityGuard => [0.1, 1.0, 0.6, 0.3],
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct CategorySettings {
+    pub colour: [f32; 4],
+    pub layer: u8,
+}
+
+pub const LAYER_COUNT: u8 = 32;
+
+// Symmetric matrix of which collision layers interact, stored as a bit mask per layer
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LayerMatrix {
+    masks: [u32; LAYER_COUNT as usize],
+}
+
+impl LayerMatrix {
+    pub fn new() -> LayerMatrix {
+        LayerMatrix {
+            masks: [0; LAYER_COUNT as usize],
+        }
+    }
+
+    pub fn interacts(&self, a: u8, b: u8) -> bool {
+        if a >= LAYER_COUNT || b >= LAYER_COUNT {
+            return false;
+        }
+
+        self.masks[a as usize] & (1 << b) != 0
+    }
+
+    pub fn set(&mut self, a: u8, b: u8, interacts: bool) {
+        if a >= LAYER_COUNT || b >= LAYER_COUNT {
+            return;
+        }
+
+        if interacts {
+            self.masks[a as usize] |= 1 << b;
+            self.masks[b as usize] |= 1 << a;
+        }
+        else {
+            self.masks[a as usize] &= !(1 << b);
+            self.masks[b as usize] &= !(1 << a);
+        }
+    }
+
+    pub fn mask(&self, layer: u8) -> u32 {
+        self.masks.get(layer as usize).cloned().unwrap_or(0)
+    }
+}
+
+impl Default for LayerMatrix {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Categories {
+    settings: [CategorySettings; CATEGORY_COUNT],
+    pub layers: LayerMatrix,
+}
+
+impl Default for Categories {
+    // Each category on its own layer, with the usual fighting game interactions
+    fn default() -> Self {
+        let mut settings = [CategorySettings { colour: [0.0; 4], layer: 0 }; CATEGORY_COUNT];
+        for category in BoxCategory::ALL.iter() {
+            settings[category.index()] = CategorySettings {
+                colour: category.default_colour(),
+                layer: category.index() as u8,
+            };
+        }
+
+        let mut categories = Categories {
+            settings,
+            layers: LayerMatrix::new(),
+        };
+
+        categories.set_interacts(BoxCategory::Hitbox, BoxCategory::Hurtbox, true);
+        categories.set_interacts(BoxCategory::Pushbox, BoxCategory::Pushbox, true);
+        categories.set_interacts(BoxCategory::Throwbox, BoxCategory::Hurtbox, true);
+        categories.set_interacts(BoxCategory::GrabTech, BoxCategory::Throwbox, true);
+        categories.set_interacts(BoxCategory::ProximityGuard, BoxCategory::Hurtbox, true);
+
+        categories
+    }
+}
+
+impl Categories {
+    pub fn get(&self, category: BoxCategory) -> &CategorySettings {
+        &self.settings[category.index()]
+    }
+
+    pub fn get_mut(&mut self, category: BoxCategory) -> &mut CategorySettings {
+        &mut self.settings[category.index()]
+    }
+
+    pub fn colour(&self, category: BoxCategory) -> [f32; 4] {
+        self.get(category).colour
+    }
+
+    pub fn layer(&self, category: BoxCategory) -> u8 {
+        self.get(category).layer
+    }
+
+    // Whether boxes of the two categories collide, going through their layers
+    pub fn interacts(&self, a: BoxCategory, b: BoxCategory) -> bool {
+        self.layers.interacts(self.layer(a), self.layer(b))
+    }
+
+    pub fn set_interacts(&mut self, a: BoxCategory, b: BoxCategory, interacts: bool) {
+        let (a, b) = (self.layer(a), self.layer(b));
+        self.layers.set(a, b, interacts);
+    }
+}
@@ -0,0 +1,560 @@
+use std::{
+    path::Path,
+    thread,
+    time::Instant,
+};
+use crate::{
+    diff,
+    merge,
+    export::{
+        self,
+        registry::{self, ExportTarget, Exporter, FormatOption, FormatOptions, OptionValue, Registry, TypesExporter},
+    },
+    frame::AnimationData,
+    frame_data::FrameDataSummary,
+    lint::{Linter, Severity},
+    project::Project,
+    project_file,
+    script,
+    validate,
+    watch::{self, Watcher},
+};
+
+// Exit codes
+pub const SUCCESS: i32 = 0;
+pub const FAILURE: i32 = 1;
+pub const USAGE: i32 = 2;
+
+const HELP: &str = "\
+usage: animation-creator [<command> <arguments>]
+
+Runs the editor when no command is given.
+
+commands:
+    validate <project>                          Check the project for errors
+    lint <project> [--deny-warnings]            Run the lint rules set up in the project
+    list <project>                              List animations and data types
+    diff <old project> <new project> [--json]   Print what changed between two projects
+    merge <base> <ours> <theirs> [<output>]     Three way merge into <output>, or <ours> when
+                                                not given so it can be used as a git merge driver
+    summary <project> [<animation>]             Print frame data of every or one animation
+    export <project> <format> <directory>       Export after validating, with the options of
+        [--<option>] [--<option>=<value>]       the format and the export profile picked for it
+        [--profile=<name>]                      in the project or given
+    import <project> <format> <file>            Add the animations read from <file> to the project
+        [--<option>] [--<option>=<value>]
+    watch <project> [<format> <directory>]      Export with the targets saved in the project, or
+        [--<option>] [--<option>=<value>]       the given format, whenever the project or its
+                                                sprite sheets change, until interrupted
+    formats                                     List the export and import formats and their options
+    check-types <project> <directory>           Fail if generated types are out of date, with the
+        [--csharp] [--csharp-namespace=<name>]  options of the types format
+    script <project> <script>                   Run a script saved in the project, or a script
+        [--save] [--deny-warnings]              file, saving its changes when --save is given
+    help                                        Print this message
+";
+
+// Arguments split into positional ones and `--` flags
+struct Arguments<'a> {
+    positional: Vec<&'a str>,
+    flags: Vec<&'a str>,
+}
+
+impl<'a> Arguments<'a> {
+    fn parse(args: &'a [String]) -> Arguments<'a> {
+        let (flags, positional) = args.iter().map(|x| x.as_str()).partition(|x| x.starts_with("--"));
+
+        Arguments {
+            positional,
+            flags,
+        }
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.flags.iter().any(|x| &x[2..] == name)
+    }
+
+    // The value of a `--name=value` flag
+    fn value(&self, name: &str) -> Option<&'a str> {
+        self.flags.iter()
+            .filter_map(|x| x[2..].find('=').map(|i| (&x[2..2 + i], &x[3 + i..])))
+            .find(|(flag, _)| *flag == name)
+            .map(|(_, value)| value)
+    }
+
+    fn check(&self, count: std::ops::RangeInclusive<usize>, flags: &[&str]) -> Result<(), String> {
+        if !count.contains(&self.positional.len()) {
+            return Err("wrong number of arguments".to_string());
+        }
+
+        match self.flags.iter().find(|x| !flags.contains(&x[2..].split('=').next().unwrap_or(""))) {
+            Some(flag) => Err(format!("unknown flag '{}'", flag)),
+            None => Ok(()),
+        }
+    }
+}
+
+fn load(path: &str) -> Option<Project> {
+    match project_file::load(Path::new(path)) {
+        Ok(project) => Some(project),
+        Err(error) => {
+            eprintln!("error: {}", error);
+            None
+        }
+    }
+}
+
+// Prints every error, returning whether there were none
+fn report_validation(project: &Project) -> bool {
+    let errors = validate::validate_project(project);
+
+    for error in errors.iter() {
+        eprintln!("error: {}", error);
+    }
+
+    errors.is_empty()
+}
+
+fn validate(project: &Project) -> i32 {
+    match report_validation(project) {
+        true => {
+            println!("{} animations, no errors", project.animations.len());
+            SUCCESS
+        }
+        false => FAILURE,
+    }
+}
+
+// Fails on errors, and on warnings too when denied
+fn lint(project: &Project, args: &Arguments) -> i32 {
+    let problems = Linter::with_builtin_rules().run(project, &project.lint);
+    let failing = if args.flag("deny-warnings") { Severity::Warning } else { Severity::Error };
+
+    for problem in problems.iter() {
+        println!("{}", problem.describe(project));
+    }
+
+    match problems.iter().any(|x| x.severity >= failing) {
+        true => FAILURE,
+        false => SUCCESS,
+    }
+}
+
+fn print_diff(old: &Project, new: &Project, args: &Arguments) -> i32 {
+    let changes = diff::diff_projects(old, new);
+
+    match args.flag("json") {
+        true => println!("{}", serde_json::to_string_pretty(&diff::to_json(&changes)).unwrap_or_default()),
+        false => print!("{}", diff::to_text(&changes)),
+    }
+
+    SUCCESS
+}
+
+// Fails when there were conflicts, the merged project is written either way
+fn merge_files(base: &Project, ours: &Project, theirs: &Project, output: &str) -> i32 {
+    let result = match merge::merge_projects(base, ours, theirs) {
+        Ok(result) => result,
+        Err(error) => {
+            eprintln!("error: merged project is invalid: {}", error);
+            return FAILURE;
+        }
+    };
+
+    if let Err(error) = project_file::save(&result.project, Path::new(output)) {
+        eprintln!("error: {}", error);
+        return FAILURE;
+    }
+
+    for conflict in result.conflicts.iter() {
+        eprintln!("conflict: {}", conflict);
+    }
+
+    match result.conflicts.is_empty() {
+        true => SUCCESS,
+        false => FAILURE,
+    }
+}
+
+fn list(project: &Project) -> i32 {
+    println!("animations:");
+    for animation in project.animations.iter() {
+        println!(
+            "    {} ({} frames, {} ticks at {} fps, {} boxes, {} markers)",
+            animation.name,
+            animation.frames().len(),
+            animation.total_ticks(),
+            animation.fps(),
+            animation.data_boxes().len(),
+            animation.markers().len(),
+        );
+    }
+
+    let types = match export::project_types(project) {
+        Ok(types) => types,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            return FAILURE;
+        }
+    };
+
+    println!("types:");
+    for (name, storage) in types {
+        let fields = storage.get_desc().iter()
+            .map(|x| format!("{}: {}", x.name, x.dtype.name()))
+            .collect::<Vec<_>>();
+
+        println!("    {} {{ {} }}", name, fields.join(", "));
+    }
+
+    SUCCESS
+}
+
+fn print_summary(animation: &AnimationData) {
+    let summary = FrameDataSummary::summarize(animation);
+    let value = |x: Option<u32>| x.map(|x| x.to_string()).unwrap_or_else(|| "-".to_string());
+    let advantage = |x: Option<i32>| x.map(|x| format!("{:+}", x)).unwrap_or_else(|| "-".to_string());
+
+    println!(
+        "{}: startup {}, active {}, recovery {}, total {}, on hit {}, on block {}",
+        animation.name,
+        value(summary.startup),
+        value(summary.active),
+        value(summary.recovery),
+        summary.total,
+        advantage(summary.on_hit),
+        advantage(summary.on_block),
+    );
+}
+
+fn summary(project: &Project, name: Option<&str>) -> i32 {
+    match name {
+        Some(name) => match project.get_animation(name) {
+            Some(animation) => print_summary(animation),
+            None => {
+                eprintln!("error: no animation named '{}'", name);
+                return FAILURE;
+            }
+        },
+        None => project.animations.iter().for_each(print_summary),
+    }
+
+    SUCCESS
+}
+
+// Option flags given on the command line over the defaults of the format
+fn format_options(options: &[FormatOption], args: &Arguments) -> FormatOptions {
+    let mut values = FormatOptions::defaults(options);
+
+    for option in options.iter() {
+        let value = match (&option.default, args.value(option.name)) {
+            (OptionValue::Flag(_), _) if args.flag(option.name) => OptionValue::Flag(true),
+            (OptionValue::Text(_), Some(value)) => OptionValue::Text(value.to_string()),
+            _ => continue,
+        };
+
+        values.set(option.name, value);
+    }
+
+    values
+}
+
+fn option_names(options: Vec<FormatOption>) -> Vec<&'static str> {
+    options.into_iter().map(|x| x.name).collect()
+}
+
+// Checks the flags against the options of the format named by the second argument and `extra`
+fn check_format_flags(args: &Arguments, registry: &Registry, import: bool, extra: &[&'static str]) -> Result<(), String> {
+    if args.positional.len() != 3 {
+        return Err("wrong number of arguments".to_string());
+    }
+
+    let format = args.positional[1];
+    let options = match import {
+        true => registry.importer(format).map(|x| x.options()),
+        false => registry.exporter(format).map(|x| x.options()),
+    };
+
+    match options {
+        Some(options) => {
+            let mut names = option_names(options);
+            names.extend_from_slice(extra);
+            args.check(3..=3, &names)
+        }
+        None => Err(format!("unknown format '{}', `formats` lists them", format)),
+    }
+}
+
+fn list_formats(registry: &Registry) -> i32 {
+    let print_options = |options: Vec<FormatOption>| {
+        for option in options {
+            match option.default {
+                OptionValue::Flag(_) => println!("        --{}: {}", option.name, option.description),
+                OptionValue::Text(default) => println!("        --{}=<value>: {} (default '{}')", option.name, option.description, default),
+            }
+        }
+    };
+
+    println!("export formats:");
+    for exporter in registry.exporters() {
+        println!("    {}: {}", exporter.name(), exporter.description());
+        print_options(exporter.options());
+    }
+
+    println!("import formats:");
+    for importer in registry.importers() {
+        println!("    {}: {}", importer.name(), importer.description());
+        print_options(importer.options());
+    }
+
+    SUCCESS
+}
+
+fn export(project: &Project, registry: &Registry, format: &str, directory: &str, args: &Arguments) -> i32 {
+    if !report_validation(project) {
+        eprintln!("error: not exporting a project with errors");
+        return FAILURE;
+    }
+
+    let exporter = match registry.exporter(format) {
+        Some(exporter) => exporter,
+        None => {
+            eprintln!("error: unknown format '{}'", format);
+            return USAGE;
+        }
+    };
+
+    let profile = match args.value("profile") {
+        Some(name) => match project.export_profiles.get(name) {
+            Some(profile) => profile.clone(),
+            None => {
+                eprintln!("error: no export profile named '{}'", name);
+                return FAILURE;
+            }
+        },
+        None => project.exporter_profile(format),
+    };
+
+    let options = format_options(&exporter.options(), args);
+    match registry::run_exporter(exporter, project, Path::new(directory), &options, &profile) {
+        Ok(written) => {
+            for path in written {
+                println!("{}", path.display());
+            }
+            SUCCESS
+        }
+        Err(error) => {
+            eprintln!("error: {}", error);
+            FAILURE
+        }
+    }
+}
+
+fn check_types(project: &Project, directory: &str, args: &Arguments) -> i32 {
+    let options = TypesExporter::codegen_options(&format_options(&TypesExporter.options(), args));
+
+    match export::type_codegen::check_project(project, Path::new(directory), &options) {
+        Ok(()) => SUCCESS,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            FAILURE
+        }
+    }
+}
+
+// Saves the project with the imported animations added when the import and validation succeed
+fn import(mut project: Project, registry: &Registry, path: &str, format: &str, file: &str, args: &Arguments) -> i32 {
+    let importer = match registry.importer(format) {
+        Some(importer) => importer,
+        None => {
+            eprintln!("error: unknown format '{}'", format);
+            return USAGE;
+        }
+    };
+
+    let options = format_options(&importer.options(), args);
+    let added = match importer.import(&mut project, Path::new(file), &options) {
+        Ok(added) => added,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            return FAILURE;
+        }
+    };
+
+    if !report_validation(&project) {
+        eprintln!("error: not saving a project with errors");
+        return FAILURE;
+    }
+
+    if let Err(error) = project_file::save(&project, Path::new(path)) {
+        eprintln!("error: {}", error);
+        return FAILURE;
+    }
+
+    for name in added {
+        println!("{}", name);
+    }
+
+    SUCCESS
+}
+
+fn report_exports(results: Vec<Result<String, String>>) {
+    for result in results {
+        match result {
+            Ok(line) => println!("{}", line),
+            Err(error) => eprintln!("error: {}", error),
+        }
+    }
+}
+
+// Reloads and exports whenever the watched files change, load and export errors are reported
+// and watching goes on
+fn watch_project(path: &str, registry: &Registry, args: &Arguments) -> i32 {
+    let target = match args.positional.len() {
+        3 => Some(ExportTarget {
+            exporter: args.positional[1].to_string(),
+            directory: args.positional[2].to_string(),
+            options: match registry.exporter(args.positional[1]) {
+                Some(exporter) => format_options(&exporter.options(), args),
+                None => FormatOptions::default(),
+            },
+        }),
+        _ => None,
+    };
+
+    let project_path = Path::new(path);
+    let mut watcher = Watcher::new(vec![project_path.to_path_buf()]);
+    let mut changed = true;
+
+    loop {
+        if changed {
+            match project_file::load(project_path) {
+                Ok(project) => {
+                    watcher.set_files(watch::watched_files(&project, project_path));
+
+                    let targets = match &target {
+                        Some(target) => vec![target.clone()],
+                        None => project.export_targets.clone(),
+                    };
+
+                    match targets.is_empty() {
+                        true => eprintln!("error: the project has no export targets, add them in the export window or give a format and directory"),
+                        false => report_exports(watch::export_targets(registry, &project, &targets)),
+                    }
+                }
+                Err(error) => eprintln!("error: {}", error),
+            }
+
+            let files = watcher.files().map(|x| x.display().to_string()).collect::<Vec<_>>();
+            println!("watching {}", files.join(", "));
+        }
+
+        thread::sleep(watch::POLL_INTERVAL);
+        changed = watcher.poll(Instant::now());
+    }
+}
+
+// Runs a script saved in the project, or a file when no script has that name
+fn run_script(mut project: Project, path: &str, name: &str, args: &Arguments) -> i32 {
+    let source = match project.scripts.get(name) {
+        Some(source) => source.clone(),
+        None => match std::fs::read_to_string(name) {
+            Ok(source) => source,
+            Err(error) => {
+                eprintln!("error: no script named '{}' in the project and reading it as a file failed: {}", name, error);
+                return FAILURE;
+            }
+        },
+    };
+
+    let output = script::run(&mut project, &source);
+    for line in output.printed.iter() {
+        println!("{}", line);
+    }
+    for warning in output.warnings.iter() {
+        eprintln!("warning: {}", warning);
+    }
+
+    if let Some(error) = &output.error {
+        eprintln!("error: {}", error);
+        return FAILURE;
+    }
+
+    if args.flag("save") {
+        if let Err(error) = project_file::save(&project, Path::new(path)) {
+            eprintln!("error: {}", error);
+            return FAILURE;
+        }
+    }
+
+    match args.flag("deny-warnings") && !output.warnings.is_empty() {
+        true => FAILURE,
+        false => SUCCESS,
+    }
+}
+
+// Runs the command in `args`, not including the program name, returning the exit code
+pub fn run(args: &[String], registry: &Registry) -> i32 {
+    let command = args[0].as_str();
+    let args = Arguments::parse(&args[1..]);
+
+    let checked = match command {
+        "validate" | "list" => args.check(1..=1, &[]),
+        "lint" => args.check(1..=1, &["deny-warnings"]),
+        "summary" => args.check(1..=2, &[]),
+        "diff" => args.check(2..=2, &["json"]),
+        "merge" => args.check(3..=4, &[]),
+        "export" => check_format_flags(&args, registry, false, &["profile"]),
+        "import" => check_format_flags(&args, registry, true, &[]),
+        "watch" => match args.positional.len() {
+            1 => args.check(1..=1, &[]),
+            _ => check_format_flags(&args, registry, false, &[]),
+        },
+        "check-types" => args.check(2..=2, &option_names(TypesExporter.options())),
+        "script" => args.check(2..=2, &["save", "deny-warnings"]),
+        "formats" => return list_formats(registry),
+        "help" | "--help" | "-h" => {
+            print!("{}", HELP);
+            return SUCCESS;
+        }
+        _ => Err(format!("unknown command '{}'", command)),
+    };
+
+    if let Err(error) = checked {
+        eprintln!("error: {}\n\n{}", error, HELP);
+        return USAGE;
+    }
+
+    // Keeps going when the project fails to load, it may be fixed while watching
+    if command == "watch" {
+        return watch_project(args.positional[0], registry, &args);
+    }
+
+    let project = match load(args.positional[0]) {
+        Some(project) => project,
+        None => return FAILURE,
+    };
+
+    match command {
+        "validate" => validate(&project),
+        "lint" => lint(&project, &args),
+        "list" => list(&project),
+        "diff" => match load(args.positional[1]) {
+            Some(new) => print_diff(&project, &new, &args),
+            None => FAILURE,
+        },
+        "merge" => match (load(args.positional[1]), load(args.positional[2])) {
+            (Some(ours), Some(theirs)) => {
+                let output = args.positional.get(3).unwrap_or(&args.positional[1]);
+                merge_files(&project, &ours, &theirs, output)
+            }
+            _ => FAILURE,
+        },
+        "summary" => summary(&project, args.positional.get(1).cloned()),
+        "export" => export(&project, registry, args.positional[1], args.positional[2], &args),
+        "import" => import(project, registry, args.positional[0], args.positional[1], args.positional[2], &args),
+        "check-types" => check_types(&project, args.positional[1], &args),
+        "script" => run_script(project, args.positional[0], args.positional[1], &args),
+        _ => unreachable!(),
+    }
+}
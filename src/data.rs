@@ -5,8 +5,9 @@ use std::{
     fmt::Debug,
 };
 use fnv::FnvHashMap;
+use crate::expression::{Expression, ExpressionError};
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum DataType {
     F32,
     I32,
@@ -17,6 +18,50 @@ pub enum DataType {
 }
 
 impl DataType {
+    pub const ALL: [DataType; 6] = [
+        DataType::F32,
+        DataType::I32,
+        DataType::U32,
+        DataType::Char,
+        DataType::Bool,
+        DataType::OwnedString,
+    ];
+
+    // Used to store the type in project files
+    pub fn name(&self) -> &'static str {
+        match self {
+            DataType::F32 => "f32",
+            DataType::I32 => "i32",
+            DataType::U32 => "u32",
+            DataType::Char => "char",
+            DataType::Bool => "bool",
+            DataType::OwnedString => "string",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<DataType> {
+        DataType::ALL.iter().find(|x| x.name() == name).cloned()
+    }
+
+    // Types that can be used in and computed by expressions
+    pub fn is_numeric(&self) -> bool {
+        match self {
+            DataType::F32 | DataType::I32 | DataType::U32 | DataType::Bool => true,
+            DataType::Char | DataType::OwnedString => false,
+        }
+    }
+
+    // Integers are rounded and clamped, None for chars and strings
+    pub fn from_f64(&self, value: f64) -> Option<DataValue> {
+        match self {
+            DataType::F32 => Some(DataValue::F32(value as f32)),
+            DataType::I32 => Some(DataValue::I32(value.round() as i32)),
+            DataType::U32 => Some(DataValue::U32(value.round() as u32)),
+            DataType::Bool => Some(DataValue::Bool(value != 0.0)),
+            DataType::Char | DataType::OwnedString => None,
+        }
+    }
+
     pub fn to_default_value(&self) -> DataValue {
         match self {
             DataType::F32 => DataValue::F32(f32::default()),
@@ -54,6 +99,30 @@ impl DataValue {
     }
 }
 
+impl DataValue {
+    pub fn dtype(&self) -> DataType {
+        match self {
+            DataValue::F32(_) => DataType::F32,
+            DataValue::I32(_) => DataType::I32,
+            DataValue::U32(_) => DataType::U32,
+            DataValue::Char(_) => DataType::Char,
+            DataValue::Bool(_) => DataType::Bool,
+            DataValue::OwnedString(_) => DataType::OwnedString,
+        }
+    }
+
+    // Numeric values as a float, None for chars and strings
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            DataValue::F32(val) => Some(*val as f64),
+            DataValue::I32(val) => Some(*val as f64),
+            DataValue::U32(val) => Some(*val as f64),
+            DataValue::Bool(val) => Some(if *val { 1.0 } else { 0.0 }),
+            DataValue::Char(_) | DataValue::OwnedString(_) => None,
+        }
+    }
+}
+
 impl std::fmt::Display for DataValue {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
@@ -67,22 +136,125 @@ impl std::fmt::Display for DataValue {
     }
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub enum FieldError {
+    UnknownField(String),
+    DuplicateName(String),
+    OutOfRange { index: usize, len: usize },
+    // The field is used by the expression of another field
+    InUse { field: String, by: String },
+    Expression { field: String, error: ExpressionError },
+    Computed(String),
+    UnknownRecord,
+    TypeMismatch { field: String, expected: DataType, found: DataType },
+}
+
+impl std::fmt::Display for FieldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            FieldError::UnknownField(name) => write!(f, "no field named '{}'", name),
+            FieldError::DuplicateName(name) => write!(f, "a field named '{}' already exists", name),
+            FieldError::OutOfRange { index, len } => write!(f, "field {} is out of range for {} fields", index, len),
+            FieldError::InUse { field, by } => write!(f, "'{}' is used by the expression of '{}'", field, by),
+            FieldError::Expression { field, error } => write!(f, "'{}': {}", field, error),
+            FieldError::Computed(name) => write!(f, "'{}' is computed and can't be set", name),
+            FieldError::UnknownRecord => write!(f, "no such record"),
+            FieldError::TypeMismatch { field, expected, found } => write!(f, "'{}' is {} but the value is {}", field, expected.name(), found.name()),
+        }
+    }
+}
+
+impl Error for FieldError {}
+
+#[derive(Clone, Debug)]
 pub struct DataDescription {
     pub name: String,
     pub dtype: DataType,
+    // Computed from the other fields of the record instead of being set
+    expression: Option<Expression>,
 }
 
 impl DataDescription {
     pub fn new(name: String, dtype: DataType) -> Self {
         DataDescription {
             name,
-            dtype
+            dtype,
+            expression: None,
+        }
+    }
+
+    pub fn expression(&self) -> Option<&Expression> {
+        self.expression.as_ref()
+    }
+
+    pub fn is_computed(&self) -> bool {
+        self.expression.is_some()
+    }
+}
+
+// Indices of the computed fields, each after the computed fields its expression uses
+fn evaluation_order(description: &[DataDescription]) -> Result<Vec<usize>, ExpressionError> {
+    // None until visited, false while the fields it uses are visited
+    fn visit(index: usize, description: &[DataDescription], done: &mut [Option<bool>], path: &mut Vec<usize>, order: &mut Vec<usize>) -> Result<(), ExpressionError> {
+        match done[index] {
+            Some(true) => return Ok(()),
+            Some(false) => {
+                let start = path.iter().position(|x| *x == index).unwrap_or(0);
+                let cycle = path[start..].iter().chain(Some(&index)).map(|x| description[*x].name.clone()).collect();
+                return Err(ExpressionError::Cycle(cycle));
+            }
+            None => (),
+        }
+
+        done[index] = Some(false);
+        path.push(index);
+
+        if let Some(expression) = &description[index].expression {
+            for name in expression.fields() {
+                if let Some(used) = description.iter().position(|x| x.name == name) {
+                    visit(used, description, done, path, order)?;
+                }
+            }
+
+            order.push(index);
+        }
+
+        path.pop();
+        done[index] = Some(true);
+        Ok(())
+    }
+
+    let mut done = vec![None; description.len()];
+    let mut order = vec![];
+    for index in 0..description.len() {
+        visit(index, description, &mut done, &mut vec![], &mut order)?;
+    }
+
+    Ok(order)
+}
+
+fn compute_fields(description: &[DataDescription], order: &[usize], values: &mut [DataValue]) {
+    for &index in order {
+        let expression = match &description[index].expression {
+            Some(expression) => expression,
+            None => continue,
+        };
+
+        let value = expression.evaluate(&|name| {
+            description.iter().position(|x| x.name == name)
+                .and_then(|x| values[x].as_f64())
+                .unwrap_or(0.0)
+        });
+
+        if let Some(value) = description[index].dtype.from_f64(value) {
+            values[index] = value;
         }
     }
 }
 
+#[derive(Clone, Debug)]
 pub struct DataTypeStorage<K> {
-    pub description: Vec<DataDescription>,
+    description: Vec<DataDescription>,
     values: FnvHashMap<K, Vec<DataValue>>,
 }
 
@@ -95,16 +267,18 @@ impl<K: Hash + Eq> DataTypeStorage<K> {
     }
 
     pub fn gen_new(&mut self, key: K) -> &mut [DataValue] {
-        let values = self.values.entry(key).or_insert(vec![]);
-        values.clear();
-
-        for desc in &self.description {
-            values.push(desc.dtype.to_default_value());
-        }
+        let mut defaults = self.description.iter().map(|x| x.dtype.to_default_value()).collect::<Vec<_>>();
+        compute_fields(&self.description, &self.computed_order(), &mut defaults);
 
+        let values = self.values.entry(key).or_insert(vec![]);
+        *values = defaults;
         values
     }
 
+    pub fn remove(&mut self, key: &K) -> Option<Vec<DataValue>> {
+        self.values.remove(key)
+    }
+
     pub fn get_desc(&self) -> &[DataDescription] {
         &self.description[..]
     }
@@ -113,6 +287,7 @@ impl<K: Hash + Eq> DataTypeStorage<K> {
         self.values.get(key).map(|x| x.as_slice())
     }
 
+    // Computed fields are not updated until `refresh` is called, `set_value` does both
     pub fn get_mut(&mut self, key: &K) -> Option<&mut [DataValue]> {
         self.values.get_mut(key).map(|x| &mut x[..])
     }
@@ -121,87 +296,255 @@ impl<K: Hash + Eq> DataTypeStorage<K> {
         (self.description.as_slice(), self.get(key))
     }
 
-    pub fn has_field_with_name(&self, name: &str) -> bool {
-        for desc in self.description.iter() {
-            if desc.name == name {
-                return true;
+    // Sets the field and recomputes the fields that depend on it
+    pub fn set(&mut self, key: &K, index: usize, value: DataValue) -> Result<(), FieldError> {
+        self.check_index(index)?;
+        let desc = &self.description[index];
+        if desc.is_computed() {
+            return Err(FieldError::Computed(desc.name.clone()));
+        }
+        if value.dtype() != desc.dtype {
+            return Err(FieldError::TypeMismatch { field: desc.name.clone(), expected: desc.dtype, found: value.dtype() });
+        }
+
+        let values = self.values.get_mut(key).ok_or(FieldError::UnknownRecord)?;
+        values[index] = value;
+        self.refresh(key);
+
+        Ok(())
+    }
+
+    // Parses `value` into the field, failing with the parse error or the error of `set`
+    pub fn set_value(&mut self, key: &K, index: usize, value: &str) -> Result<(), Box<dyn Error>> {
+        self.check_index(index)?;
+        let mut parsed = self.description[index].dtype.to_default_value();
+        parsed.set_value(value)?;
+
+        Ok(self.set(key, index, parsed)?)
+    }
+
+    // Recomputes the computed fields of one record
+    pub fn refresh(&mut self, key: &K) {
+        let order = self.computed_order();
+        if let Some(values) = self.values.get_mut(key) {
+            compute_fields(&self.description, &order, values);
+        }
+    }
+
+    pub fn refresh_all(&mut self) {
+        let order = self.computed_order();
+        for values in self.values.values_mut() {
+            compute_fields(&self.description, &order, values);
+        }
+    }
+
+    // Expressions are checked for cycles when they are set
+    fn computed_order(&self) -> Vec<usize> {
+        evaluation_order(&self.description).unwrap_or_default()
+    }
+
+    // Makes the field computed from `source`, or stored again when `source` is empty
+    pub fn set_expression(&mut self, index: usize, source: &str) -> Result<(), FieldError> {
+        self.check_index(index)?;
+        let name = self.description[index].name.clone();
+        let error = |error| FieldError::Expression { field: name.clone(), error };
+
+        let expression = match source.trim() {
+            "" => None,
+            source => {
+                let expression = Expression::parse(source).map_err(error)?;
+                if !self.description[index].dtype.is_numeric() {
+                    return Err(error(ExpressionError::NotNumeric(name.clone())));
+                }
+
+                for used in expression.fields() {
+                    match self.description.iter().find(|x| x.name == used) {
+                        Some(desc) if desc.dtype.is_numeric() => (),
+                        Some(_) => return Err(error(ExpressionError::NotNumeric(used.to_string()))),
+                        None => return Err(error(ExpressionError::UnknownField(used.to_string()))),
+                    }
+                }
+
+                Some(expression)
             }
-        }        
+        };
+
+        let previous = std::mem::replace(&mut self.description[index].expression, expression);
+        if let Err(e) = evaluation_order(&self.description) {
+            self.description[index].expression = previous;
+            return Err(error(e));
+        }
+
+        self.refresh_all();
+        Ok(())
+    }
 
-        false
+    // In no particular order
+    pub fn keys<'a>(&'a self) -> impl Iterator<Item = &'a K> + 'a {
+        self.values.keys()
     }
 
-    pub fn add_field(&mut self, name: String, data_type: DataType) {
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn field_index(&self, name: &str) -> Option<usize> {
+        self.description.iter().position(|x| x.name == name)
+    }
+
+    pub fn get_field(&self, key: &K, name: &str) -> Option<&DataValue> {
+        let index = self.field_index(name)?;
+        self.get(key).and_then(|x| x.get(index))
+    }
+
+    pub fn has_field_with_name(&self, name: &str) -> bool {
+        self.field_index(name).is_some()
+    }
+
+    pub fn add_field(&mut self, name: String, data_type: DataType) -> Result<(), FieldError> {
+        if self.has_field_with_name(&name) {
+            return Err(FieldError::DuplicateName(name));
+        }
+
         self.description.push(DataDescription::new(name, data_type));
 
         for value in self.values.values_mut() {
             value.push(data_type.to_default_value());
         }
+
+        self.check_invariant();
+        Ok(())
+    }
+
+    // Fields used by an expression can't be renamed as the expression refers to them by name
+    pub fn rename_field(&mut self, index: usize, name: String) -> Result<(), FieldError> {
+        self.check_index(index)?;
+        if self.description[index].name == name {
+            return Ok(());
+        }
+        if self.has_field_with_name(&name) {
+            return Err(FieldError::DuplicateName(name));
+        }
+
+        if let Some(user) = self.used_by(index) {
+            return Err(FieldError::InUse { field: self.description[index].name.clone(), by: user.name.clone() });
+        }
+
+        self.description[index].name = name;
+        Ok(())
     }
 
-    pub fn remove_field_with_name(&mut self, name: &str) {
-        let mut contains = false;
-        let mut index = 0;
-        for (i, desc) in self.description.iter().enumerate() {
-            if desc.name == name {
-                contains = true;
-                index = i;
-                break;
+    // Renames several fields at once so names can be swapped or moved along a chain, as pairs of
+    // field index and new name. Nothing is renamed if any of the new names clash
+    pub fn rename_fields(&mut self, renames: &[(usize, String)]) -> Result<(), FieldError> {
+        let mut names = self.description.iter().map(|x| x.name.clone()).collect::<Vec<_>>();
+        for (index, name) in renames.iter() {
+            self.check_index(*index)?;
+            if self.description[*index].name == *name {
+                continue;
+            }
+
+            if let Some(user) = self.used_by(*index) {
+                return Err(FieldError::InUse { field: self.description[*index].name.clone(), by: user.name.clone() });
             }
+            names[*index] = name.clone();
         }
 
-        if !contains {
-            return;
+        for (i, name) in names.iter().enumerate() {
+            if names[..i].contains(name) {
+                return Err(FieldError::DuplicateName(name.clone()));
+            }
         }
 
-        for value in self.values.values_mut() {
-            value.remove(index);
+        for (desc, name) in self.description.iter_mut().zip(names) {
+            desc.name = name;
         }
+        Ok(())
+    }
+
+    pub fn remove_field_with_name(&mut self, name: &str) -> Result<DataDescription, FieldError> {
+        let index = self.field_index(name).ok_or_else(|| FieldError::UnknownField(name.to_string()))?;
+        self.remove_field(index)
     }
 
-    pub fn remove_field(&mut self, index: usize) {
-        if index >= self.description.len() {
-            return;
+    pub fn remove_field(&mut self, index: usize) -> Result<DataDescription, FieldError> {
+        self.check_index(index)?;
+
+        if let Some(user) = self.used_by(index) {
+            return Err(FieldError::InUse { field: self.description[index].name.clone(), by: user.name.clone() });
         }
 
-        self.description.remove(index);
+        let desc = self.description.remove(index);
 
         for value in self.values.values_mut() {
             value.remove(index);
         }
+
+        self.check_invariant();
+        Ok(desc)
     }
 
-    pub fn move_field_up(&mut self, index: usize) {
-        if index == 0 || self.description.len() == 0 {
-            return;
+    // Moving the first field up or the last field down does nothing
+    pub fn move_field_up(&mut self, index: usize) -> Result<(), FieldError> {
+        self.check_index(index)?;
+
+        if index > 0 {
+            self.swap_fields(index - 1, index);
         }
 
-        let (left, right) = self.description.split_at_mut(index);
-        std::mem::swap(&mut right[0], &mut left[index - 1]);
+        Ok(())
+    }
+
+    pub fn move_field_down(&mut self, index: usize) -> Result<(), FieldError> {
+        self.check_index(index)?;
 
-        for value in self.values.values_mut() {
-            let (left, right) = value.split_at_mut(index);
-            std::mem::swap(&mut right[0], &mut left[index - 1]);
+        if index + 1 < self.description.len() {
+            self.swap_fields(index, index + 1);
         }
+
+        Ok(())
+    }
+
+    // The first field with an expression using the field
+    fn used_by(&self, index: usize) -> Option<&DataDescription> {
+        let name = self.description[index].name.as_str();
+        self.description.iter().find(|x| x.expression.as_ref().map(|x| x.fields().contains(&name)).unwrap_or(false))
     }
 
-    pub fn move_field_down(&mut self, index: usize) {
-        if index == self.description.len() - 1 || self.description.len() == 0 {
-            return;
+    fn check_index(&self, index: usize) -> Result<(), FieldError> {
+        match index < self.description.len() {
+            true => Ok(()),
+            false => Err(FieldError::OutOfRange { index, len: self.description.len() }),
         }
+    }
 
-        let (left, right) = self.description.split_at_mut(index + 1);
-        std::mem::swap(&mut right[0], &mut left[index]);
+    fn swap_fields(&mut self, a: usize, b: usize) {
+        self.description.swap(a, b);
 
         for value in self.values.values_mut() {
-            let (left, right) = value.split_at_mut(index + 1);
-            std::mem::swap(&mut right[0], &mut left[index]);
+            value.swap(a, b);
         }
+
+        self.check_invariant();
     }
 
+    // Every record has exactly one value per field
+    fn check_invariant(&self) {
+        debug_assert!(self.values.values().all(|x| x.len() == self.description.len()));
+    }
+}
 
+impl<K: Hash + Eq> Default for DataTypeStorage<K> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
+#[derive(Clone, Debug)]
 pub struct DataStorage<K>  {
     pub data: HashMap<String, DataTypeStorage<K>>
 }
@@ -246,47 +589,228 @@ impl<K: Hash + Eq> DataStorage<K> {
     }
 }
 
-pub struct IndexGenerator {
-    max_index: Option<u32>,
-    available: Vec<u32>,
+impl<K: Hash + Eq> Default for DataStorage<K> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
-impl IndexGenerator {
-    pub fn new() -> IndexGenerator {
-        IndexGenerator {
-            max_index: None,
-            available: vec![],
+// Key of a record. Ids are random so records created on different branches of a project are
+// unlikely to collide when merged. Ids of removed records aren't tracked, so one could be drawn again
+// but with 53 random bits that is improbable
+pub type RecordId = u64;
+
+// Kept below 2^53 so ids survive JSON readers that store numbers as doubles
+const RECORD_ID_BITS: u32 = 53;
+
+impl DataTypeStorage<RecordId> {
+    pub fn new_id(&self) -> RecordId {
+        loop {
+            let id = rand::random::<RecordId>() >> (64 - RECORD_ID_BITS);
+
+            if !self.values.contains_key(&id) {
+                return id;
+            }
         }
     }
+}
 
-    pub fn next_index(&mut self) -> u32 {
-        if self.max_index.is_none() {
-            self.max_index = Some(0);
-            0
-        }
-        else if let Some(index) = self.available.pop() {
-            index
-        }
-        else {
-            let value = self.max_index.as_mut().unwrap();
-            *value += 1;
-            *value
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+    use super::*;
+
+    const NAMES: [&str; 6] = ["startup", "active", "recovery", "damage", "name", "hits"];
+
+    fn check(storage: &DataTypeStorage<u32>, names: &[String]) {
+        let described = storage.get_desc().iter().map(|x| x.name.clone()).collect::<Vec<_>>();
+        assert_eq!(described, names);
+
+        for key in storage.keys() {
+            assert_eq!(storage.get(key).unwrap().len(), storage.get_desc().len());
         }
     }
 
-    pub fn remove_index(&mut self, index: u32) {
-        if Some(index) == self.max_index && index == 0 {
-            self.max_index = None;
-            self.available.clear();
+    // Applies one random operation, keeping `names` as the field names the storage should have
+    fn step(rng: &mut StdRng, storage: &mut DataTypeStorage<u32>, names: &mut Vec<String>) {
+        let len = names.len();
+        // One past the end to also try fields that don't exist
+        let index = rng.gen_range(0, len + 1);
+        let name = NAMES[rng.gen_range(0, NAMES.len())].to_string();
+
+        match rng.gen_range(0, 9) {
+            0 => {
+                let dtype = DataType::ALL[rng.gen_range(0, DataType::ALL.len())];
+                match storage.add_field(name.clone(), dtype) {
+                    Ok(()) => names.push(name),
+                    Err(error) => assert_eq!(error, FieldError::DuplicateName(name)),
+                }
+            }
+            1 => match storage.remove_field(index) {
+                Ok(desc) => assert_eq!(desc.name, names.remove(index)),
+                Err(FieldError::OutOfRange { .. }) => assert_eq!(index, len),
+                Err(error) => assert!(matches!(error, FieldError::InUse { .. })),
+            },
+            2 => match storage.remove_field_with_name(&name) {
+                Ok(desc) => {
+                    assert_eq!(desc.name, name);
+                    names.retain(|x| *x != name);
+                }
+                Err(FieldError::UnknownField(unknown)) => assert!(!names.contains(&unknown)),
+                Err(error) => assert!(matches!(error, FieldError::InUse { .. })),
+            },
+            3 => match storage.rename_field(index, name.clone()) {
+                Ok(()) => names[index] = name,
+                Err(FieldError::OutOfRange { .. }) => assert_eq!(index, len),
+                Err(FieldError::DuplicateName(duplicate)) => assert!(names.contains(&duplicate)),
+                Err(error) => assert!(matches!(error, FieldError::InUse { .. })),
+            },
+            4 => match storage.move_field_up(index) {
+                Ok(()) if index > 0 => names.swap(index - 1, index),
+                Ok(()) => (),
+                Err(error) => assert_eq!(error, FieldError::OutOfRange { index, len }),
+            },
+            5 => match storage.move_field_down(index) {
+                Ok(()) if index + 1 < len => names.swap(index, index + 1),
+                Ok(()) => (),
+                Err(error) => assert_eq!(error, FieldError::OutOfRange { index, len }),
+            },
+            6 => {
+                let source = match rng.gen_range(0, 3) {
+                    0 => String::new(),
+                    1 => format!("{} + 1", name),
+                    _ => format!("{} * 2 + {}", name, NAMES[rng.gen_range(0, NAMES.len())]),
+                };
+
+                match storage.set_expression(index, &source) {
+                    Ok(()) => (),
+                    Err(FieldError::OutOfRange { .. }) => assert_eq!(index, len),
+                    Err(error) => assert!(matches!(error, FieldError::Expression { .. })),
+                }
+            }
+            7 => {
+                let key = rng.gen_range(0, 8);
+                match rng.gen::<bool>() {
+                    true => { storage.gen_new(key); }
+                    false => { storage.remove(&key); }
+                }
+            }
+            _ => {
+                let key = rng.gen_range(0, 8);
+                let value = match storage.get_desc().get(index) {
+                    Some(desc) => desc.dtype.to_default_value(),
+                    None => DataValue::Bool(true),
+                };
+
+                match storage.set(&key, index, value) {
+                    Ok(()) => assert!(storage.get(&key).is_some()),
+                    Err(FieldError::UnknownRecord) => assert!(storage.get(&key).is_none()),
+                    Err(FieldError::OutOfRange { .. }) => assert_eq!(index, len),
+                    Err(error) => assert!(matches!(error, FieldError::Computed(_))),
+                }
+            }
         }
-        else if Some(index) == self.max_index && index != 0 {
-            *self.max_index.as_mut().unwrap()  -= 1;
-            self.available.retain(|x| *x != index);
+    }
+
+    #[test]
+    fn random_field_operations_keep_values_and_description_aligned() {
+        for seed in 0..64 {
+            let mut rng = StdRng::from_seed([seed; 32]);
+            let mut storage = DataTypeStorage::new();
+            let mut names = vec![];
+
+            for _ in 0..200 {
+                step(&mut rng, &mut storage, &mut names);
+                check(&storage, &names);
+            }
         }
-        else if let Some(ref mut ind) = self.max_index {
-            if index < *ind && !self.available.contains(&index) {
-                self.available.push(index);
+    }
+
+    #[test]
+    fn remove_field_with_name_drops_the_description() {
+        let mut storage = DataTypeStorage::new();
+        storage.add_field("damage".to_string(), DataType::I32).unwrap();
+        storage.add_field("hits".to_string(), DataType::U32).unwrap();
+        storage.gen_new(0);
+
+        let desc = storage.remove_field_with_name("damage").unwrap();
+        assert_eq!(desc.name, "damage");
+        assert!(!storage.has_field_with_name("damage"));
+        check(&storage, &["hits".to_string()]);
+
+        assert_eq!(storage.remove_field_with_name("damage").err(), Some(FieldError::UnknownField("damage".to_string())));
+    }
+
+    #[test]
+    fn moving_fields_of_an_empty_description_is_out_of_range() {
+        let mut storage = DataTypeStorage::<u32>::new();
+
+        assert_eq!(storage.move_field_down(0), Err(FieldError::OutOfRange { index: 0, len: 0 }));
+        assert_eq!(storage.move_field_up(0), Err(FieldError::OutOfRange { index: 0, len: 0 }));
+        assert_eq!(storage.move_field_down(usize::max_value()), Err(FieldError::OutOfRange { index: usize::max_value(), len: 0 }));
+    }
+
+    #[test]
+    fn new_ids_are_unique_and_below_2_53() {
+        let mut rng = StdRng::from_seed([7; 32]);
+        let mut storage = DataTypeStorage::new();
+        let mut live = vec![];
+        let mut seen = std::collections::HashSet::new();
+
+        for _ in 0..10_000 {
+            let id = storage.new_id();
+            assert!(id < 1 << RECORD_ID_BITS);
+            assert!(seen.insert(id));
+
+            storage.gen_new(id);
+            live.push(id);
+
+            if rng.gen_range(0, 3) == 0 {
+                let removed = live.swap_remove(rng.gen_range(0, live.len()));
+                assert!(storage.remove(&removed).is_some());
             }
         }
+
+        assert_eq!(storage.len(), live.len());
+    }
+
+    #[test]
+    fn set_checks_the_record_and_type() {
+        let mut storage = DataTypeStorage::new();
+        storage.add_field("damage".to_string(), DataType::I32).unwrap();
+        storage.gen_new(0);
+
+        assert_eq!(storage.set(&1, 0, DataValue::I32(5)), Err(FieldError::UnknownRecord));
+        assert_eq!(
+            storage.set(&0, 0, DataValue::F32(5.0)),
+            Err(FieldError::TypeMismatch { field: "damage".to_string(), expected: DataType::I32, found: DataType::F32 }),
+        );
+
+        storage.set(&0, 0, DataValue::I32(5)).unwrap();
+        assert!(matches!(storage.get_field(&0, "damage"), Some(DataValue::I32(5))));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn expressions_that_depend_on_each_other_are_rejected() {
+        let mut storage = DataTypeStorage::<u32>::new();
+        storage.add_field("a".to_string(), DataType::F32).unwrap();
+        storage.add_field("b".to_string(), DataType::F32).unwrap();
+        storage.gen_new(0);
+
+        storage.set_expression(0, "b + 1").unwrap();
+        assert_eq!(storage.set_expression(1, "a * 2"), Err(FieldError::Expression {
+            field: "b".to_string(),
+            error: ExpressionError::Cycle(vec!["a".to_string(), "b".to_string(), "a".to_string()]),
+        }));
+        assert_eq!(storage.set_expression(0, "a"), Err(FieldError::Expression {
+            field: "a".to_string(),
+            error: ExpressionError::Cycle(vec!["a".to_string(), "a".to_string()]),
+        }));
+
+        // The rejected expressions are not kept
+        assert!(!storage.get_desc()[1].is_computed());
+        assert_eq!(storage.get_desc()[0].expression().map(|x| x.source()), Some("b + 1"));
+        storage.set_value(&0, 1, "2").unwrap();
+        assert!(matches!(storage.get_field(&0, "a"), Some(DataValue::F32(x)) if *x == 3.0));
+    }
+}
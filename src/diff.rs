@@ -0,0 +1,670 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
+use serde_json::{Value, json};
+use crate::{
+    category::{BoxCategory, Categories},
+    data::{DataDescription, DataTypeStorage, DataValue, RecordId},
+    frame::{AnimationData, DataBox, Frame, Marker},
+    project::Project,
+    project_file::data_value_to_json,
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Item {
+    Animation,
+    Category(BoxCategory),
+    Type(String),
+    Field { dtype: String, field: String },
+    Record { dtype: String, key: RecordId },
+    Box { dtype: String, key: RecordId },
+    Marker { dtype: String, key: RecordId },
+    Frame(usize),
+    Script(String),
+}
+
+impl fmt::Display for Item {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Item::Animation => write!(f, "animation"),
+            Item::Category(category) => write!(f, "category {}", category.name()),
+            Item::Type(dtype) => write!(f, "type '{}'", dtype),
+            Item::Field { dtype, field } => write!(f, "field '{}' of '{}'", field, dtype),
+            Item::Record { dtype, key } => write!(f, "record {} of '{}'", key, dtype),
+            Item::Box { dtype, key } => write!(f, "box {}:{}", dtype, key),
+            Item::Marker { dtype, key } => write!(f, "marker {}:{}", dtype, key),
+            Item::Frame(frame) => write!(f, "frame {}", frame),
+            Item::Script(name) => write!(f, "script '{}'", name),
+        }
+    }
+}
+
+impl Item {
+    fn to_json(&self) -> Value {
+        match self {
+            Item::Animation => json!({ "item": "animation" }),
+            Item::Category(category) => json!({ "item": "category", "category": category.name() }),
+            Item::Type(dtype) => json!({ "item": "type", "type": dtype }),
+            Item::Field { dtype, field } => json!({ "item": "field", "type": dtype, "field": field }),
+            Item::Record { dtype, key } => json!({ "item": "record", "type": dtype, "key": key }),
+            Item::Box { dtype, key } => json!({ "item": "box", "type": dtype, "key": key }),
+            Item::Marker { dtype, key } => json!({ "item": "marker", "type": dtype, "key": key }),
+            Item::Frame(frame) => json!({ "item": "frame", "frame": frame }),
+            Item::Script(name) => json!({ "item": "script", "script": name }),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    // Holds the old name, the item has the new one
+    Renamed(String),
+    Modified,
+}
+
+// A value of an item that differs between the two projects
+#[derive(Clone, Debug, PartialEq)]
+pub struct Property {
+    pub name: String,
+    pub old: Value,
+    pub new: Value,
+}
+
+impl fmt::Display for Property {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} -> {}", self.name, self.old, self.new)?;
+
+        match (self.old.as_f64(), self.new.as_f64()) {
+            (Some(old), Some(new)) => write!(f, " ({:+})", new - old),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Change {
+    // None for project wide items such as categories
+    pub animation: Option<String>,
+    pub item: Item,
+    pub kind: ChangeKind,
+    pub properties: Vec<Property>,
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(animation) = &self.animation {
+            write!(f, "{}: ", animation)?;
+        }
+
+        match &self.kind {
+            ChangeKind::Added => write!(f, "+ {}", self.item)?,
+            ChangeKind::Removed => write!(f, "- {}", self.item)?,
+            ChangeKind::Renamed(old) => write!(f, "> {} renamed from '{}'", self.item, old)?,
+            ChangeKind::Modified => write!(f, "~ {}", self.item)?,
+        }
+
+        for (i, property) in self.properties.iter().enumerate() {
+            write!(f, "{}{}", if i == 0 { ": " } else { ", " }, property)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Change {
+    pub fn to_json(&self) -> Value {
+        let mut value = self.item.to_json();
+        let (kind, renamed_from) = match &self.kind {
+            ChangeKind::Added => ("added", None),
+            ChangeKind::Removed => ("removed", None),
+            ChangeKind::Renamed(old) => ("renamed", Some(old.clone())),
+            ChangeKind::Modified => ("modified", None),
+        };
+
+        let properties = self.properties.iter()
+            .map(|x| json!({ "name": x.name, "old": x.old, "new": x.new }))
+            .collect::<Vec<_>>();
+
+        value["animation"] = json!(self.animation);
+        value["change"] = json!(kind);
+        value["renamed_from"] = json!(renamed_from);
+        value["properties"] = json!(properties);
+        value
+    }
+}
+
+pub fn to_text(changes: &[Change]) -> String {
+    changes.iter().map(|x| format!("{}\n", x)).collect()
+}
+
+pub fn to_json(changes: &[Change]) -> Value {
+    Value::Array(changes.iter().map(Change::to_json).collect())
+}
+
+// Collects properties that differ
+struct Properties(Vec<Property>);
+
+impl Properties {
+    fn compare<T: Into<Value> + PartialEq>(&mut self, name: &str, old: T, new: T) {
+        if old != new {
+            self.0.push(Property {
+                name: name.to_string(),
+                old: old.into(),
+                new: new.into(),
+            });
+        }
+    }
+
+    fn compare_value(&mut self, name: &str, old: &DataValue, new: &DataValue) {
+        let (old, new) = (data_value_to_json(old), data_value_to_json(new));
+        self.compare(name, old, new);
+    }
+}
+
+struct Differ {
+    changes: Vec<Change>,
+}
+
+impl Differ {
+    fn push(&mut self, animation: Option<&str>, item: Item, kind: ChangeKind, properties: Vec<Property>) {
+        self.changes.push(Change {
+            animation: animation.map(|x| x.to_string()),
+            item,
+            kind,
+            properties,
+        });
+    }
+
+    fn categories(&mut self, old: &Categories, new: &Categories) {
+        for category in BoxCategory::ALL.iter() {
+            let mut properties = Properties(vec![]);
+            properties.compare("layer", old.layer(*category), new.layer(*category));
+
+            for other in BoxCategory::ALL.iter() {
+                let name = format!("interacts_with_{}", other.name());
+                properties.compare(&name, old.interacts(*category, *other), new.interacts(*category, *other));
+            }
+
+            if !properties.0.is_empty() {
+                self.push(None, Item::Category(*category), ChangeKind::Modified, properties.0);
+            }
+        }
+    }
+
+    fn scripts(&mut self, old: &BTreeMap<String, String>, new: &BTreeMap<String, String>) {
+        for (name, source) in old.iter() {
+            match new.get(name) {
+                Some(new_source) if new_source != source => {
+                    let mut properties = Properties(vec![]);
+                    properties.compare("source", source.as_str(), new_source.as_str());
+                    self.push(None, Item::Script(name.clone()), ChangeKind::Modified, properties.0);
+                }
+                Some(_) => (),
+                None => self.push(None, Item::Script(name.clone()), ChangeKind::Removed, vec![]),
+            }
+        }
+
+        for name in new.keys().filter(|x| !old.contains_key(*x)) {
+            self.push(None, Item::Script(name.clone()), ChangeKind::Added, vec![]);
+        }
+    }
+
+    // Types that were removed and added with identical fields count as renamed, returns old to new names
+    fn types(&mut self, animation: &str, old: &AnimationData, new: &AnimationData) -> HashMap<String, String> {
+        let (old_data, new_data) = (&old.data().data, &new.data().data);
+        let same_fields = |a: &DataTypeStorage<RecordId>, b: &DataTypeStorage<RecordId>| {
+            a.get_desc().len() == b.get_desc().len() &&
+                a.get_desc().iter().zip(b.get_desc()).all(|(a, b)| a.name == b.name && a.dtype == b.dtype)
+        };
+
+        let mut removed = old_data.keys().filter(|x| !new_data.contains_key(*x)).cloned().collect::<Vec<_>>();
+        let mut added = new_data.keys().filter(|x| !old_data.contains_key(*x)).cloned().collect::<Vec<_>>();
+        removed.sort();
+        added.sort();
+
+        let mut names = old_data.keys().filter(|x| new_data.contains_key(*x)).map(|x| (x.clone(), x.clone())).collect::<HashMap<_, _>>();
+        for old_name in removed {
+            let renamed = added.iter().position(|x| same_fields(&old_data[&old_name], &new_data[x]));
+
+            match renamed {
+                Some(index) => {
+                    let new_name = added.remove(index);
+                    self.push(Some(animation), Item::Type(new_name.clone()), ChangeKind::Renamed(old_name.clone()), vec![]);
+                    names.insert(old_name, new_name);
+                }
+                None => self.push(Some(animation), Item::Type(old_name), ChangeKind::Removed, vec![]),
+            }
+        }
+
+        for name in added {
+            self.push(Some(animation), Item::Type(name), ChangeKind::Added, vec![]);
+        }
+
+        names
+    }
+
+    // A removed and an added field at the same position with the same type count as renamed,
+    // returns the index of the old field for every new field
+    fn fields(&mut self, animation: &str, dtype: &str, old: &DataTypeStorage<RecordId>, new: &DataTypeStorage<RecordId>) -> Vec<Option<usize>> {
+        let (old_desc, new_desc) = (old.get_desc(), new.get_desc());
+        let mut mapping = new_desc.iter().map(|x| old.field_index(&x.name)).collect::<Vec<_>>();
+
+        for (i, desc) in new_desc.iter().enumerate() {
+            if mapping[i].is_some() {
+                continue;
+            }
+
+            let renamed = old_desc.get(i)
+                .filter(|x| x.dtype == desc.dtype && new.field_index(&x.name).is_none())
+                .map(|_| i);
+
+            let item = Item::Field { dtype: dtype.to_string(), field: desc.name.clone() };
+            match renamed {
+                Some(index) => {
+                    self.push(Some(animation), item, ChangeKind::Renamed(old_desc[index].name.clone()), vec![]);
+                    mapping[i] = Some(index);
+                }
+                None => self.push(Some(animation), item, ChangeKind::Added, vec![]),
+            }
+        }
+
+        for (i, desc) in old_desc.iter().enumerate() {
+            if !mapping.contains(&Some(i)) {
+                self.push(Some(animation), Item::Field { dtype: dtype.to_string(), field: desc.name.clone() }, ChangeKind::Removed, vec![]);
+            }
+        }
+
+        for (i, desc) in new_desc.iter().enumerate() {
+            if let Some(old_index) = mapping[i] {
+                let expression = |x: &DataDescription| json!(x.expression().map(|x| x.source()));
+                let mut properties = Properties(vec![]);
+                properties.compare("type", old_desc[old_index].dtype.name(), desc.dtype.name());
+                properties.compare("expression", expression(&old_desc[old_index]), expression(desc));
+
+                if !properties.0.is_empty() {
+                    self.push(Some(animation), Item::Field { dtype: dtype.to_string(), field: desc.name.clone() }, ChangeKind::Modified, properties.0);
+                }
+            }
+        }
+
+        mapping
+    }
+
+    fn records(&mut self, animation: &str, dtype: &str, old: &DataTypeStorage<RecordId>, new: &DataTypeStorage<RecordId>, mapping: &[Option<usize>]) {
+        let mut keys = old.keys().chain(new.keys()).cloned().collect::<Vec<_>>();
+        keys.sort();
+        keys.dedup();
+
+        for key in keys {
+            let item = Item::Record { dtype: dtype.to_string(), key };
+
+            match (old.get(&key), new.get(&key)) {
+                (Some(_), None) => self.push(Some(animation), item, ChangeKind::Removed, vec![]),
+                (None, Some(_)) => self.push(Some(animation), item, ChangeKind::Added, vec![]),
+                (Some(old_values), Some(new_values)) => {
+                    let mut properties = Properties(vec![]);
+
+                    for (i, desc) in new.get_desc().iter().enumerate() {
+                        let old_value = mapping[i].and_then(|x| old_values.get(x));
+
+                        match (old_value, new_values.get(i)) {
+                            (Some(old_value), Some(new_value)) if old.get_desc()[mapping[i].unwrap()].dtype == desc.dtype => {
+                                properties.compare_value(&desc.name, old_value, new_value);
+                            }
+                            _ => (),
+                        }
+                    }
+
+                    if !properties.0.is_empty() {
+                        self.push(Some(animation), item, ChangeKind::Modified, properties.0);
+                    }
+                }
+                (None, None) => (),
+            }
+        }
+    }
+
+    fn frame(&mut self, animation: &str, index: usize, old: &Frame, new: &Frame) {
+        let mut properties = Properties(vec![]);
+        let (old_rect, new_rect) = (old.rect(), new.rect());
+
+        properties.compare("x", old_rect.translation.vector.x, new_rect.translation.vector.x);
+        properties.compare("y", old_rect.translation.vector.y, new_rect.translation.vector.y);
+        properties.compare("width", old_rect.rect.width, new_rect.rect.width);
+        properties.compare("height", old_rect.rect.height, new_rect.rect.height);
+        properties.compare("pivot_x", old.centre()[0], new.centre()[0]);
+        properties.compare("pivot_y", old.centre()[1], new.centre()[1]);
+        properties.compare("duration", old.duration(), new.duration());
+        properties.compare("flipped_x", old.flipped()[0], new.flipped()[0]);
+        properties.compare("flipped_y", old.flipped()[1], new.flipped()[1]);
+
+        let mut names = old.anchors().iter().chain(new.anchors()).map(|x| x.name.clone()).collect::<Vec<_>>();
+        names.sort();
+        names.dedup();
+
+        for name in names {
+            let position = |frame: &Frame| frame.anchor(&name).map(|x| {
+                let translation = x.transformation.translation.vector;
+                json!([translation.x, translation.y, x.transformation.rotation.angle()])
+            }).unwrap_or(Value::Null);
+
+            properties.compare(&format!("anchor_{}", name), position(old), position(new));
+        }
+
+        if !properties.0.is_empty() {
+            self.push(Some(animation), Item::Frame(index), ChangeKind::Modified, properties.0);
+        }
+    }
+
+    fn frames(&mut self, animation: &str, old: &AnimationData, new: &AnimationData) {
+        for index in 0..old.frames().len().max(new.frames().len()) {
+            match (old.frame(index), new.frame(index)) {
+                (Some(_), None) => self.push(Some(animation), Item::Frame(index), ChangeKind::Removed, vec![]),
+                (None, Some(_)) => self.push(Some(animation), Item::Frame(index), ChangeKind::Added, vec![]),
+                (Some(old_frame), Some(new_frame)) => self.frame(animation, index, old_frame, new_frame),
+                (None, None) => (),
+            }
+        }
+    }
+
+    fn data_box(&mut self, animation: &str, item: Item, old: (&AnimationData, usize), new: (&AnimationData, usize)) {
+        let (old_box, new_box): (&DataBox<_>, &DataBox<_>) = (&old.0.data_boxes()[old.1], &new.0.data_boxes()[new.1]);
+        let (old_translation, new_translation) = (old_box.shape.get_translation(), new_box.shape.get_translation());
+        let mut properties = Properties(vec![]);
+
+        properties.compare("category", old_box.category.name(), new_box.category.name());
+        properties.compare("parent", json!(old_box.parent), json!(new_box.parent));
+        properties.compare("x", old_translation.x, new_translation.x);
+        properties.compare("y", old_translation.y, new_translation.y);
+        properties.compare("width", old_box.shape.rect.width, new_box.shape.rect.width);
+        properties.compare("height", old_box.shape.rect.height, new_box.shape.rect.height);
+        properties.compare("angle", old_box.shape.get_rotation(), new_box.shape.get_rotation());
+        properties.compare("active", active_ranges(old.0, old.1), active_ranges(new.0, new.1));
+
+        if !properties.0.is_empty() {
+            self.push(Some(animation), item, ChangeKind::Modified, properties.0);
+        }
+    }
+
+    fn marker(&mut self, animation: &str, item: Item, old: &Marker<(String, RecordId)>, new: &Marker<(String, RecordId)>) {
+        let mut properties = Properties(vec![]);
+
+        properties.compare("name", old.name.as_str(), new.name.as_str());
+        properties.compare("start", old.start, new.start);
+        properties.compare("length", old.length, new.length);
+
+        if !properties.0.is_empty() {
+            self.push(Some(animation), item, ChangeKind::Modified, properties.0);
+        }
+    }
+
+    fn animation(&mut self, old: &AnimationData, new: &AnimationData) {
+        let name = new.name.as_str();
+        let mut properties = Properties(vec![]);
+        properties.compare("fps", old.fps(), new.fps());
+        properties.compare("sprite_sheet", json!(old.sprite_sheet), json!(new.sprite_sheet));
+        if !properties.0.is_empty() {
+            self.push(Some(name), Item::Animation, ChangeKind::Modified, properties.0);
+        }
+
+        let type_names = self.types(name, old, new);
+        let mut shared = type_names.iter().collect::<Vec<_>>();
+        shared.sort();
+
+        for (old_name, new_name) in shared {
+            let (old_storage, new_storage) = (&old.data().data[old_name], &new.data().data[new_name]);
+            let mapping = self.fields(name, new_name, old_storage, new_storage);
+            self.records(name, new_name, old_storage, new_storage, &mapping);
+        }
+
+        self.frames(name, old, new);
+
+        // Boxes and markers are matched by the record they use, going through renamed types
+        let renamed = |(dtype, key): &(String, RecordId)| (type_names.get(dtype).cloned().unwrap_or_else(|| dtype.clone()), *key);
+
+        let old_boxes = old.data_boxes().iter().map(|x| renamed(&x.data)).collect::<Vec<_>>();
+        let new_boxes = new.data_boxes().iter().map(|x| x.data.clone()).collect::<Vec<_>>();
+        let (pairs, removed, added) = match_references(&old_boxes, &new_boxes);
+
+        for (old_index, new_index) in pairs {
+            let (dtype, key) = new_boxes[new_index].clone();
+            self.data_box(name, Item::Box { dtype, key }, (old, old_index), (new, new_index));
+        }
+        for index in removed {
+            let (dtype, key) = old_boxes[index].clone();
+            self.push(Some(name), Item::Box { dtype, key }, ChangeKind::Removed, vec![]);
+        }
+        for index in added {
+            let (dtype, key) = new_boxes[index].clone();
+            self.push(Some(name), Item::Box { dtype, key }, ChangeKind::Added, vec![]);
+        }
+
+        let old_markers = old.markers().iter().map(|x| renamed(&x.data)).collect::<Vec<_>>();
+        let new_markers = new.markers().iter().map(|x| x.data.clone()).collect::<Vec<_>>();
+        let (pairs, removed, added) = match_references(&old_markers, &new_markers);
+
+        for (old_index, new_index) in pairs {
+            let (dtype, key) = new_markers[new_index].clone();
+            self.marker(name, Item::Marker { dtype, key }, &old.markers()[old_index], &new.markers()[new_index]);
+        }
+        for index in removed {
+            let (dtype, key) = old_markers[index].clone();
+            self.push(Some(name), Item::Marker { dtype, key }, ChangeKind::Removed, vec![]);
+        }
+        for index in added {
+            let (dtype, key) = new_markers[index].clone();
+            self.push(Some(name), Item::Marker { dtype, key }, ChangeKind::Added, vec![]);
+        }
+    }
+}
+
+// Frames the box is active on written as ranges, such as "0..3, 5..6"
+pub fn active_ranges(animation: &AnimationData, data_box: usize) -> String {
+    animation.activation_ranges(data_box).iter()
+        .map(|x| format!("{}..{}", x.start, x.end))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// Pairs up equal references in order, returning the pairs and the unmatched old and new indices
+pub fn match_references(old: &[(String, RecordId)], new: &[(String, RecordId)]) -> (Vec<(usize, usize)>, Vec<usize>, Vec<usize>) {
+    let mut used = vec![false; new.len()];
+    let mut pairs = vec![];
+    let mut removed = vec![];
+
+    for (i, reference) in old.iter().enumerate() {
+        match new.iter().enumerate().position(|(j, x)| !used[j] && x == reference) {
+            Some(j) => {
+                used[j] = true;
+                pairs.push((i, j));
+            }
+            None => removed.push(i),
+        }
+    }
+
+    let added = (0..new.len()).filter(|x| !used[*x]).collect();
+    (pairs, removed, added)
+}
+
+// Changes from `old` to `new`, animations are matched by name
+pub fn diff_projects(old: &Project, new: &Project) -> Vec<Change> {
+    let mut differ = Differ { changes: vec![] };
+    differ.categories(&old.categories, &new.categories);
+    differ.scripts(&old.scripts, &new.scripts);
+
+    for animation in old.animations.iter() {
+        match new.get_animation(&animation.name) {
+            Some(new_animation) => differ.animation(animation, new_animation),
+            None => differ.push(Some(&animation.name), Item::Animation, ChangeKind::Removed, vec![]),
+        }
+    }
+
+    for animation in new.animations.iter() {
+        if old.get_animation(&animation.name).is_none() {
+            differ.push(Some(&animation.name), Item::Animation, ChangeKind::Added, vec![]);
+        }
+    }
+
+    differ.changes
+}
+
+#[cfg(test)]
+mod tests {
+    use na::{Isometry2, Vector2};
+    use crate::{
+        data::DataType,
+        shapes::{AABB, BoundingBox, Cuboid},
+    };
+    use super::*;
+
+    // `Punch` with two frames, a `Hit` box active on the first and a `Sound` marker
+    fn project() -> Project {
+        let mut animation = AnimationData::new("Punch".to_string(), 60);
+        animation.push_frame(Frame::new(AABB::new(0, 0, 8, 8)));
+        animation.push_frame(Frame::new(AABB::new(8, 0, 8, 8)));
+
+        animation.new_data_type("Hit".to_string());
+        let storage = animation.data_mut().get_storage_mut("Hit").unwrap();
+        storage.add_field("damage".to_string(), DataType::F32).unwrap();
+        storage.add_field("hitstun".to_string(), DataType::I32).unwrap();
+
+        animation.new_data_type("Sound".to_string());
+        animation.data_mut().get_storage_mut("Sound").unwrap().add_field("file".to_string(), DataType::OwnedString).unwrap();
+
+        let shape = BoundingBox::new(Cuboid::new(4.0, 2.0), Isometry2::new(Vector2::new(1.0, 2.0), 0.0));
+        animation.new_data_box("Hit", shape, BoxCategory::Hitbox).unwrap();
+        animation.set_box_active(0, 0, true);
+        animation.new_marker("whoosh".to_string(), "Sound", 0, 0).unwrap();
+
+        let mut project = Project::new();
+        project.add_animation(animation);
+        project
+    }
+
+    fn lines(old: &Project, new: &Project) -> Vec<String> {
+        to_text(&diff_projects(old, new)).lines().map(|x| x.to_string()).collect()
+    }
+
+    fn box_key(project: &Project) -> RecordId {
+        project.animations[0].data_boxes()[0].data.1
+    }
+
+    fn marker_key(project: &Project) -> RecordId {
+        project.animations[0].markers()[0].data.1
+    }
+
+    fn rename_type(project: &mut Project, old: &str, new: &str) {
+        let animation = &mut project.animations[0];
+        let storage = animation.data_mut().data.remove(old).unwrap();
+        animation.data_mut().insert(new.to_string(), storage);
+
+        for data_box in animation.data_boxes_mut().iter_mut().filter(|x| x.data.0 == old) {
+            data_box.data.0 = new.to_string();
+        }
+        for marker in animation.markers_mut().iter_mut().filter(|x| x.data.0 == old) {
+            marker.data.0 = new.to_string();
+        }
+    }
+
+    #[test]
+    fn identical_projects_have_no_changes() {
+        let project = project();
+
+        assert!(diff_projects(&project, &project.clone()).is_empty());
+        assert_eq!(to_text(&[]), "");
+    }
+
+    #[test]
+    fn a_type_with_the_same_fields_is_renamed() {
+        let old = project();
+        let mut new = old.clone();
+        rename_type(&mut new, "Hit", "Strike");
+
+        assert_eq!(lines(&old, &new), ["Punch: > type 'Strike' renamed from 'Hit'"]);
+    }
+
+    #[test]
+    fn a_type_with_different_fields_is_removed_and_added() {
+        let old = project();
+        let mut new = old.clone();
+        rename_type(&mut new, "Sound", "Audio");
+        new.animations[0].data_mut().get_storage_mut("Audio").unwrap().add_field("volume".to_string(), DataType::F32).unwrap();
+
+        let key = marker_key(&old);
+        assert_eq!(lines(&old, &new), [
+            "Punch: - type 'Sound'".to_string(),
+            "Punch: + type 'Audio'".to_string(),
+            format!("Punch: - marker Sound:{}", key),
+            format!("Punch: + marker Audio:{}", key),
+        ]);
+    }
+
+    #[test]
+    fn a_field_in_the_same_place_with_the_same_type_is_renamed() {
+        let old = project();
+        let mut new = old.clone();
+        new.animations[0].data_mut().get_storage_mut("Hit").unwrap().rename_field(0, "power".to_string()).unwrap();
+
+        let changes = diff_projects(&old, &new);
+        assert_eq!(to_text(&changes), "Punch: > field 'power' of 'Hit' renamed from 'damage'\n");
+        assert_eq!(to_json(&changes), json!([{
+            "item": "field",
+            "type": "Hit",
+            "field": "power",
+            "animation": "Punch",
+            "change": "renamed",
+            "renamed_from": "damage",
+            "properties": [],
+        }]));
+    }
+
+    #[test]
+    fn a_field_replaced_by_one_of_another_type_is_removed_and_added() {
+        let old = project();
+        let mut new = old.clone();
+        let storage = new.animations[0].data_mut().get_storage_mut("Hit").unwrap();
+        storage.remove_field_with_name("hitstun").unwrap();
+        storage.add_field("effect".to_string(), DataType::OwnedString).unwrap();
+
+        assert_eq!(lines(&old, &new), [
+            "Punch: + field 'effect' of 'Hit'",
+            "Punch: - field 'hitstun' of 'Hit'",
+        ]);
+    }
+
+    #[test]
+    fn record_values_are_compared_through_renamed_fields() {
+        let old = project();
+        let mut new = old.clone();
+        let key = box_key(&old);
+        let storage = new.animations[0].data_mut().get_storage_mut("Hit").unwrap();
+        storage.rename_field(0, "power".to_string()).unwrap();
+        storage.set_value(&key, 0, "12.5").unwrap();
+
+        assert_eq!(lines(&old, &new), [
+            "Punch: > field 'power' of 'Hit' renamed from 'damage'".to_string(),
+            format!("Punch: ~ record {} of 'Hit': power 0.0 -> 12.5 (+12.5)", key),
+        ]);
+    }
+
+    #[test]
+    fn frames_boxes_and_markers_are_compared() {
+        let old = project();
+        let mut new = old.clone();
+        let animation = &mut new.animations[0];
+        animation.frame_mut(1).unwrap().set_duration(4);
+        animation.push_frame(Frame::new(AABB::new(16, 0, 8, 8)));
+        animation.set_box_active(0, 1, true);
+        animation.markers_mut()[0].start = 2;
+
+        assert_eq!(lines(&old, &new), [
+            "Punch: ~ frame 1: duration 1 -> 4 (+3)".to_string(),
+            "Punch: + frame 2".to_string(),
+            format!("Punch: ~ box Hit:{}: active \"0..1\" -> \"0..2\"", box_key(&old)),
+            format!("Punch: ~ marker Sound:{}: start 0 -> 2 (+2)", marker_key(&old)),
+        ]);
+    }
+}
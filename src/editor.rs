@@ -0,0 +1,162 @@
+use glium::Surface;
+use crate::{
+    export::registry::Registry,
+    frame,
+    project,
+    render,
+    state,
+    support,
+    ui,
+    window,
+};
+
+// Opens the editor window and runs until it is closed
+pub fn run(registry: &Registry) {
+    let mut events_loop = glium::glutin::EventsLoop::new();
+    let mut window = window::Window::from_builder(&mut events_loop, |window, context| {
+        (window, context.with_vsync(true))
+    }).unwrap();
+
+    let display = window.display().clone();
+    let canvas_renderer = render::CanvasRenderer::new(&display).unwrap();
+    let mut textures = render::image_render::Textures::new();
+
+    let canvas = state::canvas::Canvas::new(render::render_windows::RenderWindow::new(-0.4, -1.0, 1.4, 2.0));
+    let mut state = state::State::new(canvas);
+    let mut project = project::Project::new();
+    project.add_animation(frame::AnimationData::new("Animation".to_string(), 12));
+
+    let mut storage_editor = ui::storage_editor::StorageEditor::new(Default::default());
+    let mut canvas_editor = ui::canvas_editor::CanvasEditor::new();
+    let mut timeline_editor = ui::timeline_editor::TimelineEditor::new();
+    let mut category_editor = ui::category_editor::CategoryEditor::new();
+    let mut frame_data_panel = ui::frame_data_panel::FrameDataPanel::new();
+    let mut sandbox_editor = ui::sandbox_editor::SandboxEditor::new();
+    let mut box_editor = ui::box_editor::BoxEditor::new();
+    let mut export_panel = ui::export_panel::ExportPanel::new();
+    let mut profile_editor = ui::profile_editor::ProfileEditor::new();
+    let mut project_panel = ui::project_panel::ProjectPanel::new();
+    let mut problems_panel = ui::problems_panel::ProblemsPanel::new();
+    let mut search_panel = ui::search_panel::SearchPanel::new();
+    let mut script_panel = ui::script_panel::ScriptPanel::new();
+
+    support::run(&mut window, &mut events_loop, |target, ui, input, dt, no_render | {
+        let (width, height) = target.get_dimensions();
+        let dims = (width as f32, height as f32);
+        export_panel.update_watch(&project, registry, project_panel.path());
+
+        let animation = &mut project.animations[state.current_animation];
+        state.update(input, dims, ui.want_capture_mouse());
+        state.update_playback(animation, dt);
+        state.update_tools(input, dims, animation);
+
+        // Types of the current animation, records are edited from the box editor
+        ui.window(im_str!("Data Types"))
+            .position((300.0, 0.0), imgui::ImGuiCond::Appearing)
+            .size((250.0, 200.0), imgui::ImGuiCond::Appearing)
+            .build(|| {
+                storage_editor.create_ui(animation.data_mut(), ui);
+            });
+
+        ui.window(im_str!("Canvas"))
+            .position((0.0, 200.0), imgui::ImGuiCond::Appearing)
+            .size((300.0, 200.0), imgui::ImGuiCond::Appearing)
+            .build(|| {
+                canvas_editor.create_ui(&mut state, animation, &mut textures, &display, ui);
+            });
+
+        ui.window(im_str!("Timeline"))
+            .position((0.0, 400.0), imgui::ImGuiCond::Appearing)
+            .size((300.0, 200.0), imgui::ImGuiCond::Appearing)
+            .build(|| {
+                timeline_editor.create_ui(&mut state, animation, ui);
+            });
+
+        ui.window(im_str!("Boxes"))
+            .position((550.0, 0.0), imgui::ImGuiCond::Appearing)
+            .size((250.0, 300.0), imgui::ImGuiCond::Appearing)
+            .build(|| {
+                box_editor.create_ui(&mut state, animation, ui);
+            });
+
+        ui.window(im_str!("Categories"))
+            .position((0.0, 600.0), imgui::ImGuiCond::Appearing)
+            .size((300.0, 200.0), imgui::ImGuiCond::Appearing)
+            .build(|| {
+                category_editor.create_ui(&mut state, &mut project.categories, ui);
+            });
+
+        ui.window(im_str!("Sandbox"))
+            .position((300.0, 350.0), imgui::ImGuiCond::Appearing)
+            .size((250.0, 250.0), imgui::ImGuiCond::Appearing)
+            .build(|| {
+                sandbox_editor.create_ui(&mut state, &project, ui);
+            });
+
+        ui.window(im_str!("Export"))
+            .position((550.0, 300.0), imgui::ImGuiCond::Appearing)
+            .size((250.0, 150.0), imgui::ImGuiCond::Appearing)
+            .build(|| {
+                export_panel.create_ui(&mut project, registry, ui);
+            });
+
+        ui.window(im_str!("Export Profiles"))
+            .position((1150.0, 0.0), imgui::ImGuiCond::Appearing)
+            .size((300.0, 350.0), imgui::ImGuiCond::Appearing)
+            .build(|| {
+                profile_editor.create_ui(&mut project, ui);
+            });
+
+        ui.window(im_str!("Project"))
+            .position((550.0, 450.0), imgui::ImGuiCond::Appearing)
+            .size((250.0, 120.0), imgui::ImGuiCond::Appearing)
+            .build(|| {
+                project_panel.create_ui(&mut state, &mut project, ui);
+            });
+
+        ui.window(im_str!("Problems"))
+            .position((800.0, 0.0), imgui::ImGuiCond::Appearing)
+            .size((350.0, 250.0), imgui::ImGuiCond::Appearing)
+            .build(|| {
+                problems_panel.create_ui(&mut state, &mut project, ui);
+            });
+
+        ui.window(im_str!("Scripts"))
+            .position((800.0, 450.0), imgui::ImGuiCond::Appearing)
+            .size((350.0, 300.0), imgui::ImGuiCond::Appearing)
+            .build(|| {
+                script_panel.create_ui(&mut project, ui);
+            });
+
+        let animation = &project.animations[state.current_animation];
+        ui.window(im_str!("Search"))
+            .position((800.0, 250.0), imgui::ImGuiCond::Appearing)
+            .size((350.0, 200.0), imgui::ImGuiCond::Appearing)
+            .build(|| {
+                search_panel.create_ui(&mut state, animation, ui);
+            });
+
+        let animation = &project.animations[state.current_animation];
+        ui.window(im_str!("Frame Data"))
+            .position((300.0, 200.0), imgui::ImGuiCond::Appearing)
+            .size((200.0, 150.0), imgui::ImGuiCond::Appearing)
+            .build(|| {
+                frame_data_panel.create_ui(animation, ui);
+            });
+
+        target.clear_color(1.0, 1.0, 1.0, 0.0);
+        if !no_render {
+            let result = match state.sandbox.enabled {
+                true => state.canvas.render_sandbox(target, &canvas_renderer, &textures, &project, &state.sandbox, dims),
+                false => state.canvas.render(target, &canvas_renderer, &textures, &project.categories, animation, state.current_frame, dims),
+            };
+
+            if let Err(error) = result {
+                eprintln!("Failed to render canvas: {}", error);
+            }
+        }
+
+        true
+    });
+
+}
@@ -0,0 +1,447 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs,
+    hash::Hash,
+    path::{Path, PathBuf},
+};
+use crate::{
+    data::{DataType, DataTypeStorage, DataValue, RecordId},
+    frame::AnimationData,
+    project::Project,
+    shapes::{BoundingBox, Cuboid},
+};
+use super::{ExportOptions, ExportSpace, box_shape, file_names, frame_flipped, frame_pivot, to_export_space};
+
+// Every multi byte value is little endian. The layout of each section is described by the
+// structs emitted by `binary_codegen`, so change both together and bump `VERSION`
+pub const MAGIC: [u8; 4] = *b"FDAT";
+pub const VERSION: u16 = 1;
+pub const NONE: u32 = u32::max_value();
+
+pub const FLAG_MIRRORED: u16 = 1;
+pub const FLAG_IMAGE_SPACE: u16 = 1 << 1;
+pub const FRAME_FLIPPED_X: u32 = 1;
+pub const FRAME_FLIPPED_Y: u32 = 1 << 1;
+
+pub const HEADER_SIZE: u32 = 96;
+pub const STRING_SIZE: u32 = 8;
+pub const TYPE_SIZE: u32 = 28;
+pub const FIELD_SIZE: u32 = 12;
+pub const FRAME_SIZE: u32 = 40;
+pub const ANCHOR_SIZE: u32 = 16;
+pub const BOX_SIZE: u32 = 24;
+pub const SHAPE_SIZE: u32 = 24;
+pub const MARKER_SIZE: u32 = 20;
+
+pub fn dtype_id(dtype: DataType) -> u32 {
+    match dtype {
+        DataType::F32 => 0,
+        DataType::I32 => 1,
+        DataType::U32 => 2,
+        DataType::Char => 3,
+        DataType::Bool => 4,
+        DataType::OwnedString => 5,
+    }
+}
+
+// Chars are stored as their code point, strings as an index into the string table
+pub fn dtype_size(dtype: DataType) -> u32 {
+    match dtype {
+        DataType::Bool => 1,
+        _ => 4,
+    }
+}
+
+fn align_to(value: u32, align: u32) -> u32 {
+    (value + align - 1) / align * align
+}
+
+#[derive(Clone, Debug)]
+pub struct FieldLayout {
+    pub name: String,
+    pub dtype: DataType,
+    pub offset: u32,
+}
+
+// Fields in description order with C alignment rules so a `#[repr(C)]` struct matches it
+#[derive(Clone, Debug)]
+pub struct RecordLayout {
+    pub fields: Vec<FieldLayout>,
+    pub size: u32,
+    pub align: u32,
+}
+
+impl RecordLayout {
+    pub fn new<K: Hash + Eq>(storage: &DataTypeStorage<K>) -> RecordLayout {
+        let mut fields = vec![];
+        let mut offset = 0;
+        let mut align = 1;
+
+        for desc in storage.get_desc() {
+            let size = dtype_size(desc.dtype);
+            offset = align_to(offset, size);
+            align = align.max(size);
+
+            fields.push(FieldLayout {
+                name: desc.name.clone(),
+                dtype: desc.dtype,
+                offset,
+            });
+            offset += size;
+        }
+
+        RecordLayout {
+            fields,
+            size: align_to(offset, align),
+            align,
+        }
+    }
+}
+
+struct Buffer {
+    bytes: Vec<u8>,
+}
+
+impl Buffer {
+    fn u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    fn u16(&mut self, value: u16) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn i32(&mut self, value: i32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn f32(&mut self, value: f32) {
+        self.bytes.extend_from_slice(&value.to_bits().to_le_bytes());
+    }
+
+    fn pad_to(&mut self, align: u32) {
+        while self.bytes.len() as u32 % align != 0 {
+            self.bytes.push(0);
+        }
+    }
+
+    fn position(&self) -> u32 {
+        self.bytes.len() as u32
+    }
+}
+
+// Strings are deduplicated and indexed in the order they are first used
+struct StringTable {
+    strings: Vec<String>,
+    indices: HashMap<String, u32>,
+}
+
+impl StringTable {
+    fn new() -> StringTable {
+        StringTable {
+            strings: vec![],
+            indices: HashMap::new(),
+        }
+    }
+
+    fn index(&mut self, value: &str) -> u32 {
+        if let Some(index) = self.indices.get(value) {
+            return *index;
+        }
+
+        let index = self.strings.len() as u32;
+        self.strings.push(value.to_string());
+        self.indices.insert(value.to_string(), index);
+
+        index
+    }
+
+    fn optional(&mut self, value: Option<&str>) -> u32 {
+        value.map(|x| self.index(x)).unwrap_or(NONE)
+    }
+}
+
+// Records of a single type, renumbered from 0 in the order they are referenced
+struct TypeRecords<'a> {
+    name: &'a str,
+    storage: &'a DataTypeStorage<RecordId>,
+    layout: RecordLayout,
+    keys: Vec<RecordId>,
+}
+
+struct Records<'a> {
+    types: Vec<TypeRecords<'a>>,
+}
+
+impl<'a> Records<'a> {
+    // Returns (type index, record index) for the record, NONE for both if the type does not exist
+    fn index(&mut self, animation: &'a AnimationData, (dtype, key): &(String, RecordId)) -> (u32, u32) {
+        let type_index = match self.types.iter().position(|x| x.name == dtype.as_str()) {
+            Some(index) => index,
+            None => match animation.data().data.get_key_value(dtype.as_str()) {
+                Some((name, storage)) => {
+                    self.types.push(TypeRecords {
+                        name: name.as_str(),
+                        storage,
+                        layout: RecordLayout::new(storage),
+                        keys: vec![],
+                    });
+                    self.types.len() - 1
+                }
+                None => return (NONE, NONE),
+            },
+        };
+
+        let records = &mut self.types[type_index];
+        let record = match records.keys.iter().position(|x| x == key) {
+            Some(index) => index,
+            None => {
+                records.keys.push(*key);
+                records.keys.len() - 1
+            }
+        };
+
+        (type_index as u32, record as u32)
+    }
+}
+
+fn write_record(buffer: &mut Buffer, strings: &mut StringTable, layout: &RecordLayout, values: Option<&[DataValue]>) {
+    let start = buffer.position();
+
+    for (i, field) in layout.fields.iter().enumerate() {
+        while buffer.position() < start + field.offset {
+            buffer.u8(0);
+        }
+
+        let default = field.dtype.to_default_value();
+        let value = values.and_then(|x| x.get(i)).unwrap_or(&default);
+
+        match value {
+            DataValue::F32(val) => buffer.f32(*val),
+            DataValue::I32(val) => buffer.i32(*val),
+            DataValue::U32(val) => buffer.u32(*val),
+            DataValue::Char(val) => buffer.u32(*val as u32),
+            DataValue::Bool(val) => buffer.u8(*val as u8),
+            DataValue::OwnedString(val) => {
+                let index = strings.index(val);
+                buffer.u32(index);
+            }
+        }
+    }
+
+    while buffer.position() < start + layout.size {
+        buffer.u8(0);
+    }
+}
+
+pub fn export_animation(animation: &AnimationData, options: &ExportOptions) -> Vec<u8> {
+    let mut strings = StringTable::new();
+    let mut records = Records { types: vec![] };
+
+    let name = strings.index(&animation.name);
+    let sprite_sheet = strings.optional(animation.sprite_sheet.as_ref().map(|x| x.as_str()));
+
+    let mut frames = Buffer { bytes: vec![] };
+    let mut anchors = Buffer { bytes: vec![] };
+    let mut anchor_count = 0;
+
+    for frame in animation.frames() {
+        let rect = frame.rect();
+        let pivot = frame_pivot(frame, options);
+        let flipped = frame_flipped(frame, options);
+        let flags = (if flipped[0] { FRAME_FLIPPED_X } else { 0 }) | (if flipped[1] { FRAME_FLIPPED_Y } else { 0 });
+
+        frames.u32(rect.translation.vector.x);
+        frames.u32(rect.translation.vector.y);
+        frames.u32(rect.rect.width);
+        frames.u32(rect.rect.height);
+        frames.u32(pivot[0]);
+        frames.u32(pivot[1]);
+        frames.u32(frame.duration() as u32);
+        frames.u32(flags);
+        frames.u32(anchor_count);
+        frames.u32(frame.anchors().len() as u32);
+
+        for anchor in frame.anchors() {
+            let shape = BoundingBox::new(Cuboid::new(0.0, 0.0), anchor.transformation);
+            let shape = to_export_space(&shape, frame, options);
+            let translation = shape.get_translation();
+
+            anchors.u32(strings.index(&anchor.name));
+            anchors.f32(translation.x);
+            anchors.f32(translation.y);
+            anchors.f32(shape.get_rotation());
+            anchor_count += 1;
+        }
+    }
+
+    let mut boxes = Buffer { bytes: vec![] };
+    let mut shapes = Buffer { bytes: vec![] };
+    let mut box_count = 0;
+    let mut shape_count = 0;
+
+    // Grouped by category so readers can take a contiguous run of a single category
+    let mut ordered = animation.data_boxes().iter().enumerate().collect::<Vec<_>>();
+    ordered.sort_by_key(|(index, data_box)| (data_box.category, *index));
+
+    for (index, data_box) in ordered {
+        let (type_index, record) = records.index(animation, &data_box.data);
+        let first_shape = shape_count;
+
+        for range in animation.activation_ranges(index) {
+            for frame in range {
+                if let Some(shape) = box_shape(animation, data_box, frame, options) {
+                    let translation = shape.get_translation();
+
+                    shapes.u32(frame as u32);
+                    shapes.f32(translation.x);
+                    shapes.f32(translation.y);
+                    shapes.f32(shape.rect.width);
+                    shapes.f32(shape.rect.height);
+                    shapes.f32(shape.get_rotation());
+                    shape_count += 1;
+                }
+            }
+        }
+
+        boxes.u32(data_box.category.index() as u32);
+        boxes.u32(type_index);
+        boxes.u32(record);
+        boxes.u32(strings.optional(data_box.parent.as_ref().map(|x| x.as_str())));
+        boxes.u32(first_shape);
+        boxes.u32(shape_count - first_shape);
+        box_count += 1;
+    }
+
+    let mut markers = Buffer { bytes: vec![] };
+    for marker in animation.markers() {
+        let (type_index, record) = records.index(animation, &marker.data);
+
+        markers.u32(strings.index(&marker.name));
+        markers.u32(marker.start);
+        markers.u32(marker.length);
+        markers.u32(type_index);
+        markers.u32(record);
+    }
+
+    // Records go in their own block, each type starting on its own alignment
+    let mut record_data = Buffer { bytes: vec![] };
+    let mut record_offsets = vec![];
+    for type_records in records.types.iter() {
+        record_data.pad_to(4);
+        record_offsets.push(record_data.position());
+
+        for key in type_records.keys.iter() {
+            write_record(&mut record_data, &mut strings, &type_records.layout, type_records.storage.get(key));
+        }
+    }
+
+    let mut types = Buffer { bytes: vec![] };
+    let mut fields = Buffer { bytes: vec![] };
+    let mut field_count = 0;
+    let mut type_names = vec![];
+    for type_records in records.types.iter() {
+        type_names.push(strings.index(type_records.name));
+
+        for field in type_records.layout.fields.iter() {
+            fields.u32(strings.index(&field.name));
+            fields.u32(dtype_id(field.dtype));
+            fields.u32(field.offset);
+        }
+    }
+
+    let mut string_entries = Buffer { bytes: vec![] };
+    let mut string_data = Buffer { bytes: vec![] };
+    for value in strings.strings.iter() {
+        string_entries.u32(string_data.position());
+        string_entries.u32(value.len() as u32);
+        string_data.bytes.extend_from_slice(value.as_bytes());
+    }
+    string_data.pad_to(4);
+
+    // Everything is a multiple of 4 bytes so sections can be laid out back to back
+    let strings_offset = HEADER_SIZE;
+    let string_data_offset = strings_offset + string_entries.position();
+    let types_offset = string_data_offset + string_data.position();
+    let fields_offset = types_offset + TYPE_SIZE * records.types.len() as u32;
+    let frames_offset = fields_offset + fields.position();
+    let anchors_offset = frames_offset + frames.position();
+    let boxes_offset = anchors_offset + anchors.position();
+    let shapes_offset = boxes_offset + boxes.position();
+    let markers_offset = shapes_offset + shapes.position();
+    let record_data_offset = markers_offset + markers.position();
+
+    for (i, type_records) in records.types.iter().enumerate() {
+        types.u32(type_names[i]);
+        types.u32(field_count);
+        types.u32(type_records.layout.fields.len() as u32);
+        types.u32(type_records.layout.size);
+        types.u32(type_records.layout.align);
+        types.u32(type_records.keys.len() as u32);
+        types.u32(record_data_offset + record_offsets[i]);
+        field_count += type_records.layout.fields.len() as u32;
+    }
+
+    let mut flags = 0;
+    if options.mirrored {
+        flags |= FLAG_MIRRORED;
+    }
+    if options.space == ExportSpace::Image {
+        flags |= FLAG_IMAGE_SPACE;
+    }
+
+    let mut output = Buffer { bytes: Vec::with_capacity((record_data_offset + record_data.position()) as usize) };
+    output.bytes.extend_from_slice(&MAGIC);
+    output.u16(VERSION);
+    output.u16(flags);
+    output.u32(animation.fps() as u32);
+    output.u32(name);
+    output.u32(sprite_sheet);
+    output.u32(strings.strings.len() as u32);
+    output.u32(strings_offset);
+    output.u32(string_data_offset);
+    output.u32(records.types.len() as u32);
+    output.u32(types_offset);
+    output.u32(field_count);
+    output.u32(fields_offset);
+    output.u32(animation.frames().len() as u32);
+    output.u32(frames_offset);
+    output.u32(anchor_count);
+    output.u32(anchors_offset);
+    output.u32(box_count);
+    output.u32(boxes_offset);
+    output.u32(shape_count);
+    output.u32(shapes_offset);
+    output.u32(animation.markers().len() as u32);
+    output.u32(markers_offset);
+    output.u32(record_data_offset);
+    output.u32(record_data.position());
+    debug_assert_eq!(output.position(), HEADER_SIZE);
+
+    for section in [string_entries, string_data, types, fields, frames, anchors, boxes, shapes, markers, record_data].iter() {
+        output.bytes.extend_from_slice(&section.bytes);
+    }
+
+    output.bytes
+}
+
+// Writes `<name>.fdat` for every animation, returning the written paths. Names are made into file names
+// with `file_names`
+pub fn write_project(project: &Project, directory: &Path, options: &ExportOptions) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let names = file_names(project, &[])?;
+    fs::create_dir_all(directory)?;
+    let mut written = vec![];
+
+    for (animation, name) in project.animations.iter().zip(names.iter()) {
+        let path = directory.join(format!("{}.fdat", name));
+        fs::write(&path, export_animation(animation, options))?;
+        written.push(path);
+    }
+
+    Ok(written)
+}
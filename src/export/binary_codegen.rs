@@ -0,0 +1,371 @@
+use std::{
+    error::Error,
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+use crate::{
+    data::DataType,
+    project::Project,
+};
+use super::{
+    identifier, project_types, unique_identifiers,
+    binary::{self, RecordLayout},
+    type_codegen::RUST_KEYWORDS,
+};
+
+// C11 keywords and the ones C23 adds
+const C_KEYWORDS: [&str; 47] = [
+    "auto", "break", "case", "char", "const", "continue", "default", "do", "double", "else", "enum",
+    "extern", "float", "for", "goto", "if", "inline", "int", "long", "register", "restrict", "return",
+    "short", "signed", "sizeof", "static", "struct", "switch", "typedef", "union", "unsigned", "void",
+    "volatile", "while", "_Alignas", "_Alignof", "_Atomic", "_Bool", "_Complex", "_Generic",
+    "_Imaginary", "_Noreturn", "_Static_assert", "_Thread_local", "bool", "true", "false",
+];
+
+// (name, rust type, c type) of every field of the fixed sections, in file order
+const HEADER: [(&str, &str, &str); 25] = [
+    ("magic", "[u8; 4]", "uint8_t magic[4]"),
+    ("version", "u16", "uint16_t"),
+    ("flags", "u16", "uint16_t"),
+    ("fps", "u32", "uint32_t"),
+    ("name", "u32", "uint32_t"),
+    ("sprite_sheet", "u32", "uint32_t"),
+    ("string_count", "u32", "uint32_t"),
+    ("strings_offset", "u32", "uint32_t"),
+    ("string_data_offset", "u32", "uint32_t"),
+    ("type_count", "u32", "uint32_t"),
+    ("types_offset", "u32", "uint32_t"),
+    ("field_count", "u32", "uint32_t"),
+    ("fields_offset", "u32", "uint32_t"),
+    ("frame_count", "u32", "uint32_t"),
+    ("frames_offset", "u32", "uint32_t"),
+    ("anchor_count", "u32", "uint32_t"),
+    ("anchors_offset", "u32", "uint32_t"),
+    ("box_count", "u32", "uint32_t"),
+    ("boxes_offset", "u32", "uint32_t"),
+    ("shape_count", "u32", "uint32_t"),
+    ("shapes_offset", "u32", "uint32_t"),
+    ("marker_count", "u32", "uint32_t"),
+    ("markers_offset", "u32", "uint32_t"),
+    ("record_data_offset", "u32", "uint32_t"),
+    ("record_data_size", "u32", "uint32_t"),
+];
+
+fn u32_fields(names: &[&'static str]) -> Vec<(&'static str, &'static str, &'static str)> {
+    names.iter().map(|x| (*x, "u32", "uint32_t")).collect()
+}
+
+fn shape_fields(names: &[&'static str]) -> Vec<(&'static str, &'static str, &'static str)> {
+    names.iter().enumerate()
+        .map(|(i, x)| if i == 0 { (*x, "u32", "uint32_t") } else { (*x, "f32", "float") })
+        .collect()
+}
+
+// (struct name, size, fields) of every fixed size section
+fn sections() -> Vec<(&'static str, u32, Vec<(&'static str, &'static str, &'static str)>)> {
+    vec![
+        ("Header", binary::HEADER_SIZE, HEADER.to_vec()),
+        ("StringEntry", binary::STRING_SIZE, u32_fields(&["offset", "length"])),
+        ("TypeEntry", binary::TYPE_SIZE, u32_fields(&["name", "first_field", "field_count", "record_size", "record_align", "record_count", "records_offset"])),
+        ("FieldEntry", binary::FIELD_SIZE, u32_fields(&["name", "dtype", "offset"])),
+        ("FrameEntry", binary::FRAME_SIZE, u32_fields(&["x", "y", "width", "height", "pivot_x", "pivot_y", "duration", "flags", "first_anchor", "anchor_count"])),
+        ("AnchorEntry", binary::ANCHOR_SIZE, shape_fields(&["name", "x", "y", "angle"])),
+        ("BoxEntry", binary::BOX_SIZE, u32_fields(&["category", "type_index", "record", "parent", "first_shape", "shape_count"])),
+        ("ShapeEntry", binary::SHAPE_SIZE, shape_fields(&["frame", "x", "y", "width", "height", "angle"])),
+        ("MarkerEntry", binary::MARKER_SIZE, u32_fields(&["name", "start", "length", "type_index", "record"])),
+    ]
+}
+
+fn rust_type(dtype: DataType) -> &'static str {
+    match dtype {
+        DataType::F32 => "f32",
+        DataType::I32 => "i32",
+        DataType::U32 => "u32",
+        DataType::Char => "u32",
+        DataType::Bool => "u8",
+        DataType::OwnedString => "u32",
+    }
+}
+
+fn c_type(dtype: DataType) -> &'static str {
+    match dtype {
+        DataType::F32 => "float",
+        DataType::I32 => "int32_t",
+        DataType::U32 => "uint32_t",
+        DataType::Char => "uint32_t",
+        DataType::Bool => "uint8_t",
+        DataType::OwnedString => "uint32_t",
+    }
+}
+
+fn field_comment(dtype: DataType) -> &'static str {
+    match dtype {
+        DataType::Char => " // Unicode code point",
+        DataType::Bool => " // 0 or 1",
+        DataType::OwnedString => " // Index into the string table",
+        _ => "",
+    }
+}
+
+const RUST_READER: &str = r#"
+// Borrows a file produced by the exporter, this assumes a little endian target
+pub struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    // The bytes must be aligned to 4, which is the case for a `Vec<u32>` based buffer
+    pub fn new(bytes: &'a [u8]) -> Option<Reader<'a>> {
+        if bytes.len() < std::mem::size_of::<Header>() || bytes.as_ptr() as usize % 4 != 0 {
+            return None;
+        }
+
+        let reader = Reader { bytes };
+        let header = reader.header();
+        if header.magic != MAGIC || header.version != VERSION {
+            return None;
+        }
+
+        Some(reader)
+    }
+
+    pub fn header(&self) -> &'a Header {
+        unsafe { &*(self.bytes.as_ptr() as *const Header) }
+    }
+
+    // Empty if the section does not fit in the file
+    fn section<T>(&self, offset: u32, count: u32) -> &'a [T] {
+        let size = std::mem::size_of::<T>() * count as usize;
+        if offset as usize + size > self.bytes.len() {
+            return &[];
+        }
+
+        unsafe { std::slice::from_raw_parts(self.bytes.as_ptr().add(offset as usize) as *const T, count as usize) }
+    }
+
+    pub fn string(&self, index: u32) -> Option<&'a str> {
+        let header = self.header();
+        let entry = self.section::<StringEntry>(header.strings_offset, header.string_count).get(index as usize)?;
+        let start = (header.string_data_offset + entry.offset) as usize;
+        let bytes = self.bytes.get(start..start + entry.length as usize)?;
+
+        std::str::from_utf8(bytes).ok()
+    }
+
+    pub fn types(&self) -> &'a [TypeEntry] {
+        self.section(self.header().types_offset, self.header().type_count)
+    }
+
+    pub fn fields(&self) -> &'a [FieldEntry] {
+        self.section(self.header().fields_offset, self.header().field_count)
+    }
+
+    pub fn frames(&self) -> &'a [FrameEntry] {
+        self.section(self.header().frames_offset, self.header().frame_count)
+    }
+
+    pub fn anchors(&self) -> &'a [AnchorEntry] {
+        self.section(self.header().anchors_offset, self.header().anchor_count)
+    }
+
+    pub fn boxes(&self) -> &'a [BoxEntry] {
+        self.section(self.header().boxes_offset, self.header().box_count)
+    }
+
+    pub fn shapes(&self) -> &'a [ShapeEntry] {
+        self.section(self.header().shapes_offset, self.header().shape_count)
+    }
+
+    pub fn markers(&self) -> &'a [MarkerEntry] {
+        self.section(self.header().markers_offset, self.header().marker_count)
+    }
+
+    pub fn type_index(&self, name: &str) -> Option<u32> {
+        self.types().iter().position(|x| self.string(x.name) == Some(name)).map(|x| x as u32)
+    }
+
+    // Records of the type at `type_index`, empty if `T` does not match the records size
+    pub fn records<T>(&self, type_index: u32) -> &'a [T] {
+        match self.types().get(type_index as usize) {
+            Some(entry) if entry.record_size as usize == std::mem::size_of::<T>() => {
+                self.section(entry.records_offset, entry.record_count)
+            }
+            _ => &[],
+        }
+    }
+}
+"#;
+
+const C_READER: &str = r#"
+/* The data must be aligned to 4 and the target little endian */
+static inline const FdatHeader *fdat_header(const void *data, size_t size) {
+    const FdatHeader *header = (const FdatHeader *)data;
+    if (size < sizeof(FdatHeader) || memcmp(header->magic, "FDAT", 4) != 0 || header->version != FDAT_VERSION) {
+        return NULL;
+    }
+
+    return header;
+}
+
+#define FDAT_SECTION(header, type, offset) ((const type *)((const uint8_t *)(header) + (offset)))
+
+static inline const char *fdat_string(const FdatHeader *header, uint32_t index, uint32_t *length) {
+    if (index >= header->string_count) {
+        return NULL;
+    }
+
+    const FdatStringEntry *entry = FDAT_SECTION(header, FdatStringEntry, header->strings_offset) + index;
+    *length = entry->length;
+
+    return (const char *)header + header->string_data_offset + entry->offset;
+}
+
+static inline const void *fdat_records(const FdatHeader *header, uint32_t type_index) {
+    if (type_index >= header->type_count) {
+        return NULL;
+    }
+
+    const FdatTypeEntry *entry = FDAT_SECTION(header, FdatTypeEntry, header->types_offset) + type_index;
+    return (const uint8_t *)header + entry->records_offset;
+}
+"#;
+
+// A data type with the identifiers of it and its fields, which are the same in Rust and C
+struct RecordType<'a> {
+    name: &'a str,
+    ident: String,
+    layout: RecordLayout,
+    fields: Vec<String>,
+}
+
+// Keywords of either language get a `_` suffix so the field names match between the two
+fn escape(identifier: String) -> String {
+    match identifier == "_" || RUST_KEYWORDS.contains(&identifier.as_str()) || C_KEYWORDS.contains(&identifier.as_str()) {
+        true => identifier + "_",
+        false => identifier,
+    }
+}
+
+fn record_types(project: &Project) -> Result<Vec<RecordType>, Box<dyn Error>> {
+    let types = project_types(project)?;
+    let idents = unique_identifiers(types.iter().map(|(name, _)| escape(identifier(name))).collect(), &[]);
+
+    Ok(types.into_iter().zip(idents).map(|((name, storage), ident)| {
+        let layout = RecordLayout::new(storage);
+        let fields = unique_identifiers(layout.fields.iter().map(|x| escape(identifier(&x.name))).collect(), &[]);
+
+        RecordType { name, ident, layout, fields }
+    }).collect())
+}
+
+pub fn rust_source(project: &Project) -> Result<String, Box<dyn Error>> {
+    let mut out = String::new();
+
+    writeln!(out, "// Generated by frame-data, do not edit. Reads files written by the binary exporter").unwrap();
+    writeln!(out, "#![allow(dead_code)]\n").unwrap();
+    writeln!(out, "pub const MAGIC: [u8; 4] = *b\"FDAT\";").unwrap();
+    writeln!(out, "pub const VERSION: u16 = {};", binary::VERSION).unwrap();
+    writeln!(out, "pub const NONE: u32 = 0x{:x};", binary::NONE).unwrap();
+    writeln!(out, "pub const FLAG_MIRRORED: u16 = {};", binary::FLAG_MIRRORED).unwrap();
+    writeln!(out, "pub const FLAG_IMAGE_SPACE: u16 = {};", binary::FLAG_IMAGE_SPACE).unwrap();
+    writeln!(out, "pub const FRAME_FLIPPED_X: u32 = {};", binary::FRAME_FLIPPED_X).unwrap();
+    writeln!(out, "pub const FRAME_FLIPPED_Y: u32 = {};", binary::FRAME_FLIPPED_Y).unwrap();
+
+    for (name, size, fields) in sections() {
+        writeln!(out, "\n// {} bytes", size).unwrap();
+        writeln!(out, "#[repr(C)]\n#[derive(Copy, Clone, Debug)]\npub struct {} {{", name).unwrap();
+        for (field, rust, _) in fields {
+            writeln!(out, "    pub {}: {},", field, rust).unwrap();
+        }
+        writeln!(out, "}}").unwrap();
+    }
+
+    // In their own module so they can't clash with the types above
+    writeln!(out, "\n// Records of the data types, read with `Reader::records`").unwrap();
+    writeln!(out, "pub mod records {{").unwrap();
+    for (i, record) in record_types(project)?.iter().enumerate() {
+        if i > 0 {
+            writeln!(out).unwrap();
+        }
+
+        // A struct without fields would have a different size in Rust and C
+        if record.fields.is_empty() {
+            writeln!(out, "    // The \"{}\" type has no fields, its records are empty", record.name).unwrap();
+            continue;
+        }
+
+        writeln!(out, "    // Record of the \"{}\" type, {} bytes", record.name, record.layout.size).unwrap();
+        writeln!(out, "    #[repr(C)]\n    #[derive(Copy, Clone, Debug)]\n    pub struct {} {{", record.ident).unwrap();
+        for (field, ident) in record.layout.fields.iter().zip(record.fields.iter()) {
+            writeln!(out, "        pub {}: {},{}", ident, rust_type(field.dtype), field_comment(field.dtype)).unwrap();
+        }
+        writeln!(out, "    }}").unwrap();
+    }
+    writeln!(out, "}}").unwrap();
+
+    out.push_str(RUST_READER);
+    Ok(out)
+}
+
+pub fn c_header(project: &Project) -> Result<String, Box<dyn Error>> {
+    let mut out = String::new();
+
+    writeln!(out, "/* Generated by frame-data, do not edit. Reads files written by the binary exporter */").unwrap();
+    writeln!(out, "#pragma once\n\n#include <stddef.h>\n#include <stdint.h>\n#include <string.h>\n").unwrap();
+    writeln!(out, "#define FDAT_VERSION {}", binary::VERSION).unwrap();
+    writeln!(out, "#define FDAT_NONE 0x{:x}u", binary::NONE).unwrap();
+    writeln!(out, "#define FDAT_FLAG_MIRRORED {}", binary::FLAG_MIRRORED).unwrap();
+    writeln!(out, "#define FDAT_FLAG_IMAGE_SPACE {}", binary::FLAG_IMAGE_SPACE).unwrap();
+    writeln!(out, "#define FDAT_FRAME_FLIPPED_X {}", binary::FRAME_FLIPPED_X).unwrap();
+    writeln!(out, "#define FDAT_FRAME_FLIPPED_Y {}", binary::FRAME_FLIPPED_Y).unwrap();
+
+    for (name, size, fields) in sections() {
+        writeln!(out, "\ntypedef struct Fdat{} {{", name).unwrap();
+        for (field, _, c) in fields {
+            // Arrays carry their own name
+            match c.contains(' ') {
+                true => writeln!(out, "    {};", c).unwrap(),
+                false => writeln!(out, "    {} {};", c, field).unwrap(),
+            }
+        }
+        writeln!(out, "}} Fdat{};", name).unwrap();
+        writeln!(out, "_Static_assert(sizeof(Fdat{}) == {}, \"Fdat{} size\");", name, size, name).unwrap();
+    }
+
+    for record in record_types(project)? {
+        // Prefixed so they can't clash with the types above, C has no empty structs
+        if record.fields.is_empty() {
+            writeln!(out, "\n/* The \"{}\" type has no fields, its records are empty */", record.name).unwrap();
+            continue;
+        }
+
+        let ident = format!("FdatRecord_{}", record.ident);
+        writeln!(out, "\n/* Record of the \"{}\" type */", record.name).unwrap();
+        writeln!(out, "typedef struct {} {{", ident).unwrap();
+        for (field, name) in record.layout.fields.iter().zip(record.fields.iter()) {
+            let comment = field_comment(field.dtype).replace("//", "/*");
+            let comment = if comment.is_empty() { comment } else { format!("{} */", comment) };
+
+            writeln!(out, "    {} {};{}", c_type(field.dtype), name, comment).unwrap();
+        }
+        writeln!(out, "}} {};", ident).unwrap();
+        writeln!(out, "_Static_assert(sizeof({}) == {}, \"{} size\");", ident, record.layout.size, ident).unwrap();
+    }
+
+    out.push_str(C_READER);
+    Ok(out)
+}
+
+// Writes `frame_data.rs` and `frame_data.h` into the directory, returning the written paths
+pub fn write_project(project: &Project, directory: &Path) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let (rust_source, c_header) = (rust_source(project)?, c_header(project)?);
+    fs::create_dir_all(directory)?;
+
+    let rust = directory.join("frame_data.rs");
+    let c = directory.join("frame_data.h");
+    fs::write(&rust, rust_source)?;
+    fs::write(&c, c_header)?;
+
+    Ok(vec![rust, c])
+}
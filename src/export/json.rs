@@ -0,0 +1,229 @@
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+use serde_json::{Map, Number, Value, json};
+use crate::{
+    category::{BoxCategory, Categories},
+    data::{DataDescription, DataStorage, DataValue, RecordId},
+    frame::AnimationData,
+    frame_data::FrameDataSummary,
+    project::Project,
+    shapes::{BoundingBox, Cuboid},
+};
+use super::{ExportOptions, RecordIndices, box_shape, file_names, frame_flipped, frame_pivot, to_export_space, schema};
+
+// Bumped whenever the layout changes in a way readers need to know about, see docs/json_export.md
+pub const FORMAT_VERSION: u32 = 2;
+
+pub fn data_value_to_json(value: &DataValue) -> Value {
+    match value {
+        DataValue::F32(val) => Number::from_f64(*val as f64).map(Value::Number).unwrap_or(Value::Null),
+        DataValue::I32(val) => json!(val),
+        DataValue::U32(val) => json!(val),
+        DataValue::Char(val) => json!(val.to_string()),
+        DataValue::Bool(val) => json!(val),
+        DataValue::OwnedString(val) => json!(val),
+    }
+}
+
+// Fields keyed by their `DataDescription::name`
+pub fn record_to_json(description: &[DataDescription], values: &[DataValue]) -> Value {
+    let mut record = Map::new();
+
+    for (desc, value) in description.iter().zip(values.iter()) {
+        record.insert(desc.name.clone(), data_value_to_json(value));
+    }
+
+    Value::Object(record)
+}
+
+fn data_to_json(data: &DataStorage<RecordId>, (dtype, key): &(String, RecordId)) -> Value {
+    match data.get_with_desc(dtype, key) {
+        Some((description, values)) => record_to_json(description, values),
+        None => Value::Null,
+    }
+}
+
+fn shape_to_json(shape: &BoundingBox<f32>) -> Value {
+    let translation = shape.get_translation();
+
+    json!({
+        "x": translation.x,
+        "y": translation.y,
+        "width": shape.rect.width,
+        "height": shape.rect.height,
+        "angle": shape.get_rotation(),
+    })
+}
+
+fn categories_to_json(categories: &Categories) -> Value {
+    let mut values = Map::new();
+
+    for category in BoxCategory::ALL.iter() {
+        let interacts = BoxCategory::ALL.iter()
+            .filter(|x| categories.interacts(*category, **x))
+            .map(|x| x.name())
+            .collect::<Vec<_>>();
+
+        values.insert(category.name().to_string(), json!({
+            "layer": categories.layer(*category),
+            "interacts_with": interacts,
+        }));
+    }
+
+    Value::Object(values)
+}
+
+fn summary_to_json(summary: &FrameDataSummary) -> Value {
+    json!({
+        "total": summary.total,
+        "startup": summary.startup,
+        "active": summary.active,
+        "recovery": summary.recovery,
+        "on_hit": summary.on_hit,
+        "on_block": summary.on_block,
+    })
+}
+
+fn frames_to_json(animation: &AnimationData, options: &ExportOptions) -> Value {
+    let frames = animation.frames().iter().map(|frame| {
+        let rect = frame.rect();
+        let pivot = frame_pivot(frame, options);
+        let mut anchors = Map::new();
+
+        for anchor in frame.anchors() {
+            let shape = BoundingBox::new(Cuboid::new(0.0, 0.0), anchor.transformation);
+            let shape = to_export_space(&shape, frame, options);
+            let translation = shape.get_translation();
+
+            anchors.insert(anchor.name.clone(), json!({
+                "x": translation.x,
+                "y": translation.y,
+                "angle": shape.get_rotation(),
+            }));
+        }
+
+        json!({
+            "rect": {
+                "x": rect.translation.vector.x,
+                "y": rect.translation.vector.y,
+                "width": rect.rect.width,
+                "height": rect.rect.height,
+            },
+            "pivot": { "x": pivot[0], "y": pivot[1] },
+            "duration": frame.duration(),
+            "flipped": frame_flipped(frame, options),
+            "anchors": anchors,
+        })
+    }).collect::<Vec<_>>();
+
+    Value::Array(frames)
+}
+
+fn boxes_to_json(animation: &AnimationData, records: &RecordIndices, options: &ExportOptions) -> Value {
+    let mut groups = Map::new();
+
+    for category in BoxCategory::ALL.iter() {
+        let boxes = animation.boxes_in_category(*category).map(|(index, data_box)| {
+            let ranges = animation.activation_ranges(index);
+            let active = ranges.iter().map(|range| json!({
+                "start_frame": range.start,
+                "end_frame": range.end,
+                "start_tick": animation.frame_start_tick(range.start),
+                "end_tick": animation.frame_start_tick(range.end),
+            })).collect::<Vec<_>>();
+
+            let frames = ranges.iter().flat_map(|range| range.clone()).filter_map(|frame| {
+                box_shape(animation, data_box, frame, options).map(|shape| {
+                    let mut value = shape_to_json(&shape);
+                    value["frame"] = json!(frame);
+                    value
+                })
+            }).collect::<Vec<_>>();
+
+            json!({
+                "type": data_box.data.0,
+                "key": records.get(&data_box.data),
+                "parent": data_box.parent,
+                "active": active,
+                "frames": frames,
+                "data": data_to_json(animation.data(), &data_box.data),
+            })
+        }).collect::<Vec<_>>();
+
+        groups.insert(category.name().to_string(), Value::Array(boxes));
+    }
+
+    Value::Object(groups)
+}
+
+fn markers_to_json(animation: &AnimationData, records: &RecordIndices) -> Value {
+    let markers = animation.markers().iter().map(|marker| json!({
+        "name": marker.name,
+        "start_tick": marker.start,
+        "length": marker.length,
+        "type": marker.data.0,
+        "key": records.get(&marker.data),
+        "data": data_to_json(animation.data(), &marker.data),
+    })).collect::<Vec<_>>();
+
+    Value::Array(markers)
+}
+
+pub fn export_animation(animation: &AnimationData, categories: &Categories, options: &ExportOptions) -> Value {
+    let records = RecordIndices::new(animation);
+
+    json!({
+        "format_version": FORMAT_VERSION,
+        "name": animation.name,
+        "fps": animation.fps(),
+        "sprite_sheet": animation.sprite_sheet,
+        "space": match options.space {
+            super::ExportSpace::Pivot => "pivot",
+            super::ExportSpace::Image => "image",
+        },
+        "mirrored": options.mirrored,
+        "categories": categories_to_json(categories),
+        "frames": frames_to_json(animation, options),
+        "boxes": boxes_to_json(animation, &records, options),
+        "markers": markers_to_json(animation, &records),
+        "summary": summary_to_json(&FrameDataSummary::summarize(animation)),
+    })
+}
+
+pub fn to_string(value: &Value, options: &ExportOptions) -> Result<String, Box<dyn Error>> {
+    let mut text = match options.pretty {
+        true => serde_json::to_string_pretty(value)?,
+        false => serde_json::to_string(value)?,
+    };
+    text.push('\n');
+
+    Ok(text)
+}
+
+// Writes `<name>.json` for every animation along with `schema.json`, returning the written paths. Names
+// are made into file names with `file_names`
+pub fn write_project(project: &Project, directory: &Path, options: &ExportOptions) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    // Before writing anything so a failed export leaves the directory as it was
+    let schema = schema::project_schema(project)?;
+    let names = file_names(project, &["schema"])?;
+
+    fs::create_dir_all(directory)?;
+    let mut written = vec![];
+
+    for (animation, name) in project.animations.iter().zip(names.iter()) {
+        let path = directory.join(format!("{}.json", name));
+        let value = export_animation(animation, &project.categories, options);
+
+        fs::write(&path, to_string(&value, options)?)?;
+        written.push(path);
+    }
+
+    let path = directory.join("schema.json");
+    fs::write(&path, to_string(&schema, options)?)?;
+    written.push(path);
+
+    Ok(written)
+}
@@ -0,0 +1,320 @@
+pub mod registry;
+pub mod profile;
+pub(crate) mod json;
+pub(crate) mod schema;
+pub(crate) mod binary;
+pub(crate) mod binary_codegen;
+pub(crate) mod type_codegen;
+
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+};
+use na::{Isometry2, Vector2};
+use crate::{
+    data::{DataTypeStorage, RecordId},
+    frame::{AnimationData, DataBox, Frame},
+    project::Project,
+    shapes::{BoundingBox, MirrorAxis},
+};
+
+// What exported box and anchor positions are relative to
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ExportSpace {
+    // The frames pivot, y down
+    Pivot,
+    // The top left of the frames rect, y down
+    Image,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct ExportOptions {
+    pub space: ExportSpace,
+    // Emits the animation flipped horizontally about each frames pivot
+    pub mirrored: bool,
+    pub pretty: bool,
+    // Applied to box and anchor positions after moving them into the export space, in this order
+    pub flip_y: bool,
+    pub scale: f32,
+    // Positions and sizes are rounded to multiples of this, 0 keeps them as they are
+    pub step: f32,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        ExportOptions {
+            space: ExportSpace::Pivot,
+            mirrored: false,
+            pretty: true,
+            flip_y: false,
+            scale: 1.0,
+            step: 0.0,
+        }
+    }
+}
+
+pub fn frame_pivot(frame: &Frame, options: &ExportOptions) -> [u32; 2] {
+    let centre = frame.centre();
+
+    match options.mirrored {
+        true => [frame.rect().rect.width.saturating_sub(centre[0]), centre[1]],
+        false => centre,
+    }
+}
+
+pub fn frame_flipped(frame: &Frame, options: &ExportOptions) -> [bool; 2] {
+    let flipped = frame.flipped();
+    [flipped[0] != options.mirrored, flipped[1]]
+}
+
+// Rounds to the nearest multiple of `step`, a step of 0 or less leaves the value as it is
+pub fn quantize(value: f32, step: f32) -> f32 {
+    match step > 0.0 {
+        true => (value / step).round() * step,
+        false => value,
+    }
+}
+
+// Moves a shape that is relative to the pivot into the export space
+pub fn to_export_space(shape: &BoundingBox<f32>, frame: &Frame, options: &ExportOptions) -> BoundingBox<f32> {
+    let mut shape = *shape;
+    if options.mirrored {
+        shape.mirror(MirrorAxis::Horizontal);
+    }
+
+    let mut shape = match options.space {
+        ExportSpace::Pivot => shape,
+        ExportSpace::Image => {
+            let pivot = frame_pivot(frame, options);
+            shape.transformed(&Isometry2::new(Vector2::new(pivot[0] as f32, pivot[1] as f32), 0.0))
+        }
+    };
+
+    // Flipped about the origin of the export space
+    if options.flip_y {
+        shape.mirror(MirrorAxis::Vertical);
+    }
+
+    let translation = shape.get_translation() * options.scale;
+    shape.transformation.translation.vector = Vector2::new(quantize(translation.x, options.step), quantize(translation.y, options.step));
+    shape.rect.width = quantize(shape.rect.width * options.scale, options.step);
+    shape.rect.height = quantize(shape.rect.height * options.scale, options.step);
+
+    shape
+}
+
+// Shape of the box on the frame with its parent anchor applied, in the export space
+pub fn box_shape<S>(animation: &AnimationData, data_box: &DataBox<S>, frame: usize, options: &ExportOptions) -> Option<BoundingBox<f32>> {
+    let value = animation.frame(frame)?;
+    let shape = animation.world_shape(data_box, frame);
+
+    Some(to_export_space(&shape, value, options))
+}
+
+// Compact indices for the records an animation refers to, numbered per type from 0 in the order
+// boxes and then markers first use them so exports don't carry the sparse record ids
+pub struct RecordIndices {
+    indices: HashMap<(String, RecordId), u32>,
+}
+
+impl RecordIndices {
+    pub fn new(animation: &AnimationData) -> RecordIndices {
+        let mut indices = HashMap::new();
+        let mut counts: HashMap<&str, u32> = HashMap::new();
+        let references = animation.data_boxes().iter().map(|x| &x.data).chain(animation.markers().iter().map(|x| &x.data));
+
+        for reference in references {
+            if indices.contains_key(reference) {
+                continue;
+            }
+
+            let count = counts.entry(reference.0.as_str()).or_insert(0);
+            indices.insert(reference.clone(), *count);
+            *count += 1;
+        }
+
+        RecordIndices {
+            indices,
+        }
+    }
+
+    pub fn get(&self, reference: &(String, RecordId)) -> Option<u32> {
+        self.indices.get(reference).cloned()
+    }
+}
+
+fn same_fields(a: &DataTypeStorage<RecordId>, b: &DataTypeStorage<RecordId>) -> bool {
+    a.get_desc().len() == b.get_desc().len() && a.get_desc().iter().zip(b.get_desc()).all(|(x, y)| x.name == y.name && x.dtype == y.dtype)
+}
+
+// Data types across every animation of the project sorted by name. One schema and one set of generated
+// types covers every animation, so animations with a type of the same name must give it the same fields
+pub fn project_types(project: &Project) -> Result<Vec<(&str, &DataTypeStorage<RecordId>)>, Box<dyn Error>> {
+    // With the animation that first defined the type
+    let mut types: Vec<(&str, &DataTypeStorage<RecordId>, &str)> = vec![];
+
+    for animation in project.animations.iter() {
+        for (name, storage) in animation.data().data.iter() {
+            match types.iter().find(|(x, _, _)| *x == name.as_str()) {
+                Some((_, first, _)) if same_fields(first, storage) => (),
+                Some((_, _, first)) => {
+                    return Err(format!("type '{}' has different fields in '{}' and '{}'", name, first, animation.name).into());
+                }
+                None => types.push((name.as_str(), storage, animation.name.as_str())),
+            }
+        }
+    }
+
+    types.sort_by(|a, b| a.0.cmp(b.0));
+    Ok(types.into_iter().map(|(name, storage, _)| (name, storage)).collect())
+}
+
+// Turns a user given name into something usable as an identifier in generated code
+pub fn identifier(name: &str) -> String {
+    let mut identifier = name.chars()
+        .map(|x| if x.is_ascii_alphanumeric() { x } else { '_' })
+        .collect::<String>();
+
+    if identifier.chars().next().map(|x| x.is_ascii_digit()).unwrap_or(true) {
+        identifier.insert(0, '_');
+    }
+
+    identifier
+}
+
+// Animation names with anything that could leave the directory or isn't allowed in a file name
+// replaced by `_`. Fails if two animations end up with the same file, ignoring case for case insensitive
+// file systems, or an animation would take one of the `reserved` names
+pub fn file_names(project: &Project, reserved: &[&str]) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut names: Vec<String> = vec![];
+
+    for animation in project.animations.iter() {
+        let mut name = animation.name.trim()
+            .chars()
+            .map(|x| if x.is_alphanumeric() || "-_ ".contains(x) { x } else { '_' })
+            .collect::<String>();
+        if name.is_empty() {
+            name.push('_');
+        }
+
+        if reserved.iter().any(|x| x.eq_ignore_ascii_case(&name)) {
+            return Err(format!("the file of animation '{}' would replace the exported '{}' file", animation.name, name).into());
+        }
+        if let Some(index) = names.iter().position(|x| x.to_lowercase() == name.to_lowercase()) {
+            let other = &project.animations[index].name;
+            return Err(format!("animations '{}' and '{}' would be written to the same file '{}'", other, animation.name, name).into());
+        }
+
+        names.push(name);
+    }
+
+    Ok(names)
+}
+
+// Adds `_2`, `_3`... to identifiers that are reserved or were already taken by an earlier one, so names
+// that only differ in characters `identifier` replaces still get their own identifier
+pub fn unique_identifiers(identifiers: Vec<String>, reserved: &[&str]) -> Vec<String> {
+    let mut taken = reserved.iter().map(|x| x.to_string()).collect::<HashSet<_>>();
+
+    identifiers.into_iter().map(|identifier| {
+        let mut unique = identifier.clone();
+        let mut count = 1;
+
+        while taken.contains(&unique) {
+            count += 1;
+            unique = format!("{}_{}", identifier, count);
+        }
+
+        taken.insert(unique.clone());
+        unique
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use na::Isometry2;
+    use crate::{
+        category::BoxCategory,
+        data::{DataType, RecordId},
+        frame::{AnimationData, DataBox},
+        project::Project,
+        shapes::{BoundingBox, Cuboid},
+    };
+    use super::{RecordIndices, file_names, project_types};
+
+    fn shape() -> BoundingBox<f32> {
+        BoundingBox::new(Cuboid::new(8.0, 8.0), Isometry2::identity())
+    }
+
+    #[test]
+    fn record_indices_count_each_type_from_0_in_first_use_order() {
+        let mut animation = AnimationData::new("attack".to_string(), 60);
+        animation.new_data_type("Hit".to_string());
+        animation.new_data_type("Move".to_string());
+
+        let first = animation.new_data_box("Hit", shape(), BoxCategory::Hitbox).unwrap();
+        let moving = animation.new_data_box("Move", shape(), BoxCategory::Pushbox).unwrap();
+        let second = animation.new_data_box("Hit", shape(), BoxCategory::Hitbox).unwrap();
+        // Shares the record of the first box
+        let shared = animation.data_boxes()[first].data.clone();
+        animation.push_data_box(DataBox::new(shape(), shared, BoxCategory::Hurtbox));
+        let marker = animation.new_marker("cancel".to_string(), "Move", 0, 1).unwrap();
+
+        let records = RecordIndices::new(&animation);
+        let index = |data: &(String, RecordId)| records.get(data);
+
+        assert_eq!(index(&animation.data_boxes()[first].data), Some(0));
+        assert_eq!(index(&animation.data_boxes()[second].data), Some(1));
+        assert_eq!(index(&animation.data_boxes()[3].data), Some(0));
+        assert_eq!(index(&animation.data_boxes()[moving].data), Some(0));
+        assert_eq!(index(&animation.markers()[marker].data), Some(1));
+        assert_eq!(index(&("Hit".to_string(), RecordId::max_value())), None);
+    }
+
+    fn animation(name: &str, fields: &[(&str, DataType)]) -> AnimationData {
+        let mut animation = AnimationData::new(name.to_string(), 60);
+        animation.new_data_type("Hit".to_string());
+
+        let storage = animation.data_mut().get_storage_mut("Hit").unwrap();
+        for (field, dtype) in fields.iter() {
+            storage.add_field(field.to_string(), *dtype).unwrap();
+        }
+
+        animation
+    }
+
+    #[test]
+    fn project_types_requires_animations_to_agree_on_fields() {
+        let mut project = Project::new();
+        project.add_animation(animation("jab", &[("damage", DataType::I32)]));
+        project.add_animation(animation("kick", &[("damage", DataType::I32)]));
+        assert_eq!(project_types(&project).unwrap().len(), 1);
+
+        project.add_animation(animation("sweep", &[("damage", DataType::F32)]));
+        let error = project_types(&project).err().unwrap().to_string();
+        assert_eq!(error, "type 'Hit' has different fields in 'jab' and 'sweep'");
+    }
+
+    fn project(names: &[&str]) -> Project {
+        let mut project = Project::new();
+        for name in names.iter() {
+            project.add_animation(AnimationData::new(name.to_string(), 60));
+        }
+
+        project
+    }
+
+    #[test]
+    fn file_names_stay_in_the_directory() {
+        let names = file_names(&project(&["../x", "a/b", "c:\\d", "light punch", ""]), &[]).unwrap();
+        assert_eq!(names, ["___x", "a_b", "c__d", "light punch", "_"]);
+    }
+
+    #[test]
+    fn file_names_reject_duplicates_and_reserved_names() {
+        assert!(file_names(&project(&["a/b", "a_b"]), &[]).is_err());
+        assert!(file_names(&project(&["Jab", "jab"]), &[]).is_err());
+        assert!(file_names(&project(&["Schema"]), &["schema"]).is_err());
+        assert!(file_names(&project(&["schema"]), &[]).is_ok());
+    }
+}
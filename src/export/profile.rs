@@ -0,0 +1,244 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    error::Error,
+};
+use crate::{
+    data::{DataType, DataTypeStorage, DataValue, FieldError, RecordId},
+    project::Project,
+};
+use super::{ExportOptions, quantize};
+
+// How the game's data differs from the editor's, saved with the project and picked for each exporter
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExportProfile {
+    // Y up instead of the editor's y down
+    pub flip_y: bool,
+    // Game units per pixel, for example 0.01 for 100 pixels to a meter
+    pub scale: f32,
+    // Box and anchor positions and sizes are rounded to multiples of this after scaling, 0 keeps them as they are
+    pub box_step: f32,
+    // The same for F32 fields, 1/256 gives values that store exactly as 24.8 fixed point
+    pub field_step: f32,
+    // Frame durations and markers are converted to this tick rate
+    pub fps: Option<u16>,
+    // New names of fields by `Type.field`
+    pub renamed: BTreeMap<String, String>,
+    // Fields left out as `Type.field`
+    pub omitted: BTreeSet<String>,
+}
+
+impl Default for ExportProfile {
+    fn default() -> Self {
+        ExportProfile {
+            flip_y: false,
+            scale: 1.0,
+            box_step: 0.0,
+            field_step: 0.0,
+            fps: None,
+            renamed: BTreeMap::new(),
+            omitted: BTreeSet::new(),
+        }
+    }
+}
+
+pub fn field_path(dtype: &str, field: &str) -> String {
+    format!("{}.{}", dtype, field)
+}
+
+impl ExportProfile {
+    // Options of the built in exporters with the profile's coordinate settings
+    pub fn export_options(&self, options: ExportOptions) -> ExportOptions {
+        ExportOptions {
+            flip_y: self.flip_y,
+            scale: self.scale,
+            step: self.box_step,
+            ..options
+        }
+    }
+
+    // A copy of the project with the profile's fields, values and tick rate. Computed fields keep
+    // their values but become plain fields so the fields they use can be renamed or left out
+    pub fn apply(&self, project: &Project) -> Result<Project, Box<dyn Error>> {
+        let mut project = project.clone();
+
+        for animation in project.animations.iter_mut() {
+            if let Some(fps) = self.fps {
+                animation.convert_fps(fps);
+            }
+
+            let animation_name = animation.name.clone();
+            for (name, storage) in animation.data_mut().data.iter_mut() {
+                self.apply_fields(name, storage).map_err(|error| format!("{}: type '{}': {}", animation_name, name, error))?;
+            }
+        }
+
+        Ok(project)
+    }
+
+    fn apply_fields(&self, dtype: &str, storage: &mut DataTypeStorage<RecordId>) -> Result<(), Box<dyn Error>> {
+        for index in 0..storage.get_desc().len() {
+            storage.set_expression(index, "")?;
+        }
+
+        if self.field_step > 0.0 {
+            let keys = storage.keys().cloned().collect::<Vec<_>>();
+            let fields = storage.get_desc().iter()
+                .enumerate()
+                .filter(|(_, x)| x.dtype == DataType::F32)
+                .map(|(i, _)| i)
+                .collect::<Vec<_>>();
+
+            for key in keys.iter() {
+                let values = storage.get_mut(key).ok_or(FieldError::UnknownRecord)?;
+                for index in fields.iter() {
+                    if let DataValue::F32(value) = &mut values[*index] {
+                        *value = quantize(*value, self.field_step);
+                    }
+                }
+            }
+        }
+
+        let omitted = storage.get_desc().iter()
+            .map(|x| x.name.clone())
+            .filter(|x| self.omitted.contains(&field_path(dtype, x)))
+            .collect::<Vec<_>>();
+
+        for name in omitted.iter() {
+            storage.remove_field_with_name(name)?;
+        }
+
+        let renamed = storage.get_desc().iter()
+            .enumerate()
+            .filter_map(|(i, x)| self.renamed.get(&field_path(dtype, &x.name)).map(|name| (i, name.clone())))
+            .collect::<Vec<_>>();
+
+        storage.rename_fields(&renamed)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        frame::{AnimationData, Frame},
+        shapes::AABB,
+    };
+    use super::*;
+
+    // One record of `Hit` with the fields a = 1.3, b = 2.0, c = 3 and computed d = a + b
+    fn project() -> Project {
+        let mut animation = AnimationData::new("Punch".to_string(), 12);
+        for duration in [1, 2, 3].iter() {
+            let mut frame = Frame::new(AABB::new(0, 0, 8, 8));
+            frame.set_duration(*duration);
+            animation.push_frame(frame);
+        }
+
+        animation.new_data_type("Hit".to_string());
+        animation.new_marker("sound".to_string(), "Hit", 1, 2).unwrap();
+
+        let storage = animation.data_mut().get_storage_mut("Hit").unwrap();
+        storage.add_field("a".to_string(), DataType::F32).unwrap();
+        storage.add_field("b".to_string(), DataType::F32).unwrap();
+        storage.add_field("c".to_string(), DataType::I32).unwrap();
+        storage.add_field("d".to_string(), DataType::F32).unwrap();
+        storage.set_expression(3, "a + b").unwrap();
+
+        let key = *storage.keys().next().unwrap();
+        storage.set_value(&key, 0, "1.3").unwrap();
+        storage.set_value(&key, 1, "2.0").unwrap();
+        storage.set_value(&key, 2, "3").unwrap();
+
+        let mut project = Project::new();
+        project.add_animation(animation);
+        project
+    }
+
+    fn renamed(pairs: &[(&str, &str)]) -> ExportProfile {
+        ExportProfile {
+            renamed: pairs.iter().map(|(a, b)| (field_path("Hit", a), b.to_string())).collect(),
+            ..ExportProfile::default()
+        }
+    }
+
+    fn names(project: &Project) -> Vec<String> {
+        let storage = project.animations[0].data().get_storage("Hit").unwrap();
+        storage.get_desc().iter().map(|x| x.name.clone()).collect()
+    }
+
+    fn value(project: &Project, field: &str) -> DataValue {
+        let storage = project.animations[0].data().get_storage("Hit").unwrap();
+        let key = storage.keys().next().unwrap();
+        storage.get_field(key, field).unwrap().clone()
+    }
+
+    #[test]
+    fn omitted_fields_are_removed_and_computed_fields_keep_their_values() {
+        let profile = ExportProfile {
+            omitted: ["Hit.a", "Hit.c"].iter().map(|x| x.to_string()).collect(),
+            ..ExportProfile::default()
+        };
+
+        let applied = profile.apply(&project()).unwrap();
+        assert_eq!(names(&applied), ["b", "d"]);
+        assert!(matches!(value(&applied, "d"), DataValue::F32(x) if (x - 3.3).abs() < 1e-6));
+        assert!(applied.animations[0].data().get_storage("Hit").unwrap().get_desc().iter().all(|x| !x.is_computed()));
+    }
+
+    #[test]
+    fn renamed_fields_keep_their_values() {
+        let applied = renamed(&[("a", "x")]).apply(&project()).unwrap();
+
+        assert_eq!(names(&applied), ["x", "b", "c", "d"]);
+        assert!(matches!(value(&applied, "x"), DataValue::F32(x) if x == 1.3));
+    }
+
+    #[test]
+    fn fields_can_swap_names() {
+        let applied = renamed(&[("a", "b"), ("b", "a")]).apply(&project()).unwrap();
+
+        assert_eq!(names(&applied), ["b", "a", "c", "d"]);
+        assert!(matches!(value(&applied, "a"), DataValue::F32(x) if x == 2.0));
+        assert!(matches!(value(&applied, "b"), DataValue::F32(x) if x == 1.3));
+    }
+
+    #[test]
+    fn fields_can_be_renamed_along_a_chain() {
+        let applied = renamed(&[("a", "b"), ("b", "c"), ("c", "e")]).apply(&project()).unwrap();
+
+        assert_eq!(names(&applied), ["b", "c", "e", "d"]);
+        assert!(matches!(value(&applied, "e"), DataValue::I32(3)));
+    }
+
+    #[test]
+    fn clashing_names_are_rejected() {
+        assert!(renamed(&[("a", "c")]).apply(&project()).is_err());
+        assert!(renamed(&[("a", "x"), ("b", "x")]).apply(&project()).is_err());
+    }
+
+    #[test]
+    fn f32_fields_are_quantized() {
+        let profile = ExportProfile { field_step: 0.5, ..ExportProfile::default() };
+        let applied = profile.apply(&project()).unwrap();
+
+        assert!(matches!(value(&applied, "a"), DataValue::F32(x) if x == 1.5));
+        assert!(matches!(value(&applied, "b"), DataValue::F32(x) if x == 2.0));
+        assert!(matches!(value(&applied, "c"), DataValue::I32(3)));
+    }
+
+    #[test]
+    fn frames_and_markers_are_converted_to_the_profiles_fps() {
+        let original = project();
+        let profile = ExportProfile { fps: Some(24), ..ExportProfile::default() };
+        let applied = profile.apply(&original).unwrap();
+
+        let animation = &applied.animations[0];
+        assert_eq!(animation.fps(), 24);
+        assert_eq!(animation.frames().iter().map(|x| x.duration()).collect::<Vec<_>>(), [2, 4, 6]);
+        assert_eq!((animation.markers()[0].start, animation.markers()[0].length), (2, 4));
+
+        // The project itself is left alone
+        assert_eq!(original.animations[0].fps(), 12);
+        assert_eq!(original.animations[0].frames()[0].duration(), 1);
+    }
+}
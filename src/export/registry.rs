@@ -0,0 +1,343 @@
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    path::{Path, PathBuf},
+};
+use crate::{
+    project::Project,
+    project_file,
+};
+use super::{
+    ExportOptions, ExportSpace,
+    binary, binary_codegen, json,
+    profile::ExportProfile,
+    type_codegen::{self, CodegenOptions},
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum OptionValue {
+    Flag(bool),
+    Text(String),
+}
+
+// A setting of a format. Flags are shown as checkboxes in the export window and given as `--name`
+// on the command line, text as a text box and `--name=value`
+#[derive(Clone, Debug)]
+pub struct FormatOption {
+    // Lower case with dashes
+    pub name: &'static str,
+    pub description: &'static str,
+    pub default: OptionValue,
+}
+
+impl FormatOption {
+    pub fn flag(name: &'static str, description: &'static str) -> FormatOption {
+        FormatOption {
+            name,
+            description,
+            default: OptionValue::Flag(false),
+        }
+    }
+
+    pub fn text(name: &'static str, description: &'static str, default: &str) -> FormatOption {
+        FormatOption {
+            name,
+            description,
+            default: OptionValue::Text(default.to_string()),
+        }
+    }
+}
+
+// Values of the options of one format, missing options read as unset flags or empty text
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FormatOptions {
+    values: BTreeMap<String, OptionValue>,
+}
+
+impl FormatOptions {
+    pub fn defaults(options: &[FormatOption]) -> FormatOptions {
+        FormatOptions {
+            values: options.iter().map(|x| (x.name.to_string(), x.default.clone())).collect(),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&OptionValue> {
+        self.values.get(name)
+    }
+
+    pub fn set(&mut self, name: &str, value: OptionValue) {
+        self.values.insert(name.to_string(), value);
+    }
+
+    pub fn flag(&self, name: &str) -> bool {
+        match self.values.get(name) {
+            Some(OptionValue::Flag(value)) => *value,
+            _ => false,
+        }
+    }
+
+    pub fn text(&self, name: &str) -> &str {
+        match self.values.get(name) {
+            Some(OptionValue::Text(value)) => value,
+            _ => "",
+        }
+    }
+
+    pub fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a str, &'a OptionValue)> + 'a {
+        self.values.iter().map(|(name, value)| (name.as_str(), value))
+    }
+}
+
+// An exporter with its options and directory saved in the project, run together by watch mode
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExportTarget {
+    pub exporter: String,
+    pub directory: String,
+    pub options: FormatOptions,
+}
+
+impl ExportTarget {
+    // Exports with the profile the project picks for the exporter
+    pub fn run(&self, registry: &Registry, project: &Project) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+        let exporter = registry.exporter(&self.exporter).ok_or_else(|| format!("no export format named '{}'", self.exporter))?;
+        let profile = project.exporter_profile(&self.exporter);
+
+        run_exporter(exporter, project, Path::new(&self.directory), &self.options, &profile)
+    }
+}
+
+// Writes a project into a directory, returning the paths written. The project has the fields, values
+// and timing of the profile already applied, the coordinate settings are left to the exporter
+pub trait Exporter {
+    // Used to pick the format on the command line, lower case with dashes
+    fn name(&self) -> &str;
+    fn description(&self) -> &str;
+
+    fn options(&self) -> Vec<FormatOption> {
+        vec![]
+    }
+
+    fn export(&self, project: &Project, directory: &Path, options: &FormatOptions, profile: &ExportProfile) -> Result<Vec<PathBuf>, Box<dyn Error>>;
+}
+
+// Applies the profile to a copy of the project and exports that
+pub fn run_exporter(exporter: &dyn Exporter, project: &Project, directory: &Path, options: &FormatOptions, profile: &ExportProfile) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    if *profile == ExportProfile::default() {
+        return exporter.export(project, directory, options, profile);
+    }
+
+    let project = profile.apply(project)?;
+    exporter.export(&project, directory, options, profile)
+}
+
+// Reads a file into the open project, returning the names of the animations it added
+pub trait Importer {
+    fn name(&self) -> &str;
+    fn description(&self) -> &str;
+
+    fn options(&self) -> Vec<FormatOption> {
+        vec![]
+    }
+
+    fn import(&self, project: &mut Project, path: &Path, options: &FormatOptions) -> Result<Vec<String>, Box<dyn Error>>;
+}
+
+// The formats the editor and command line offer. Other crates can register their own formats and
+// run the editor with `animation_creator::run`
+pub struct Registry {
+    exporters: Vec<Box<dyn Exporter>>,
+    importers: Vec<Box<dyn Importer>>,
+}
+
+impl Registry {
+    pub fn new() -> Registry {
+        Registry {
+            exporters: vec![],
+            importers: vec![],
+        }
+    }
+
+    pub fn with_builtin_formats() -> Registry {
+        let mut registry = Registry::new();
+        registry.register_exporter(Box::new(JsonExporter));
+        registry.register_exporter(Box::new(BinaryExporter));
+        registry.register_exporter(Box::new(BinaryReaderExporter));
+        registry.register_exporter(Box::new(TypesExporter));
+        registry.register_importer(Box::new(ProjectImporter));
+
+        registry
+    }
+
+    // Replaces any exporter with the same name
+    pub fn register_exporter(&mut self, exporter: Box<dyn Exporter>) {
+        self.exporters.retain(|x| x.name() != exporter.name());
+        self.exporters.push(exporter);
+    }
+
+    // Replaces any importer with the same name
+    pub fn register_importer(&mut self, importer: Box<dyn Importer>) {
+        self.importers.retain(|x| x.name() != importer.name());
+        self.importers.push(importer);
+    }
+
+    pub fn exporters(&self) -> &[Box<dyn Exporter>] {
+        &self.exporters
+    }
+
+    pub fn importers(&self) -> &[Box<dyn Importer>] {
+        &self.importers
+    }
+
+    pub fn exporter(&self, name: &str) -> Option<&dyn Exporter> {
+        self.exporters.iter().find(|x| x.name() == name).map(|x| x.as_ref())
+    }
+
+    pub fn importer(&self, name: &str) -> Option<&dyn Importer> {
+        self.importers.iter().find(|x| x.name() == name).map(|x| x.as_ref())
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn layout_options() -> Vec<FormatOption> {
+    vec![
+        FormatOption::flag("image-space", "Positions relative to the top left of the image instead of the pivot"),
+        FormatOption::flag("mirrored", "Flip every frame horizontally about its pivot"),
+    ]
+}
+
+fn export_options(options: &FormatOptions, profile: &ExportProfile) -> ExportOptions {
+    profile.export_options(ExportOptions {
+        space: if options.flag("image-space") { ExportSpace::Image } else { ExportSpace::Pivot },
+        mirrored: options.flag("mirrored"),
+        pretty: !options.flag("compact"),
+        ..ExportOptions::default()
+    })
+}
+
+pub struct JsonExporter;
+
+impl Exporter for JsonExporter {
+    fn name(&self) -> &str {
+        "json"
+    }
+
+    fn description(&self) -> &str {
+        "A JSON file per animation and a schema of the data types"
+    }
+
+    fn options(&self) -> Vec<FormatOption> {
+        let mut options = layout_options();
+        options.push(FormatOption::flag("compact", "Leave out indentation and line breaks"));
+        options
+    }
+
+    fn export(&self, project: &Project, directory: &Path, options: &FormatOptions, profile: &ExportProfile) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+        json::write_project(project, directory, &export_options(options, profile))
+    }
+}
+
+pub struct BinaryExporter;
+
+impl Exporter for BinaryExporter {
+    fn name(&self) -> &str {
+        "binary"
+    }
+
+    fn description(&self) -> &str {
+        "A binary file per animation"
+    }
+
+    fn options(&self) -> Vec<FormatOption> {
+        layout_options()
+    }
+
+    fn export(&self, project: &Project, directory: &Path, options: &FormatOptions, profile: &ExportProfile) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+        binary::write_project(project, directory, &export_options(options, profile))
+    }
+}
+
+pub struct BinaryReaderExporter;
+
+impl Exporter for BinaryReaderExporter {
+    fn name(&self) -> &str {
+        "binary-reader"
+    }
+
+    fn description(&self) -> &str {
+        "Rust and C code reading the binary files"
+    }
+
+    fn export(&self, project: &Project, directory: &Path, _: &FormatOptions, _: &ExportProfile) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+        binary_codegen::write_project(project, directory)
+    }
+}
+
+pub struct TypesExporter;
+
+impl TypesExporter {
+    pub(crate) fn codegen_options(options: &FormatOptions) -> CodegenOptions {
+        let namespace = options.text("csharp-namespace").trim();
+
+        CodegenOptions {
+            csharp: options.flag("csharp"),
+            csharp_namespace: match namespace.is_empty() {
+                true => CodegenOptions::default().csharp_namespace,
+                false => namespace.to_string(),
+            },
+        }
+    }
+}
+
+impl Exporter for TypesExporter {
+    fn name(&self) -> &str {
+        "types"
+    }
+
+    fn description(&self) -> &str {
+        "Rust and optionally C# types for the data types"
+    }
+
+    fn options(&self) -> Vec<FormatOption> {
+        vec![
+            FormatOption::flag("csharp", "Also generate C# types"),
+            FormatOption::text("csharp-namespace", "Namespace of the C# types", &CodegenOptions::default().csharp_namespace),
+        ]
+    }
+
+    fn export(&self, project: &Project, directory: &Path, options: &FormatOptions, _: &ExportProfile) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+        type_codegen::write_project(project, directory, &TypesExporter::codegen_options(options))
+    }
+}
+
+// Copies the animations of another project file, keeping their data types
+pub struct ProjectImporter;
+
+impl Importer for ProjectImporter {
+    fn name(&self) -> &str {
+        "project"
+    }
+
+    fn description(&self) -> &str {
+        "The animations of another project file"
+    }
+
+    fn import(&self, project: &mut Project, path: &Path, _: &FormatOptions) -> Result<Vec<String>, Box<dyn Error>> {
+        let other = project_file::load(path)?;
+
+        if let Some(animation) = other.animations.iter().find(|x| project.get_animation(&x.name).is_some()) {
+            return Err(format!("the project already has an animation named '{}'", animation.name).into());
+        }
+
+        let names = other.animations.iter().map(|x| x.name.clone()).collect();
+        for animation in other.animations {
+            project.add_animation(animation);
+        }
+
+        Ok(names)
+    }
+}
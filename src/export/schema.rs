@@ -0,0 +1,148 @@
+use std::{
+    error::Error,
+    hash::Hash,
+};
+use serde_json::{Map, Value, json};
+use crate::{
+    category::BoxCategory,
+    data::{DataType, DataTypeStorage},
+    project::Project,
+};
+use super::{json::FORMAT_VERSION, project_types};
+
+pub fn data_type_to_schema(dtype: DataType) -> Value {
+    match dtype {
+        // NaN and infinities have no JSON number and are written as null
+        DataType::F32 => json!({ "type": ["number", "null"] }),
+        DataType::I32 => json!({ "type": "integer", "minimum": i32::min_value(), "maximum": i32::max_value() }),
+        DataType::U32 => json!({ "type": "integer", "minimum": 0, "maximum": u32::max_value() }),
+        DataType::Char => json!({ "type": "string", "minLength": 1, "maxLength": 1 }),
+        DataType::Bool => json!({ "type": "boolean" }),
+        DataType::OwnedString => json!({ "type": "string" }),
+    }
+}
+
+pub fn storage_to_schema<K: Hash + Eq>(storage: &DataTypeStorage<K>) -> Value {
+    let mut properties = Map::new();
+    let mut required = vec![];
+
+    for desc in storage.get_desc() {
+        properties.insert(desc.name.clone(), data_type_to_schema(desc.dtype));
+        required.push(desc.name.clone());
+    }
+
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    })
+}
+
+fn type_definitions(project: &Project) -> Result<Map<String, Value>, Box<dyn Error>> {
+    let mut definitions = Map::new();
+
+    for (name, storage) in project_types(project)? {
+        definitions.insert(name.to_string(), storage_to_schema(storage));
+    }
+
+    Ok(definitions)
+}
+
+// Schema for the files written by `json::write_project`, with a definition for each user data type
+pub fn project_schema(project: &Project) -> Result<Value, Box<dyn Error>> {
+    let definitions = type_definitions(project)?;
+
+    // Each record is tagged with its type in the sibling "type" property
+    let records = definitions.keys().map(|name| json!({
+        "if": { "properties": { "type": { "const": name } } },
+        "then": { "properties": { "data": { "$ref": format!("#/definitions/{}", name) } } },
+    })).collect::<Vec<_>>();
+
+    let number = json!({ "type": "number" });
+    let tick = json!({ "type": "integer", "minimum": 0 });
+    let geometry = json!({
+        "type": "object",
+        "properties": {
+            "frame": tick, "x": number, "y": number, "width": number, "height": number, "angle": number,
+        },
+        "required": ["frame", "x", "y", "width", "height", "angle"],
+    });
+    let frame = json!({
+        "type": "object",
+        "properties": {
+            "rect": {
+                "type": "object",
+                "properties": { "x": tick, "y": tick, "width": tick, "height": tick },
+                "required": ["x", "y", "width", "height"],
+            },
+            "pivot": {
+                "type": "object",
+                "properties": { "x": tick, "y": tick },
+                "required": ["x", "y"],
+            },
+            "duration": { "type": "integer", "minimum": 1 },
+            "flipped": { "type": "array", "items": { "type": "boolean" }, "minItems": 2, "maxItems": 2 },
+            "anchors": {
+                "type": "object",
+                "additionalProperties": {
+                    "type": "object",
+                    "properties": { "x": number, "y": number, "angle": number },
+                    "required": ["x", "y", "angle"],
+                },
+            },
+        },
+        "required": ["rect", "pivot", "duration", "flipped", "anchors"],
+    });
+    let record = json!({
+        "type": "object",
+        "properties": {
+            "type": { "type": "string", "enum": definitions.keys().collect::<Vec<_>>() },
+            "key": tick,
+        },
+        "required": ["type", "key", "data"],
+        "allOf": records,
+    });
+
+    let mut box_schema = record.clone();
+    box_schema["properties"]["parent"] = json!({ "type": ["string", "null"] });
+    box_schema["properties"]["active"] = json!({
+        "type": "array",
+        "items": {
+            "type": "object",
+            "properties": { "start_frame": tick, "end_frame": tick, "start_tick": tick, "end_tick": tick },
+        },
+    });
+    box_schema["properties"]["frames"] = json!({ "type": "array", "items": geometry });
+
+    let mut marker_schema = record;
+    marker_schema["properties"]["name"] = json!({ "type": "string" });
+    marker_schema["properties"]["start_tick"] = tick.clone();
+    marker_schema["properties"]["length"] = tick.clone();
+
+    let mut boxes = Map::new();
+    for category in BoxCategory::ALL.iter() {
+        boxes.insert(category.name().to_string(), json!({ "type": "array", "items": box_schema }));
+    }
+
+    Ok(json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "frame-data animation",
+        "description": format!("Layout version {}", FORMAT_VERSION),
+        "type": "object",
+        "definitions": definitions,
+        "properties": {
+            "format_version": { "const": FORMAT_VERSION },
+            "name": { "type": "string" },
+            "fps": tick,
+            "sprite_sheet": { "type": ["string", "null"] },
+            "space": { "enum": ["pivot", "image"] },
+            "mirrored": { "type": "boolean" },
+            "frames": { "type": "array", "items": frame },
+            "boxes": { "type": "object", "properties": boxes },
+            "markers": { "type": "array", "items": marker_schema },
+            "summary": { "type": "object" },
+        },
+        "required": ["format_version", "name", "fps", "frames", "boxes", "markers"],
+    }))
+}
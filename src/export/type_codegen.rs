@@ -0,0 +1,299 @@
+use std::{
+    error::Error,
+    fmt::Write,
+    fs,
+    hash::Hash,
+    path::{Path, PathBuf},
+};
+use crate::{
+    data::{DataType, DataTypeStorage},
+    project::Project,
+};
+use super::{identifier, project_types, unique_identifiers};
+
+pub const RUST_FILE: &str = "frame_data_types.rs";
+pub const CSHARP_FILE: &str = "FrameDataTypes.cs";
+
+// Including the ones reserved for later use
+pub const RUST_KEYWORDS: [&str; 51] = [
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use",
+    "where", "while", "Self", "abstract", "become", "box", "do", "final", "macro", "override", "priv",
+    "try", "typeof", "unsized", "virtual", "yield",
+];
+
+// The Rust enum over every type, which no type can be named
+const RECORD_ENUM: &str = "Record";
+
+#[derive(Clone, Debug)]
+pub struct CodegenOptions {
+    pub csharp: bool,
+    pub csharp_namespace: String,
+}
+
+impl Default for CodegenOptions {
+    fn default() -> Self {
+        CodegenOptions {
+            csharp: false,
+            csharp_namespace: "FrameData".to_string(),
+        }
+    }
+}
+
+// Splits on anything that isn't alphanumeric and on lower to upper case changes
+fn words(name: &str) -> Vec<String> {
+    let mut words = vec![];
+    let mut current = String::new();
+    let mut previous_lower = false;
+
+    for c in identifier(name).chars() {
+        if c == '_' || (c.is_ascii_uppercase() && previous_lower) {
+            if !current.is_empty() {
+                words.push(current.clone());
+                current.clear();
+            }
+        }
+
+        if c != '_' {
+            current.push(c);
+        }
+        previous_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+    }
+
+    if !current.is_empty() {
+        words.push(current);
+    }
+
+    words
+}
+
+pub fn pascal_case(name: &str) -> String {
+    let mut result = words(name).iter()
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + &chars.as_str().to_ascii_lowercase(),
+                None => String::new(),
+            }
+        })
+        .collect::<String>();
+
+    if result.chars().next().map(|x| !x.is_ascii_alphabetic()).unwrap_or(true) {
+        result.insert(0, '_');
+    }
+
+    result
+}
+
+pub fn snake_case(name: &str) -> String {
+    let mut result = words(name).iter()
+        .map(|x| x.to_ascii_lowercase())
+        .collect::<Vec<_>>()
+        .join("_");
+
+    if result.chars().next().map(|x| !x.is_ascii_alphabetic()).unwrap_or(true) {
+        result.insert(0, '_');
+    }
+    if RUST_KEYWORDS.contains(&result.as_str()) {
+        result.push('_');
+    }
+
+    result
+}
+
+fn rust_type(dtype: DataType) -> &'static str {
+    match dtype {
+        DataType::F32 => "f32",
+        DataType::I32 => "i32",
+        DataType::U32 => "u32",
+        DataType::Char => "char",
+        DataType::Bool => "bool",
+        DataType::OwnedString => "String",
+    }
+}
+
+fn csharp_type(dtype: DataType) -> &'static str {
+    match dtype {
+        DataType::F32 => "float",
+        DataType::I32 => "int",
+        DataType::U32 => "uint",
+        DataType::Char => "char",
+        DataType::Bool => "bool",
+        DataType::OwnedString => "string",
+    }
+}
+
+fn sorted<'a, K: Hash + Eq>(types: &[(&'a str, &'a DataTypeStorage<K>)]) -> Vec<(&'a str, &'a DataTypeStorage<K>)> {
+    let mut types = types.to_vec();
+    types.sort_by(|a, b| a.0.cmp(b.0));
+    types
+}
+
+// Type names in the order of `types`. Names that would be the same after changing their case, or
+// clash with the `Record` enum, get `_2`, `_3`... in name order so the output stays the same
+fn type_names<K: Hash + Eq>(types: &[(&str, &DataTypeStorage<K>)]) -> Vec<String> {
+    let mut reserved = RUST_KEYWORDS.to_vec();
+    reserved.push(RECORD_ENUM);
+
+    unique_identifiers(types.iter().map(|(name, _)| pascal_case(name)).collect(), &reserved)
+}
+
+// Field names made unique within their type the same way, along with anything in `reserved`
+fn field_names<K: Hash + Eq>(storage: &DataTypeStorage<K>, case: fn(&str) -> String, reserved: &[&str]) -> Vec<String> {
+    unique_identifiers(storage.get_desc().iter().map(|x| case(&x.name)).collect(), reserved)
+}
+
+// Structs for every type and a `Record` enum over all of them, matching the records in the JSON export
+pub fn rust_source<K: Hash + Eq>(types: &[(&str, &DataTypeStorage<K>)]) -> String {
+    let mut out = String::new();
+
+    writeln!(out, "// Generated by frame-data, do not edit").unwrap();
+    writeln!(out, "use serde::{{Deserialize, Serialize}};").unwrap();
+
+    let types = sorted(types);
+    let names = type_names(&types);
+    for ((_, storage), type_name) in types.iter().zip(names.iter()) {
+        writeln!(out, "\n#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]").unwrap();
+        writeln!(out, "pub struct {} {{", type_name).unwrap();
+        for (desc, field) in storage.get_desc().iter().zip(field_names(*storage, snake_case, &[])) {
+            if field != desc.name {
+                writeln!(out, "    #[serde(rename = {:?})]", desc.name).unwrap();
+            }
+            writeln!(out, "    pub {}: {},", field, rust_type(desc.dtype)).unwrap();
+        }
+        writeln!(out, "}}").unwrap();
+    }
+
+    writeln!(out, "\n#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]").unwrap();
+    writeln!(out, "#[serde(tag = \"type\", content = \"data\")]").unwrap();
+    writeln!(out, "pub enum {} {{", RECORD_ENUM).unwrap();
+    for ((name, _), variant) in types.iter().zip(names.iter()) {
+        if variant != name {
+            writeln!(out, "    #[serde(rename = {:?})]", name).unwrap();
+        }
+        writeln!(out, "    {}({}),", variant, variant).unwrap();
+    }
+    writeln!(out, "}}").unwrap();
+
+    out
+}
+
+pub fn csharp_source<K: Hash + Eq>(types: &[(&str, &DataTypeStorage<K>)], namespace: &str) -> String {
+    let mut out = String::new();
+
+    writeln!(out, "// Generated by frame-data, do not edit").unwrap();
+    writeln!(out, "using System;\nusing System.Text.Json.Serialization;\n").unwrap();
+    writeln!(out, "namespace {}\n{{", namespace).unwrap();
+
+    let types = sorted(types);
+    for (i, ((_, storage), class)) in types.iter().zip(type_names(&types)).enumerate() {
+        if i > 0 {
+            writeln!(out).unwrap();
+        }
+
+        writeln!(out, "    [Serializable]").unwrap();
+        writeln!(out, "    public class {}\n    {{", class).unwrap();
+        // A member can't have the name of its class
+        for (desc, property) in storage.get_desc().iter().zip(field_names(*storage, pascal_case, &[class.as_str()])) {
+            writeln!(out, "        [JsonPropertyName({:?})]", desc.name).unwrap();
+            writeln!(out, "        public {} {} {{ get; set; }}", csharp_type(desc.dtype), property).unwrap();
+        }
+        writeln!(out, "    }}").unwrap();
+    }
+
+    writeln!(out, "}}").unwrap();
+    out
+}
+
+// Generated files as (file name, contents)
+pub fn generate(project: &Project, options: &CodegenOptions) -> Result<Vec<(&'static str, String)>, Box<dyn Error>> {
+    let types = project_types(project)?;
+    let mut files = vec![(RUST_FILE, rust_source(&types))];
+
+    if options.csharp {
+        files.push((CSHARP_FILE, csharp_source(&types, &options.csharp_namespace)));
+    }
+
+    Ok(files)
+}
+
+pub fn write_project(project: &Project, directory: &Path, options: &CodegenOptions) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let files = generate(project, options)?;
+    fs::create_dir_all(directory)?;
+
+    let mut written = vec![];
+    for (name, source) in files {
+        let path = directory.join(name);
+        fs::write(&path, source)?;
+        written.push(path);
+    }
+
+    Ok(written)
+}
+
+// Paths of the generated files that are missing or differ from what would be generated now
+pub fn out_of_date(project: &Project, directory: &Path, options: &CodegenOptions) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    Ok(generate(project, options)?.into_iter()
+        .map(|(name, source)| (directory.join(name), source))
+        .filter(|(path, source)| fs::read_to_string(path).ok().as_ref() != Some(source))
+        .map(|(path, _)| path)
+        .collect())
+}
+
+// Fails listing the stale files so it can be used to guard a build
+pub fn check_project(project: &Project, directory: &Path, options: &CodegenOptions) -> Result<(), Box<dyn Error>> {
+    let stale = out_of_date(project, directory, options)?;
+    if stale.is_empty() {
+        return Ok(());
+    }
+
+    let paths = stale.iter().map(|x| x.display().to_string()).collect::<Vec<_>>().join(", ");
+    Err(format!("generated types are out of date: {}", paths).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::{DataType, DataTypeStorage};
+    use super::*;
+
+    fn storage(fields: &[&str]) -> DataTypeStorage<u32> {
+        let mut storage = DataTypeStorage::new();
+        for field in fields.iter() {
+            storage.add_field(field.to_string(), DataType::I32).unwrap();
+        }
+
+        storage
+    }
+
+    #[test]
+    fn type_names_avoid_the_record_enum_and_each_other() {
+        let (a, b, c) = (storage(&[]), storage(&[]), storage(&[]));
+        let source = rust_source(&[("Record", &a), ("hit-box", &b), ("hit_box", &c)]);
+
+        assert!(source.contains("pub struct Record_2 {"));
+        assert!(source.contains("pub struct HitBox {"));
+        assert!(source.contains("pub struct HitBox_2 {"));
+        assert!(source.contains("    #[serde(rename = \"Record\")]\n    Record_2(Record_2),"));
+    }
+
+    #[test]
+    fn field_names_are_unique_within_a_type() {
+        let fields = storage(&["hitStun", "hit_stun", "type"]);
+        let source = rust_source(&[("Hit", &fields)]);
+
+        assert!(source.contains("    pub hit_stun: i32,"));
+        assert!(source.contains("    pub hit_stun_2: i32,"));
+        assert!(source.contains("    pub type_: i32,"));
+    }
+
+    #[test]
+    fn csharp_properties_avoid_the_class_name() {
+        let fields = storage(&["hit", "Hit"]);
+        let source = csharp_source(&[("hit", &fields)], "FrameData");
+
+        assert!(source.contains("public class Hit\n"));
+        assert!(source.contains("public int Hit_2 { get; set; }"));
+        assert!(source.contains("public int Hit_3 { get; set; }"));
+    }
+}
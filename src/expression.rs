@@ -0,0 +1,458 @@
+use std::{
+    error::Error,
+    fmt,
+};
+
+// Expressions of computed fields such as `startup + active + recovery` or `damage * 0.1`.
+// Every value is a number, bools are 1 and 0 and anything other than 0 counts as true
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExpressionError {
+    Syntax { position: usize, message: String },
+    UnknownField(String),
+    // Only number and bool fields can be used in expressions
+    NotNumeric(String),
+    // The fields in the order they depend on each other, starting and ending with the same field
+    Cycle(Vec<String>),
+}
+
+impl fmt::Display for ExpressionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExpressionError::Syntax { position, message } => write!(f, "{} at character {}", message, position),
+            ExpressionError::UnknownField(name) => write!(f, "no field named '{}'", name),
+            ExpressionError::NotNumeric(name) => write!(f, "'{}' is not a number or bool", name),
+            ExpressionError::Cycle(names) => write!(f, "fields depend on each other: {}", names.join(" -> ")),
+        }
+    }
+}
+
+impl Error for ExpressionError {}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Function {
+    Min,
+    Max,
+    Abs,
+    Floor,
+    Ceil,
+    Round,
+}
+
+impl Function {
+    const ALL: [Function; 6] = [Function::Min, Function::Max, Function::Abs, Function::Floor, Function::Ceil, Function::Round];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Function::Min => "min",
+            Function::Max => "max",
+            Function::Abs => "abs",
+            Function::Floor => "floor",
+            Function::Ceil => "ceil",
+            Function::Round => "round",
+        }
+    }
+
+    fn arguments(&self) -> usize {
+        match self {
+            Function::Min | Function::Max => 2,
+            _ => 1,
+        }
+    }
+
+    fn call(&self, args: &[f64]) -> f64 {
+        match self {
+            Function::Min => args[0].min(args[1]),
+            Function::Max => args[0].max(args[1]),
+            Function::Abs => args[0].abs(),
+            Function::Floor => args[0].floor(),
+            Function::Ceil => args[0].ceil(),
+            Function::Round => args[0].round(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Node {
+    Number(f64),
+    Field(String),
+    Negate(Box<Node>),
+    Not(Box<Node>),
+    Binary(&'static str, Box<Node>, Box<Node>),
+    Call(Function, Vec<Node>),
+}
+
+fn truth(value: bool) -> f64 {
+    if value { 1.0 } else { 0.0 }
+}
+
+impl Node {
+    fn evaluate(&self, field: &dyn Fn(&str) -> f64) -> f64 {
+        match self {
+            Node::Number(value) => *value,
+            Node::Field(name) => field(name),
+            Node::Negate(node) => -node.evaluate(field),
+            Node::Not(node) => truth(node.evaluate(field) == 0.0),
+            Node::Call(function, args) => {
+                let args = args.iter().map(|x| x.evaluate(field)).collect::<Vec<_>>();
+                function.call(&args)
+            }
+            Node::Binary(op, left, right) => {
+                let (a, b) = (left.evaluate(field), right.evaluate(field));
+
+                match *op {
+                    "+" => a + b,
+                    "-" => a - b,
+                    "*" => a * b,
+                    "/" => a / b,
+                    "%" => a % b,
+                    "==" => truth(a == b),
+                    "!=" => truth(a != b),
+                    "<" => truth(a < b),
+                    "<=" => truth(a <= b),
+                    ">" => truth(a > b),
+                    ">=" => truth(a >= b),
+                    "&&" => truth(a != 0.0 && b != 0.0),
+                    "||" => truth(a != 0.0 || b != 0.0),
+                    _ => unreachable!(),
+                }
+            }
+        }
+    }
+
+    fn fields<'a>(&'a self, fields: &mut Vec<&'a str>) {
+        match self {
+            Node::Number(_) => (),
+            Node::Field(name) if !fields.contains(&name.as_str()) => fields.push(name),
+            Node::Field(_) => (),
+            Node::Negate(node) | Node::Not(node) => node.fields(fields),
+            Node::Binary(_, left, right) => {
+                left.fields(fields);
+                right.fields(fields);
+            }
+            Node::Call(_, args) => {
+                for arg in args {
+                    arg.fields(fields);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Name(String),
+    Symbol(&'static str),
+    End,
+}
+
+// Longest first so `<=` is not read as `<`
+const SYMBOLS: [&str; 18] = [
+    "&&", "||", "==", "!=", "<=", ">=",
+    "<", ">", "+", "-", "*", "/", "%", "!", "(", ")", ",", "=",
+];
+
+fn tokenize(source: &str) -> Result<Vec<(usize, Token)>, ExpressionError> {
+    let mut tokens = vec![];
+    let mut rest = source;
+
+    while let Some(c) = rest.chars().next() {
+        let position = source.len() - rest.len();
+
+        if c.is_whitespace() {
+            rest = &rest[c.len_utf8()..];
+        }
+        else if c.is_ascii_digit() || c == '.' {
+            let end = rest.find(|x: char| !x.is_ascii_digit() && x != '.').unwrap_or(rest.len());
+            let value = rest[..end].parse::<f64>().map_err(|_| ExpressionError::Syntax {
+                position,
+                message: format!("'{}' is not a number", &rest[..end]),
+            })?;
+
+            tokens.push((position, Token::Number(value)));
+            rest = &rest[end..];
+        }
+        else if c.is_alphabetic() || c == '_' {
+            let end = rest.find(|x: char| !x.is_alphanumeric() && x != '_').unwrap_or(rest.len());
+            let token = match &rest[..end] {
+                "and" => Token::Symbol("&&"),
+                "or" => Token::Symbol("||"),
+                "not" => Token::Symbol("!"),
+                name => Token::Name(name.to_string()),
+            };
+
+            tokens.push((position, token));
+            rest = &rest[end..];
+        }
+        else {
+            let symbol = SYMBOLS.iter().find(|x| rest.starts_with(*x)).ok_or_else(|| ExpressionError::Syntax {
+                position,
+                message: format!("unexpected '{}'", c),
+            })?;
+
+            rest = &rest[symbol.len()..];
+
+            // A single `=` compares like `==`
+            let symbol = if *symbol == "=" { "==" } else { *symbol };
+            tokens.push((position, Token::Symbol(symbol)));
+        }
+    }
+
+    tokens.push((source.len(), Token::End));
+    Ok(tokens)
+}
+
+// Precedence from loosest to tightest, each level is left associative
+const LEVELS: [&[&str]; 5] = [
+    &["||"],
+    &["&&"],
+    &["==", "!=", "<", "<=", ">", ">="],
+    &["+", "-"],
+    &["*", "/", "%"],
+];
+
+fn error_at<T>(position: usize, message: &str) -> Result<T, ExpressionError> {
+    Err(ExpressionError::Syntax {
+        position,
+        message: message.to_string(),
+    })
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    next: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.next].1
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.tokens[self.next].1.clone();
+        if token != Token::End {
+            self.next += 1;
+        }
+
+        token
+    }
+
+    fn error<T>(&self, message: &str) -> Result<T, ExpressionError> {
+        error_at(self.tokens[self.next].0, message)
+    }
+
+    fn expect(&mut self, symbol: &'static str) -> Result<(), ExpressionError> {
+        match self.peek() {
+            Token::Symbol(x) if *x == symbol => {
+                self.advance();
+                Ok(())
+            }
+            _ => self.error(&format!("expected '{}'", symbol)),
+        }
+    }
+
+    fn binary(&mut self, level: usize) -> Result<Node, ExpressionError> {
+        if level == LEVELS.len() {
+            return self.unary();
+        }
+
+        let mut node = self.binary(level + 1)?;
+        loop {
+            let op = match self.peek() {
+                Token::Symbol(x) if LEVELS[level].contains(x) => *x,
+                _ => return Ok(node),
+            };
+
+            self.advance();
+            node = Node::Binary(op, Box::new(node), Box::new(self.binary(level + 1)?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Node, ExpressionError> {
+        match self.peek() {
+            Token::Symbol("-") => {
+                self.advance();
+                Ok(Node::Negate(Box::new(self.unary()?)))
+            }
+            Token::Symbol("!") => {
+                self.advance();
+                Ok(Node::Not(Box::new(self.unary()?)))
+            }
+            _ => self.primary(),
+        }
+    }
+
+    fn primary(&mut self) -> Result<Node, ExpressionError> {
+        match self.peek().clone() {
+            Token::Number(value) => {
+                self.advance();
+                Ok(Node::Number(value))
+            }
+            Token::Symbol("(") => {
+                self.advance();
+                let node = self.binary(0)?;
+                self.expect(")")?;
+                Ok(node)
+            }
+            Token::Name(name) => {
+                // Errors about a call point at the function's name
+                let position = self.tokens[self.next].0;
+                self.advance();
+
+                match name.as_str() {
+                    "true" => return Ok(Node::Number(1.0)),
+                    "false" => return Ok(Node::Number(0.0)),
+                    _ => (),
+                }
+
+                if self.peek() != &Token::Symbol("(") {
+                    return Ok(Node::Field(name));
+                }
+
+                let function = match Function::ALL.iter().find(|x| x.name() == name) {
+                    Some(function) => *function,
+                    None => return error_at(position, &format!("unknown function '{}'", name)),
+                };
+
+                self.advance();
+                let mut args = vec![self.binary(0)?];
+                while self.peek() == &Token::Symbol(",") {
+                    self.advance();
+                    args.push(self.binary(0)?);
+                }
+                self.expect(")")?;
+
+                if args.len() != function.arguments() {
+                    return error_at(position, &format!("'{}' takes {} arguments", name, function.arguments()));
+                }
+
+                Ok(Node::Call(function, args))
+            }
+            Token::End => self.error("unexpected end"),
+            Token::Symbol(symbol) => self.error(&format!("unexpected '{}'", symbol)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Expression {
+    source: String,
+    root: Node,
+}
+
+impl Expression {
+    pub fn parse(source: &str) -> Result<Expression, ExpressionError> {
+        let mut parser = Parser {
+            tokens: tokenize(source)?,
+            next: 0,
+        };
+
+        let root = parser.binary(0)?;
+        if parser.peek() != &Token::End {
+            return parser.error("expected an operator");
+        }
+
+        Ok(Expression {
+            source: source.trim().to_string(),
+            root,
+        })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    // Names of the fields used, in the order they appear
+    pub fn fields(&self) -> Vec<&str> {
+        let mut fields = vec![];
+        self.root.fields(&mut fields);
+        fields
+    }
+
+    pub fn evaluate(&self, field: &dyn Fn(&str) -> f64) -> f64 {
+        self.root.evaluate(field)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate(source: &str) -> f64 {
+        let values = |name: &str| match name {
+            "a" => 5.0,
+            "b" => 2.0,
+            _ => 0.0,
+        };
+
+        Expression::parse(source).unwrap().evaluate(&values)
+    }
+
+    fn syntax_error(source: &str) -> (usize, String) {
+        match Expression::parse(source) {
+            Err(ExpressionError::Syntax { position, message }) => (position, message),
+            other => panic!("expected a syntax error for '{}', found {:?}", source, other),
+        }
+    }
+
+    #[test]
+    fn operators_bind_by_precedence_and_from_the_left() {
+        assert_eq!(evaluate("1 + 2 * 3"), 7.0);
+        assert_eq!(evaluate("(1 + 2) * 3"), 9.0);
+        assert_eq!(evaluate("10 - 4 - 3"), 3.0);
+        assert_eq!(evaluate("8 / 4 / 2"), 1.0);
+        assert_eq!(evaluate("7 % 4 * 2"), 6.0);
+        assert_eq!(evaluate("a - b * 2 > 0"), 1.0);
+        assert_eq!(evaluate("1 + 1 == 2"), 1.0);
+        assert_eq!(evaluate("1 || 0 && 0"), 1.0);
+        assert_eq!(evaluate("0 && 1 || 1"), 1.0);
+        assert_eq!(evaluate("a > b && b > a"), 0.0);
+    }
+
+    #[test]
+    fn unary_operators_chain() {
+        assert_eq!(evaluate("--3"), 3.0);
+        assert_eq!(evaluate("- - -a"), -5.0);
+        assert_eq!(evaluate("!!a"), 1.0);
+        assert_eq!(evaluate("-!0"), -1.0);
+        assert_eq!(evaluate("!-b"), 0.0);
+        assert_eq!(evaluate("-b * 3"), -6.0);
+    }
+
+    #[test]
+    fn words_and_single_equals_are_aliases() {
+        let root = |source| Expression::parse(source).unwrap().root;
+
+        assert_eq!(root("a = b and not a or b"), root("a == b && !a || b"));
+        assert_eq!(evaluate("true and not false"), 1.0);
+    }
+
+    #[test]
+    fn functions_take_their_number_of_arguments() {
+        assert_eq!(evaluate("max(min(a, 3), abs(-b))"), 3.0);
+        assert_eq!(evaluate("floor(2.5) + ceil(2.5) + round(2.5)"), 8.0);
+
+        assert_eq!(syntax_error("1 + min(a)"), (4, "'min' takes 2 arguments".to_string()));
+        assert_eq!(syntax_error("abs(a, b)"), (0, "'abs' takes 1 arguments".to_string()));
+        assert_eq!(syntax_error("b * sqrt(a)"), (4, "unknown function 'sqrt'".to_string()));
+    }
+
+    #[test]
+    fn syntax_errors_point_at_the_problem() {
+        assert_eq!(syntax_error("a +"), (3, "unexpected end".to_string()));
+        assert_eq!(syntax_error("a b"), (2, "expected an operator".to_string()));
+        assert_eq!(syntax_error("(a + b"), (6, "expected ')'".to_string()));
+        assert_eq!(syntax_error("a $ b"), (2, "unexpected '$'".to_string()));
+        assert_eq!(syntax_error("* b"), (0, "unexpected '*'".to_string()));
+        assert_eq!(syntax_error("a + 1..2"), (4, "'1..2' is not a number".to_string()));
+        assert_eq!(syntax_error("max(a,)"), (6, "unexpected ')'".to_string()));
+    }
+
+    #[test]
+    fn fields_are_listed_once_in_order() {
+        let expression = Expression::parse("  b + a * b ").unwrap();
+
+        assert_eq!(expression.fields(), ["b", "a"]);
+        assert_eq!(expression.source(), "b + a * b");
+    }
+}
@@ -1,44 +1,478 @@
 use crate::shapes::*;
 use crate::data::*;
-use na::geometry::{Translation};
-use std::collections::hash_map::HashMap;
+use crate::category::BoxCategory;
+use na::geometry::{Translation, Isometry2, UnitComplex};
+use std::ops::Range;
 
+#[derive(Clone, Debug)]
 pub struct DataBox<S> {
+    // Relative to the parent anchor if there is one, otherwise to the frames pivot
     pub shape: BoundingBox<f32>,
     pub data: S, 
+    pub category: BoxCategory,
+    pub parent: Option<String>,
 }
 
 impl<S> DataBox<S> {
-    pub fn new(shape: BoundingBox<f32>, data: S) -> Self {
+    pub fn new(shape: BoundingBox<f32>, data: S, category: BoxCategory) -> Self {
         Self {
             shape,
             data,
+            category,
+            parent: None,
         }
     }
+
+    pub fn mirror(&mut self, axis: MirrorAxis) {
+        self.shape.mirror(axis);
+    }
+}
+
+// A named point on a frame such as `hand_r`, relative to the frames pivot
+#[derive(Clone, Debug)]
+pub struct Anchor {
+    pub name: String,
+    pub transformation: Isometry2<f32>,
+}
+
+impl Anchor {
+    pub fn new(name: String, transformation: Isometry2<f32>) -> Anchor {
+        Anchor {
+            name,
+            transformation,
+        }
+    }
+
+    pub fn mirror(&mut self, axis: MirrorAxis) {
+        let angle = self.transformation.rotation.angle();
+        let translation = &mut self.transformation.translation.vector;
+
+        match axis {
+            MirrorAxis::Horizontal => translation.x = -translation.x,
+            MirrorAxis::Vertical => translation.y = -translation.y,
+        }
+
+        self.transformation.rotation = UnitComplex::from_angle(-angle);
+    }
+}
+
+// Timed, non spatial data on the timeline such as sound cues or cancel windows.
+// A `length` of 0 is an instant event, otherwise it covers `start..start + length` ticks
+#[derive(Clone, Debug)]
+pub struct Marker<S> {
+    pub name: String,
+    pub start: u32,
+    pub length: u32,
+    pub data: S,
+}
+
+impl<S> Marker<S> {
+    pub fn new(name: String, start: u32, length: u32, data: S) -> Self {
+        Self {
+            name,
+            start,
+            length,
+            data,
+        }
+    }
+
+    pub fn is_instant(&self) -> bool {
+        self.length == 0
+    }
+
+    pub fn active_on(&self, tick: u32) -> bool {
+        match self.length {
+            0 => tick == self.start,
+            length => tick >= self.start && tick < self.start + length,
+        }
+    }
+}
+
+// Where a frames pivot sits relative to its rect when aligning pivots between frames
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PivotAnchor {
+    TopLeft,
+    Centre,
+    BottomCentre,
 }
 
+impl PivotAnchor {
+    fn position(&self, rect: &AABB<u32>) -> [i64; 2] {
+        let (width, height) = (rect.rect.width as i64, rect.rect.height as i64);
+
+        match self {
+            PivotAnchor::TopLeft => [0, 0],
+            PivotAnchor::Centre => [width / 2, height / 2],
+            PivotAnchor::BottomCentre => [width / 2, height],
+        }
+    }
+}
+
+// The pivot (`centre`) is in pixels relative to the top left of `rect`, and is the origin
+// that all `DataBox` coordinates are relative to
+#[derive(Clone, Debug)]
 pub struct Frame {
     centre: [u32; 2],
     rect: AABB<u32>,    
     active_hitboxes: Vec<usize>,
+    // Number of ticks the frame is shown for
+    duration: u16,
+    // Whether the sprite should be drawn flipped [horizontally, vertically]
+    flipped: [bool; 2],
+    anchors: Vec<Anchor>,
 }
 
+impl Frame {
+    pub fn new(rect: AABB<u32>) -> Frame {
+        let centre = [rect.rect.width / 2, rect.rect.height / 2];
+
+        Frame {
+            centre,
+            rect,
+            active_hitboxes: vec![],
+            duration: 1,
+            flipped: [false, false],
+            anchors: vec![],
+        }
+    }
+
+    pub fn anchors(&self) -> &[Anchor] {
+        &self.anchors
+    }
+
+    pub fn anchor(&self, name: &str) -> Option<&Anchor> {
+        self.anchors.iter().find(|x| x.name == name)
+    }
+
+    pub fn anchor_mut(&mut self, name: &str) -> Option<&mut Anchor> {
+        self.anchors.iter_mut().find(|x| x.name == name)
+    }
+
+    // Replaces the anchor if one with the same name already exists
+    pub fn set_anchor(&mut self, name: &str, transformation: Isometry2<f32>) {
+        match self.anchor_mut(name) {
+            Some(anchor) => anchor.transformation = transformation,
+            None => self.anchors.push(Anchor::new(name.to_string(), transformation)),
+        }
+    }
+
+    pub fn remove_anchor(&mut self, name: &str) {
+        self.anchors.retain(|x| x.name != name);
+    }
+
+    // Transformation from anchor space into pivot space, identity if the frame lacks the anchor
+    pub fn anchor_transformation(&self, name: &str) -> Isometry2<f32> {
+        self.anchor(name).map(|x| x.transformation).unwrap_or_else(Isometry2::identity)
+    }
+
+    pub fn flipped(&self) -> [bool; 2] {
+        self.flipped
+    }
+
+    pub fn set_flipped(&mut self, flipped: [bool; 2]) {
+        self.flipped = flipped;
+    }
+
+    // Flips the sprite about the pivot, keeping the pivot on the same pixel of the flipped sprite
+    pub fn mirror(&mut self, axis: MirrorAxis) {
+        match axis {
+            MirrorAxis::Horizontal => {
+                self.centre[0] = self.rect.rect.width.saturating_sub(self.centre[0]);
+                self.flipped[0] = !self.flipped[0];
+            }
+            MirrorAxis::Vertical => {
+                self.centre[1] = self.rect.rect.height.saturating_sub(self.centre[1]);
+                self.flipped[1] = !self.flipped[1];
+            }
+        }
+
+        for anchor in self.anchors.iter_mut() {
+            anchor.mirror(axis);
+        }
+    }
+
+    pub fn duration(&self) -> u16 {
+        self.duration
+    }
+
+    pub fn set_duration(&mut self, duration: u16) {
+        self.duration = duration.max(1);
+    }
+
+    pub fn rect(&self) -> &AABB<u32> {
+        &self.rect
+    }
+
+    pub fn centre(&self) -> [u32; 2] {
+        self.centre
+    }
+
+    pub fn set_centre(&mut self, centre: [u32; 2]) {
+        self.centre = centre;
+    }
+
+    // Offset from the pivot to the top left of the frame, in sprite space
+    pub fn rect_offset(&self) -> [f32; 2] {
+        [-(self.centre[0] as f32), -(self.centre[1] as f32)]
+    }
+
+    // Changes the rect while keeping the pivot on the same pixel of the sprite sheet
+    pub fn set_rect(&mut self, rect: AABB<u32>) {
+        let x = self.rect.translation.vector.x as i64 + self.centre[0] as i64 - rect.translation.vector.x as i64;
+        let y = self.rect.translation.vector.y as i64 + self.centre[1] as i64 - rect.translation.vector.y as i64;
+
+        self.centre = [x.max(0) as u32, y.max(0) as u32];
+        self.rect = rect;
+    }
+
+    pub fn active_hitboxes(&self) -> &[usize] {
+        &self.active_hitboxes
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct AnimationData {
+    pub name: String,
     frame_data: Vec<Frame>,
-    data_boxes: Vec<DataBox<(String, u32)>>,
-    index_generators: HashMap<String, IndexGenerator>,
-    data: DataStorage<u32>,
+    data_boxes: Vec<DataBox<(String, RecordId)>>,
+    markers: Vec<Marker<(String, RecordId)>>,
+    data: DataStorage<RecordId>,
     fps: u16,
+    pub sprite_sheet: Option<String>,
 }
 
 impl AnimationData {
-    pub fn new(fps: u16) -> AnimationData {
+    pub fn new(name: String, fps: u16) -> AnimationData {
         AnimationData {
+            name,
             frame_data: vec![],
             data_boxes: vec![],
-            index_generators: HashMap::new(),
+            markers: vec![],
             data: DataStorage::new(),
             fps,
+            sprite_sheet: None,
+        }
+    }
+
+    pub fn frames(&self) -> &[Frame] {
+        &self.frame_data
+    }
+
+    pub fn frame(&self, index: usize) -> Option<&Frame> {
+        self.frame_data.get(index)
+    }
+
+    pub fn frame_mut(&mut self, index: usize) -> Option<&mut Frame> {
+        self.frame_data.get_mut(index)
+    }
+
+    // Ticks per second
+    pub fn fps(&self) -> u16 {
+        self.fps
+    }
+
+    pub fn set_fps(&mut self, fps: u16) {
+        self.fps = fps.max(1);
+    }
+
+    // Changes the tick rate keeping frames and markers at the same times. Tick boundaries are
+    // rounded so rounding errors don't add up over the animation, frames last at least one tick
+    pub fn convert_fps(&mut self, fps: u16) {
+        let ratio = fps.max(1) as f64 / self.fps as f64;
+        let convert = |tick: u32| (tick as f64 * ratio).round() as u32;
+
+        let mut start = 0;
+        let mut converted_start = 0;
+        for frame in self.frame_data.iter_mut() {
+            let end = start + frame.duration as u32;
+            let converted_end = convert(end).max(converted_start + 1);
+
+            frame.duration = (converted_end - converted_start).min(u16::max_value() as u32) as u16;
+            start = end;
+            converted_start = converted_end;
+        }
+
+        // Markers with a length keep one so they don't become instant events
+        for marker in self.markers.iter_mut() {
+            let end = convert(marker.start.saturating_add(marker.length));
+            let start = convert(marker.start);
+            marker.length = match marker.length {
+                0 => 0,
+                _ => end.saturating_sub(start).max(1),
+            };
+            marker.start = start;
+        }
+
+        self.set_fps(fps);
+    }
+
+    // Mirrors every frame and box about their pivots
+    pub fn mirror(&mut self, axis: MirrorAxis) {
+        for frame in self.frame_data.iter_mut() {
+            frame.mirror(axis);
+        }
+
+        for data_box in self.data_boxes.iter_mut() {
+            data_box.mirror(axis);
+        }
+    }
+
+    pub fn set_pivot_range(&mut self, start: usize, end: usize, centre: [u32; 2]) {
+        for frame in self.frame_data.iter_mut().take(end + 1).skip(start) {
+            frame.centre = centre;
+        }
+    }
+
+    // Places every frames pivot at the same offset from `anchor` as the reference frame
+    pub fn align_pivots(&mut self, reference: usize, anchor: PivotAnchor) {
+        let offset = match self.frame_data.get(reference) {
+            Some(frame) => {
+                let position = anchor.position(&frame.rect);
+                [frame.centre[0] as i64 - position[0], frame.centre[1] as i64 - position[1]]
+            }
+            None => return,
+        };
+
+        for frame in self.frame_data.iter_mut() {
+            let position = anchor.position(&frame.rect);
+            let x = position[0] + offset[0];
+            let y = position[1] + offset[1];
+
+            frame.centre = [x.max(0) as u32, y.max(0) as u32];
+        }
+    }
+
+    pub fn total_ticks(&self) -> u32 {
+        self.frame_data.iter().map(|x| x.duration as u32).sum()
+    }
+
+    pub fn frame_start_tick(&self, frame: usize) -> u32 {
+        self.frame_data.iter().take(frame).map(|x| x.duration as u32).sum()
+    }
+
+    // The frame shown on the given tick, None if the tick is past the end of the animation
+    pub fn frame_at_tick(&self, tick: u32) -> Option<usize> {
+        let mut end = 0;
+
+        for (i, frame) in self.frame_data.iter().enumerate() {
+            end += frame.duration as u32;
+
+            if tick < end {
+                return Some(i);
+            }
+        }
+
+        None
+    }
+
+    pub fn push_frame(&mut self, frame: Frame) {
+        self.frame_data.push(frame);
+    }
+
+    pub fn data_boxes(&self) -> &[DataBox<(String, RecordId)>] {
+        &self.data_boxes
+    }
+
+    pub fn data_boxes_mut(&mut self) -> &mut [DataBox<(String, RecordId)>] {
+        &mut self.data_boxes
+    }
+
+    pub fn data(&self) -> &DataStorage<RecordId> {
+        &self.data
+    }
+
+    pub fn data_mut(&mut self) -> &mut DataStorage<RecordId> {
+        &mut self.data
+    }
+
+    // Contiguous runs of frames the box is active on
+    pub fn activation_ranges(&self, data_box: usize) -> Vec<Range<usize>> {
+        let mut ranges: Vec<Range<usize>> = vec![];
+
+        for (i, frame) in self.frame_data.iter().enumerate() {
+            if !frame.active_hitboxes.contains(&data_box) {
+                continue;
+            }
+
+            match ranges.last_mut() {
+                Some(range) if range.end == i => range.end = i + 1,
+                _ => ranges.push(i..i + 1),
+            }
+        }
+
+        ranges
+    }
+
+    // Same as `activation_ranges` but in ticks, using each frames duration
+    pub fn activation_tick_ranges(&self, data_box: usize) -> Vec<Range<u32>> {
+        self.activation_ranges(data_box).into_iter().map(|range| {
+            self.frame_start_tick(range.start)..self.frame_start_tick(range.end)
+        }).collect()
+    }
+
+    pub fn boxes_in_category<'a>(&'a self, category: BoxCategory) -> impl Iterator<Item = (usize, &'a DataBox<(String, RecordId)>)> + 'a {
+        self.data_boxes.iter().enumerate().filter(move |(_, x)| x.category == category)
+    }
+
+    pub fn active_boxes<'a>(&'a self, frame: usize) -> impl Iterator<Item = &'a DataBox<(String, RecordId)>> + 'a {
+        let active: &'a [usize] = self.frame(frame).map(|x| x.active_hitboxes()).unwrap_or(&[]);
+
+        active.iter().filter_map(move |index| self.data_boxes.get(*index))
+    }
+
+    // The shape of the box relative to the frames pivot, going through its parent anchor
+    pub fn world_shape<S>(&self, data_box: &DataBox<S>, frame: usize) -> BoundingBox<f32> {
+        match (&data_box.parent, self.frame(frame)) {
+            (Some(parent), Some(frame)) => data_box.shape.transformed(&frame.anchor_transformation(parent)),
+            _ => data_box.shape,
+        }
+    }
+
+    pub fn is_box_active(&self, data_box: usize, frame: usize) -> bool {
+        self.frame(frame).map(|x| x.active_hitboxes.contains(&data_box)).unwrap_or(false)
+    }
+
+    pub fn set_box_active(&mut self, data_box: usize, frame: usize, active: bool) {
+        if data_box >= self.data_boxes.len() {
+            return;
+        }
+
+        if let Some(frame) = self.frame_data.get_mut(frame) {
+            let contains = frame.active_hitboxes.contains(&data_box);
+
+            if active && !contains {
+                frame.active_hitboxes.push(data_box);
+            }
+            else if !active && contains {
+                frame.active_hitboxes.retain(|x| *x != data_box);
+            }
+        }
+    }
+
+    // Keeps the box where it is on `frame` while removing its parent
+    pub fn unparent_box(&mut self, data_box: usize, frame: usize) {
+        let shape = match self.data_boxes.get(data_box) {
+            Some(value) => self.world_shape(value, frame),
+            None => return,
+        };
+
+        let value = &mut self.data_boxes[data_box];
+        value.shape = shape;
+        value.parent = None;
+    }
+
+    // Keeps the box where it is on `frame` while attaching it to the anchor
+    pub fn reparent_box(&mut self, data_box: usize, frame: usize, anchor: &str) {
+        self.unparent_box(data_box, frame);
+
+        let inverse = match self.frame(frame) {
+            Some(value) => value.anchor_transformation(anchor).inverse(),
+            None => return,
+        };
+
+        if let Some(value) = self.data_boxes.get_mut(data_box) {
+            value.shape = value.shape.transformed(&inverse);
+            value.parent = Some(anchor.to_string());
         }
     }
 
@@ -46,37 +480,236 @@ impl AnimationData {
         self.data.new_data_type(name);
     }
 
-    pub fn generate_data(&mut self, dtype: &str) -> Option<(String, u32)> {
-        let index_gen = self.index_generators.entry(dtype.to_string()).or_insert(IndexGenerator::new());
-        let index = index_gen.next_index();
-
+    pub fn generate_data(&mut self, dtype: &str) -> Option<(String, RecordId)> {
         if let Some(ref mut data) = self.data.data.get_mut(dtype) {
-            data.gen_new(index);
-            Some((dtype.to_string(), index))
+            let id = data.new_id();
+            data.gen_new(id);
+            Some((dtype.to_string(), id))
         }
         else {
             None
         }
     }
 
-    pub fn new_data_box(&mut self, dtype: &str, bounding_box: BoundingBox<f32>) {
-        let data = match self.generate_data(dtype) {
-            Some(data) => data,
-            None => return,
-        };
+    // Adds a box whose record already exists in the storage, as when loading a project
+    pub fn push_data_box(&mut self, data_box: DataBox<(String, RecordId)>) -> usize {
+        self.data_boxes.push(data_box);
+        self.data_boxes.len() - 1
+    }
+
+    pub fn push_marker(&mut self, marker: Marker<(String, RecordId)>) -> usize {
+        self.markers.push(marker);
+        self.markers.len() - 1
+    }
+
+    pub fn new_data_box(&mut self, dtype: &str, bounding_box: BoundingBox<f32>, category: BoxCategory) -> Option<usize> {
+        let data = self.generate_data(dtype)?;
+        self.data_boxes.push(DataBox::new(bounding_box, data, category));
 
-        self.data_boxes.push(DataBox::new(bounding_box, data));
+        Some(self.data_boxes.len() - 1)
+    }
+
+    pub fn markers(&self) -> &[Marker<(String, RecordId)>] {
+        &self.markers
+    }
+
+    pub fn markers_mut(&mut self) -> &mut [Marker<(String, RecordId)>] {
+        &mut self.markers
+    }
+
+    pub fn markers_on_tick<'a>(&'a self, tick: u32) -> impl Iterator<Item = &'a Marker<(String, RecordId)>> + 'a {
+        self.markers.iter().filter(move |x| x.active_on(tick))
+    }
+
+    pub fn new_marker(&mut self, name: String, dtype: &str, start: u32, length: u32) -> Option<usize> {
+        let data = self.generate_data(dtype)?;
+        self.markers.push(Marker::new(name, start, length, data));
+
+        Some(self.markers.len() - 1)
+    }
+
+    // A record nothing refers to can't be reached again, so it would only linger
+    fn remove_unused_record(&mut self, (dtype, id): &(String, RecordId)) {
+        let used = self.data_boxes.iter().map(|x| &x.data).chain(self.markers.iter().map(|x| &x.data))
+            .any(|(x, y)| x == dtype && y == id);
+
+        if let (false, Some(storage)) = (used, self.data.get_storage_mut(dtype)) {
+            storage.remove(id);
+        }
+    }
+
+    pub fn remove_marker(&mut self, index: usize) {
+        if index < self.markers.len() {
+            let marker = self.markers.remove(index);
+            self.remove_unused_record(&marker.data);
+        }
     }
 
     pub fn remove_data_box(&mut self, index: usize) {
         if index < self.data_boxes.len() {
             let data_box = self.data_boxes.remove(index);
-            let (dtype, index) = data_box.data;
+            let removed = index;
+
+            for frame in self.frame_data.iter_mut() {
+                frame.active_hitboxes.retain(|x| *x != removed);
 
-            if let Some(gen) = self.index_generators.get_mut(&dtype) {
-                gen.remove_index(index);
+                for active in frame.active_hitboxes.iter_mut() {
+                    if *active > removed {
+                        *active -= 1;
+                    }
+                }
             }
+
+            self.remove_unused_record(&data_box.data);
         }
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use na::Vector2;
+    use super::*;
+
+    const AXES: [MirrorAxis; 2] = [MirrorAxis::Horizontal, MirrorAxis::Vertical];
+
+    // Rotations are compared by sine and cosine so angles either side of pi are equal
+    fn assert_close(a: &Isometry2<f32>, b: &Isometry2<f32>) {
+        let (r, s) = (a.rotation, b.rotation);
+        assert!((a.translation.vector - b.translation.vector).norm() < 1e-4, "{:?} != {:?}", a, b);
+        assert!((r.cos_angle() - s.cos_angle()).abs() < 1e-4 && (r.sin_angle() - s.sin_angle()).abs() < 1e-4, "{:?} != {:?}", a, b);
+    }
+
+    fn assert_frames_close(a: &Frame, b: &Frame) {
+        assert_eq!(a.centre(), b.centre());
+        assert_eq!(a.flipped(), b.flipped());
+        assert_eq!(a.anchors().len(), b.anchors().len());
+
+        for (x, y) in a.anchors().iter().zip(b.anchors().iter()) {
+            assert_eq!(x.name, y.name);
+            assert_close(&x.transformation, &y.transformation);
+        }
+    }
+
+    fn frame() -> Frame {
+        let mut frame = Frame::new(AABB::new(64, 0, 32, 48));
+        frame.set_centre([10, 40]);
+        frame.set_flipped([false, true]);
+        frame.set_anchor("hand_r", Isometry2::new(Vector2::new(5.0, -12.0), 0.7));
+        frame.set_anchor("foot_l", Isometry2::new(Vector2::new(-3.5, 0.0), -2.9));
+
+        frame
+    }
+
+    fn shape(x: f32, y: f32, angle: f32) -> BoundingBox<f32> {
+        BoundingBox::new(Cuboid::new(12.0, 5.0), Isometry2::new(Vector2::new(x, y), angle))
+    }
+
+    // Two frames, a box on the pivot and a box on `hand_r`
+    fn animation() -> AnimationData {
+        let mut animation = AnimationData::new("attack".to_string(), 60);
+        animation.push_frame(frame());
+        animation.push_frame(Frame::new(AABB::new(0, 0, 16, 16)));
+        animation.new_data_type("Hit".to_string());
+
+        animation.new_data_box("Hit", shape(7.5, -3.25, 0.4), BoxCategory::Hitbox).unwrap();
+        let parented = animation.new_data_box("Hit", shape(2.0, 1.0, -1.2), BoxCategory::Hurtbox).unwrap();
+        animation.data_boxes_mut()[parented].parent = Some("hand_r".to_string());
+
+        animation
+    }
+
+    #[test]
+    fn mirroring_an_anchor_twice_is_the_identity() {
+        let anchor = Anchor::new("hand_r".to_string(), Isometry2::new(Vector2::new(5.0, -12.0), 0.7));
+
+        for axis in AXES.iter() {
+            let mut mirrored = anchor.clone();
+            mirrored.mirror(*axis);
+            mirrored.mirror(*axis);
+
+            assert_close(&mirrored.transformation, &anchor.transformation);
+        }
+    }
+
+    #[test]
+    fn mirroring_a_data_box_twice_is_the_identity() {
+        let mut data_box = DataBox::new(shape(7.5, -3.25, 3.1), (), BoxCategory::Hitbox);
+        data_box.parent = Some("hand_r".to_string());
+
+        for axis in AXES.iter() {
+            let mut mirrored = data_box.clone();
+            mirrored.mirror(*axis);
+            mirrored.mirror(*axis);
+
+            assert_close(&mirrored.shape.transformation, &data_box.shape.transformation);
+            assert_eq!(mirrored.parent, data_box.parent);
+        }
+    }
+
+    #[test]
+    fn mirroring_a_frame_twice_is_the_identity() {
+        let original = frame();
+
+        for axis in AXES.iter() {
+            let mut mirrored = frame();
+            mirrored.mirror(*axis);
+            mirrored.mirror(*axis);
+
+            assert_frames_close(&mirrored, &original);
+        }
+
+        let mut mirrored = frame();
+        for axis in AXES.iter().chain(AXES.iter()) {
+            mirrored.mirror(*axis);
+        }
+        assert_frames_close(&mirrored, &original);
+    }
+
+    #[test]
+    fn mirroring_an_animation_twice_is_the_identity() {
+        let original = animation();
+
+        for axis in AXES.iter() {
+            let mut mirrored = animation();
+            mirrored.mirror(*axis);
+            mirrored.mirror(*axis);
+
+            for (a, b) in mirrored.frames().iter().zip(original.frames().iter()) {
+                assert_frames_close(a, b);
+            }
+
+            for (a, b) in mirrored.data_boxes().iter().zip(original.data_boxes().iter()) {
+                assert_close(&a.shape.transformation, &b.shape.transformation);
+                assert_eq!(a.parent, b.parent);
+            }
+        }
+    }
+
+    #[test]
+    fn mirroring_an_animation_mirrors_parented_boxes_in_world_space() {
+        let original = animation();
+        let mut mirrored = animation();
+        mirrored.mirror(MirrorAxis::Horizontal);
+
+        for (a, b) in mirrored.data_boxes().iter().zip(original.data_boxes().iter()) {
+            let expected = original.world_shape(b, 0).mirrored(MirrorAxis::Horizontal);
+            assert_close(&mirrored.world_shape(a, 0).transformation, &expected.transformation);
+        }
+    }
+
+    #[test]
+    fn converting_fps_keeps_markers_with_a_length() {
+        let mut animation = animation();
+        animation.new_marker("cue".to_string(), "Hit", 0, 0).unwrap();
+        animation.new_marker("cancel".to_string(), "Hit", 31, 1).unwrap();
+        animation.new_marker("loop".to_string(), "Hit", u32::max_value() - 1, 10).unwrap();
+
+        animation.convert_fps(12);
+
+        let markers = animation.markers().iter().map(|x| (x.start, x.length)).collect::<Vec<_>>();
+        assert_eq!(markers[0], (0, 0));
+        assert_eq!(markers[1], (6, 1));
+        assert!(markers[2].1 >= 1);
+    }
+}
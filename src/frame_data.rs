@@ -0,0 +1,73 @@
+use crate::{
+    category::BoxCategory,
+    frame::AnimationData,
+};
+
+// Names of the fields read from a hitboxes data record for frame advantage
+pub const HITSTUN_FIELD: &str = "hitstun";
+pub const BLOCKSTUN_FIELD: &str = "blockstun";
+
+// All values are in ticks. Startup counts up to and including the first active tick, so a
+// move with a startup of 5 hits on its 5th tick
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct FrameDataSummary {
+    pub total: u32,
+    pub startup: Option<u32>,
+    pub active: Option<u32>,
+    pub recovery: Option<u32>,
+    pub on_hit: Option<i32>,
+    pub on_block: Option<i32>,
+}
+
+impl FrameDataSummary {
+    pub fn summarize(animation: &AnimationData) -> FrameDataSummary {
+        let total = animation.total_ticks();
+        let mut first_active: Option<(u32, usize)> = None;
+        let mut last_active: Option<u32> = None;
+
+        for (index, _) in animation.boxes_in_category(BoxCategory::Hitbox) {
+            for range in animation.activation_tick_ranges(index) {
+                if first_active.map(|(tick, _)| range.start < tick).unwrap_or(true) {
+                    first_active = Some((range.start, index));
+                }
+
+                if last_active.map(|tick| range.end - 1 > tick).unwrap_or(true) {
+                    last_active = Some(range.end - 1);
+                }
+            }
+        }
+
+        let (first, hitbox) = match (first_active, last_active) {
+            (Some(first), Some(_)) => first,
+            _ => return FrameDataSummary {
+                total,
+                startup: None,
+                active: None,
+                recovery: None,
+                on_hit: None,
+                on_block: None,
+            },
+        };
+        let last = last_active.unwrap();
+
+        // Ticks the attacker is still busy for after connecting on the first active tick
+        let remaining = (total - first - 1) as i32;
+        let stun = |field: &str| {
+            let (dtype, key) = &animation.data_boxes()[hitbox].data;
+
+            animation.data().get_storage(dtype)
+                .and_then(|storage| storage.get_field(key, field))
+                .and_then(|value| value.as_f64())
+                .map(|value| value.round() as i32 - remaining)
+        };
+
+        FrameDataSummary {
+            total,
+            startup: Some(first + 1),
+            active: Some(last - first + 1),
+            recovery: Some(total - last - 1),
+            on_hit: stun(HITSTUN_FIELD),
+            on_block: stun(BLOCKSTUN_FIELD),
+        }
+    }
+}
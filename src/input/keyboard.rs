@@ -51,4 +51,10 @@ impl Keys {
             _ => (),
         }
     }
+}
+
+impl Default for Keys {
+    fn default() -> Self {
+        Self::new()
+    }
 }
\ No newline at end of file
@@ -44,6 +44,12 @@ impl Pressed {
     }
 }
 
+impl Default for Pressed {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
 pub struct Button {
     pub pressed: Pressed,
@@ -95,6 +101,12 @@ impl Button {
     }
 }
 
+impl Default for Button {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 pub struct UserInput {
     pub keyboard: keyboard::Keys,
     pub mouse: mouse::Mouse,
@@ -132,4 +144,10 @@ impl UserInput {
             _ => {}
         }
     }
+}
+
+impl Default for UserInput {
+    fn default() -> Self {
+        Self::new()
+    }
 }
\ No newline at end of file
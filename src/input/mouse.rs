@@ -28,6 +28,12 @@ impl MouseWheel {
     }
 }
 
+impl Default for MouseWheel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Mouse {
     // Stored as top left is (0, 0) bottom right (window dimenisons)
@@ -196,6 +202,12 @@ impl Mouse {
     }
 }
 
+impl Default for Mouse {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 enum ButtonIndex {
     Left,
     Right, 
@@ -0,0 +1,48 @@
+#![allow(dead_code)]
+#[macro_use]
+extern crate imgui;
+extern crate imgui_glium_renderer as imrender;
+extern crate nalgebra_glm as glm;
+extern crate nalgebra as na;
+
+// The project model and the export formats, for crates registering their own formats
+pub mod shapes;
+pub mod frame;
+pub mod data;
+pub mod expression;
+pub mod category;
+pub mod project;
+pub mod project_file;
+pub mod lint;
+pub mod export;
+
+mod support;
+mod window;
+mod input;
+mod state;
+mod ui;
+mod util;
+mod render;
+mod frame_data;
+mod simulator;
+mod validate;
+mod cli;
+mod diff;
+mod merge;
+mod query;
+mod script;
+mod watch;
+mod editor;
+
+use export::registry::Registry;
+
+// Runs the command given on the command line, or the editor when there is none, with the formats
+// of the registry. Crates adding their own formats call this from their `main`
+pub fn run(registry: Registry) {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if !args.is_empty() {
+        std::process::exit(cli::run(&args, &registry));
+    }
+
+    editor::run(&registry);
+}
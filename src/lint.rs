@@ -0,0 +1,316 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
+use crate::{
+    category::BoxCategory,
+    data::RecordId,
+    frame::AnimationData,
+    project::Project,
+};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    // The rule is not run
+    Allow,
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub const ALL: [Severity; 4] = [Severity::Allow, Severity::Info, Severity::Warning, Severity::Error];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Severity::Allow => "allow",
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Severity> {
+        Severity::ALL.iter().find(|x| x.name() == name).cloned()
+    }
+}
+
+// What a problem is about, used to jump to it in the editor
+#[derive(Clone, Debug, PartialEq)]
+pub enum Location {
+    Animation,
+    Frame(usize),
+    Box(usize),
+    BoxOnFrame { data_box: usize, frame: usize },
+    Record { dtype: String, key: RecordId },
+    Type(String),
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Location::Animation => write!(f, "animation"),
+            Location::Frame(frame) => write!(f, "frame {}", frame),
+            Location::Box(data_box) => write!(f, "box {}", data_box),
+            Location::BoxOnFrame { data_box, frame } => write!(f, "box {} on frame {}", data_box, frame),
+            Location::Record { dtype, key } => write!(f, "record {} of '{}'", key, dtype),
+            Location::Type(dtype) => write!(f, "type '{}'", dtype),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Problem {
+    pub rule: &'static str,
+    pub severity: Severity,
+    // Index into `Project::animations`
+    pub animation: usize,
+    pub location: Location,
+    pub message: String,
+}
+
+impl Problem {
+    pub fn describe(&self, project: &Project) -> String {
+        let animation = project.animations.get(self.animation).map(|x| x.name.as_str()).unwrap_or("?");
+        format!("{}[{}]: {}: {}: {}", self.severity.name(), self.rule, animation, self.location, self.message)
+    }
+}
+
+pub type RuleCheck = fn(&AnimationData, &mut Vec<(Location, String)>);
+
+pub struct Rule {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub default_severity: Severity,
+    pub check: RuleCheck,
+}
+
+// Severities that differ from a rules default, saved with the project
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LintSettings {
+    pub severities: BTreeMap<String, Severity>,
+}
+
+impl LintSettings {
+    pub fn severity(&self, rule: &Rule) -> Severity {
+        self.severities.get(rule.name).cloned().unwrap_or(rule.default_severity)
+    }
+
+    pub fn set_severity(&mut self, rule: &Rule, severity: Severity) {
+        match severity == rule.default_severity {
+            true => self.severities.remove(rule.name),
+            false => self.severities.insert(rule.name.to_string(), severity),
+        };
+    }
+}
+
+pub struct Linter {
+    rules: Vec<Rule>,
+}
+
+impl Linter {
+    pub fn new() -> Linter {
+        Linter {
+            rules: vec![],
+        }
+    }
+
+    pub fn with_builtin_rules() -> Linter {
+        let mut linter = Linter::new();
+        for rule in builtin_rules() {
+            linter.register(rule);
+        }
+
+        linter
+    }
+
+    // Replaces any rule with the same name
+    pub fn register(&mut self, rule: Rule) {
+        self.rules.retain(|x| x.name != rule.name);
+        self.rules.push(rule);
+    }
+
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    pub fn rule(&self, name: &str) -> Option<&Rule> {
+        self.rules.iter().find(|x| x.name == name)
+    }
+
+    // Most severe first, then in project order
+    pub fn run(&self, project: &Project, settings: &LintSettings) -> Vec<Problem> {
+        let mut problems = vec![];
+
+        for (index, animation) in project.animations.iter().enumerate() {
+            for rule in self.rules.iter() {
+                let severity = settings.severity(rule);
+                if severity == Severity::Allow {
+                    continue;
+                }
+
+                let mut found = vec![];
+                (rule.check)(animation, &mut found);
+
+                problems.extend(found.into_iter().map(|(location, message)| Problem {
+                    rule: rule.name,
+                    severity,
+                    animation: index,
+                    location,
+                    message,
+                }));
+            }
+        }
+
+        problems.sort_by(|a, b| b.severity.cmp(&a.severity).then(a.animation.cmp(&b.animation)));
+        problems
+    }
+}
+
+impl Default for Linter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn builtin_rules() -> Vec<Rule> {
+    vec![
+        Rule {
+            name: "zero_size_box",
+            description: "Boxes with no width or height",
+            default_severity: Severity::Warning,
+            check: zero_size_box,
+        },
+        Rule {
+            name: "inactive_box",
+            description: "Boxes that are not active on any frame",
+            default_severity: Severity::Warning,
+            check: inactive_box,
+        },
+        Rule {
+            name: "frame_without_hurtbox",
+            description: "Frames without an active hurtbox",
+            default_severity: Severity::Info,
+            check: frame_without_hurtbox,
+        },
+        Rule {
+            name: "hitbox_outside_frame",
+            description: "Hitboxes reaching outside of the frame rect",
+            default_severity: Severity::Warning,
+            check: hitbox_outside_frame,
+        },
+        Rule {
+            name: "orphaned_record",
+            description: "Data records no box or marker refers to",
+            default_severity: Severity::Warning,
+            check: orphaned_record,
+        },
+        Rule {
+            name: "duplicate_index",
+            description: "Records shared by more than one box or marker",
+            default_severity: Severity::Error,
+            check: duplicate_index,
+        },
+        Rule {
+            name: "unreferenced_type",
+            description: "Data types no box or marker uses",
+            default_severity: Severity::Info,
+            check: unreferenced_type,
+        },
+    ]
+}
+
+// Every record reference of the boxes and then the markers
+fn references<'a>(animation: &'a AnimationData) -> impl Iterator<Item = &'a (String, RecordId)> + 'a {
+    animation.data_boxes().iter().map(|x| &x.data).chain(animation.markers().iter().map(|x| &x.data))
+}
+
+fn zero_size_box(animation: &AnimationData, problems: &mut Vec<(Location, String)>) {
+    for (i, data_box) in animation.data_boxes().iter().enumerate() {
+        let rect = data_box.shape.rect;
+        if rect.width <= 0.0 || rect.height <= 0.0 {
+            problems.push((Location::Box(i), format!("size is {}x{}", rect.width, rect.height)));
+        }
+    }
+}
+
+fn inactive_box(animation: &AnimationData, problems: &mut Vec<(Location, String)>) {
+    for i in 0..animation.data_boxes().len() {
+        if animation.activation_ranges(i).is_empty() {
+            problems.push((Location::Box(i), "never active".to_string()));
+        }
+    }
+}
+
+fn frame_without_hurtbox(animation: &AnimationData, problems: &mut Vec<(Location, String)>) {
+    for i in 0..animation.frames().len() {
+        if !animation.active_boxes(i).any(|x| x.category == BoxCategory::Hurtbox) {
+            problems.push((Location::Frame(i), "no active hurtbox".to_string()));
+        }
+    }
+}
+
+fn hitbox_outside_frame(animation: &AnimationData, problems: &mut Vec<(Location, String)>) {
+    for (data_box, value) in animation.boxes_in_category(BoxCategory::Hitbox) {
+        for range in animation.activation_ranges(data_box) {
+            for frame in range {
+                let rect = animation.frames()[frame].rect().rect;
+                let [left, top] = animation.frames()[frame].rect_offset();
+                let (right, bottom) = (left + rect.width as f32, top + rect.height as f32);
+
+                let outside = animation.world_shape(value, frame).corners().iter()
+                    .any(|x| x.x < left || x.x > right || x.y < top || x.y > bottom);
+
+                if outside {
+                    problems.push((Location::BoxOnFrame { data_box, frame }, "reaches outside of the frame rect".to_string()));
+                }
+            }
+        }
+    }
+}
+
+fn orphaned_record(animation: &AnimationData, problems: &mut Vec<(Location, String)>) {
+    let mut names = animation.data().data.keys().collect::<Vec<_>>();
+    names.sort();
+
+    for name in names {
+        let storage = &animation.data().data[name];
+        let mut keys = storage.keys().cloned().collect::<Vec<_>>();
+        keys.sort();
+
+        for key in keys {
+            if !references(animation).any(|(dtype, x)| dtype == name && *x == key) {
+                problems.push((Location::Record { dtype: name.clone(), key }, "not used by any box or marker".to_string()));
+            }
+        }
+    }
+}
+
+fn duplicate_index(animation: &AnimationData, problems: &mut Vec<(Location, String)>) {
+    let mut counts: HashMap<&(String, RecordId), usize> = HashMap::new();
+    let mut order = vec![];
+
+    for reference in references(animation) {
+        let count = counts.entry(reference).or_insert(0);
+        if *count == 1 {
+            order.push(reference);
+        }
+        *count += 1;
+    }
+
+    for reference in order {
+        let message = format!("used by {} boxes and markers", counts[reference]);
+        problems.push((Location::Record { dtype: reference.0.clone(), key: reference.1 }, message));
+    }
+}
+
+fn unreferenced_type(animation: &AnimationData, problems: &mut Vec<(Location, String)>) {
+    let mut names = animation.data().data.keys().collect::<Vec<_>>();
+    names.sort();
+
+    for name in names {
+        if !references(animation).any(|(dtype, _)| dtype == name) {
+            problems.push((Location::Type(name.clone()), "not used by any box or marker".to_string()));
+        }
+    }
+}
@@ -0,0 +1,442 @@
+use std::{
+    error::Error,
+    fmt,
+};
+use serde_json::{Map, Value, json};
+use crate::{
+    data::DataType,
+    project::Project,
+    project_file::{self, data_value_to_json},
+};
+
+// Keys that only decide ordering, when both sides change them ours wins without a conflict
+const ORDER_KEYS: [&str; 2] = ["order", "index"];
+
+// Arrays that are sets, such as the frames a box is active on, merged item by item
+const SET_KEYS: [&str; 2] = ["active", "omitted"];
+
+// An item both sides changed differently, ours was kept in the merged project
+#[derive(Clone, Debug, PartialEq)]
+pub struct Conflict {
+    // Such as `animations/Jab/boxes/Strike:3/x`
+    pub path: String,
+    pub base: Option<Value>,
+    pub ours: Option<Value>,
+    pub theirs: Option<Value>,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let value = |x: &Option<Value>| x.as_ref().map(|x| x.to_string()).unwrap_or_else(|| "(removed)".to_string());
+        write!(f, "{}: base {}, ours {}, theirs {}", self.path, value(&self.base), value(&self.ours), value(&self.theirs))
+    }
+}
+
+pub struct MergeResult {
+    pub project: Project,
+    pub conflicts: Vec<Conflict>,
+}
+
+fn object(value: Value) -> Map<String, Value> {
+    match value {
+        Value::Object(map) => map,
+        _ => Map::new(),
+    }
+}
+
+// Keyed so that every animation, type, field, record, frame, box and marker can be merged on its own
+fn normalize_animation(animation: &Value, order: usize) -> Value {
+    let mut types = Map::new();
+    for (name, storage) in animation["types"].as_object().cloned().unwrap_or_default() {
+        let fields = storage["fields"].as_array().cloned().unwrap_or_default();
+        let names = fields.iter().map(|x| x["name"].as_str().unwrap_or("").to_string()).collect::<Vec<_>>();
+
+        let mut normalized_fields = Map::new();
+        for (i, field) in fields.iter().enumerate() {
+            let mut normalized = json!({ "index": i, "type": field["type"] });
+            if let Some(expression) = field.get("expression") {
+                normalized["expression"] = expression.clone();
+            }
+            normalized_fields.insert(names[i].clone(), normalized);
+        }
+
+        let mut records = Map::new();
+        for (key, values) in storage["records"].as_object().cloned().unwrap_or_default() {
+            let values = values.as_array().cloned().unwrap_or_default();
+            let record = names.iter().cloned().zip(values.into_iter()).collect::<Map<_, _>>();
+            records.insert(key, Value::Object(record));
+        }
+
+        types.insert(name, json!({ "fields": normalized_fields, "records": records }));
+    }
+
+    let frames = animation["frames"].as_array().cloned().unwrap_or_default();
+    let mut normalized_frames = Map::new();
+    for (i, frame) in frames.iter().enumerate() {
+        let mut frame = object(frame.clone());
+        frame.remove("active_boxes");
+        normalized_frames.insert(i.to_string(), Value::Object(frame));
+    }
+
+    let keyed = |items: &[Value], active: Option<&[Value]>| {
+        let mut keyed = Map::new();
+
+        for (i, item) in items.iter().enumerate() {
+            let base = format!("{}:{}", item["type"].as_str().unwrap_or(""), item["key"]);
+            let mut id = base.clone();
+            let mut duplicate = 1;
+            while keyed.contains_key(&id) {
+                id = format!("{}#{}", base, duplicate);
+                duplicate += 1;
+            }
+
+            let mut item = object(item.clone());
+            item.insert("order".to_string(), json!(i));
+            if let Some(frames) = active {
+                let active = frames.iter().enumerate()
+                    .filter(|(_, frame)| frame["active_boxes"].as_array().map(|x| x.contains(&json!(i))).unwrap_or(false))
+                    .map(|(index, _)| index)
+                    .collect::<Vec<_>>();
+                item.insert("active".to_string(), json!(active));
+            }
+
+            keyed.insert(id, Value::Object(item));
+        }
+
+        Value::Object(keyed)
+    };
+
+    let boxes = animation["boxes"].as_array().cloned().unwrap_or_default();
+    let markers = animation["markers"].as_array().cloned().unwrap_or_default();
+
+    json!({
+        "order": order,
+        "fps": animation["fps"],
+        "sprite_sheet": animation["sprite_sheet"],
+        "types": types,
+        "frames": normalized_frames,
+        "boxes": keyed(&boxes, Some(&frames)),
+        "markers": keyed(&markers, None),
+    })
+}
+
+fn normalize(project: &Project) -> Value {
+    let mut value = project_file::to_json(project);
+    let animations = value["animations"].as_array().cloned().unwrap_or_default();
+
+    let mut normalized = Map::new();
+    for (i, animation) in animations.iter().enumerate() {
+        let name = animation["name"].as_str().unwrap_or("").to_string();
+        normalized.insert(name, normalize_animation(animation, i));
+    }
+
+    value["animations"] = Value::Object(normalized);
+    value
+}
+
+// Entries of a keyed object sorted by their `order` (or `index`) and then by key
+fn ordered(value: &Value, order_key: &str) -> Vec<(String, Value)> {
+    let mut entries = value.as_object().cloned().unwrap_or_default().into_iter().collect::<Vec<_>>();
+    entries.sort_by(|a, b| {
+        let order = |x: &Value| x[order_key].as_u64().unwrap_or(u64::max_value());
+        order(&a.1).cmp(&order(&b.1)).then(a.0.cmp(&b.0))
+    });
+
+    entries
+}
+
+fn denormalize_animation(name: &str, animation: &Value) -> Value {
+    let mut types = Map::new();
+    for (type_name, storage) in animation["types"].as_object().cloned().unwrap_or_default() {
+        let fields = ordered(&storage["fields"], "index");
+
+        let records = storage["records"].as_object().cloned().unwrap_or_default().into_iter().map(|(key, record)| {
+            // Fields added on one side are missing from records added on the other
+            let values = fields.iter().map(|(field, desc)| match record.get(field) {
+                Some(value) => value.clone(),
+                None => desc["type"].as_str()
+                    .and_then(DataType::from_name)
+                    .map(|x| data_value_to_json(&x.to_default_value()))
+                    .unwrap_or(Value::Null),
+            }).collect::<Vec<_>>();
+
+            (key, Value::Array(values))
+        }).collect::<Map<_, _>>();
+
+        let fields = fields.iter().map(|(field, desc)| {
+            let mut value = json!({ "name": field, "type": desc["type"] });
+            if let Some(expression) = desc.get("expression") {
+                value["expression"] = expression.clone();
+            }
+
+            value
+        }).collect::<Vec<_>>();
+        types.insert(type_name, json!({ "fields": fields, "records": records }));
+    }
+
+    let mut frames = animation["frames"].as_object().cloned().unwrap_or_default().into_iter()
+        .filter_map(|(index, frame)| index.parse::<usize>().ok().map(|x| (x, object(frame))))
+        .collect::<Vec<_>>();
+    frames.sort_by_key(|x| x.0);
+    let mut frames = frames.into_iter().map(|(_, frame)| frame).collect::<Vec<_>>();
+    for frame in frames.iter_mut() {
+        frame.insert("active_boxes".to_string(), json!([]));
+    }
+
+    let mut boxes = vec![];
+    for (i, (_, data_box)) in ordered(&animation["boxes"], "order").into_iter().enumerate() {
+        let mut data_box = object(data_box);
+
+        let active = data_box.remove("active").and_then(|x| x.as_array().cloned()).unwrap_or_default();
+        for frame in active.iter().filter_map(|x| x.as_u64()) {
+            if let Some(Value::Array(active_boxes)) = frames.get_mut(frame as usize).and_then(|x| x.get_mut("active_boxes")) {
+                active_boxes.push(json!(i));
+            }
+        }
+
+        data_box.remove("order");
+        boxes.push(Value::Object(data_box));
+    }
+
+    let markers = ordered(&animation["markers"], "order").into_iter().map(|(_, marker)| {
+        let mut marker = object(marker);
+        marker.remove("order");
+        Value::Object(marker)
+    }).collect::<Vec<_>>();
+
+    json!({
+        "name": name,
+        "fps": animation["fps"],
+        "sprite_sheet": animation["sprite_sheet"],
+        "types": types,
+        "frames": frames,
+        "boxes": boxes,
+        "markers": markers,
+    })
+}
+
+fn denormalize(mut value: Value) -> Value {
+    let animations = ordered(&value["animations"], "order").into_iter()
+        .map(|(name, animation)| denormalize_animation(&name, &animation))
+        .collect::<Vec<_>>();
+
+    value["animations"] = Value::Array(animations);
+    value
+}
+
+fn merge_values(path: &str, base: Option<&Value>, ours: Option<&Value>, theirs: Option<&Value>, conflicts: &mut Vec<Conflict>) -> Option<Value> {
+    if ours == theirs || base == theirs {
+        return ours.cloned();
+    }
+    if base == ours {
+        return theirs.cloned();
+    }
+
+    let key = path.rsplit('/').next().unwrap_or("");
+    if let (true, Some(Value::Array(ours)), Some(Value::Array(theirs))) = (SET_KEYS.contains(&key), ours, theirs) {
+        let base: &[Value] = match base {
+            Some(Value::Array(base)) => base,
+            _ => &[],
+        };
+
+        // Items either side added are kept and items either side removed are dropped
+        let merged = ours.iter()
+            .filter(|x| theirs.contains(*x) || !base.contains(*x))
+            .chain(theirs.iter().filter(|x| !ours.contains(*x) && !base.contains(*x)))
+            .cloned()
+            .collect();
+
+        return Some(Value::Array(merged));
+    }
+
+    // Both changed the same object, merge it key by key
+    if let (Some(Value::Object(ours)), Some(Value::Object(theirs))) = (ours, theirs) {
+        let base = match base {
+            Some(Value::Object(base)) => Some(base),
+            _ => None,
+        };
+
+        let mut keys = ours.keys().chain(theirs.keys()).chain(base.iter().flat_map(|x| x.keys())).cloned().collect::<Vec<_>>();
+        keys.sort();
+        keys.dedup();
+
+        let mut merged = Map::new();
+        for key in keys {
+            let child = format!("{}/{}", path, key);
+            let value = merge_values(&child, base.and_then(|x| x.get(&key)), ours.get(&key), theirs.get(&key), conflicts);
+
+            if let Some(value) = value {
+                merged.insert(key, value);
+            }
+        }
+
+        return Some(Value::Object(merged));
+    }
+
+    if !ORDER_KEYS.contains(&key) {
+        conflicts.push(Conflict {
+            path: path.to_string(),
+            base: base.cloned(),
+            ours: ours.cloned(),
+            theirs: theirs.cloned(),
+        });
+    }
+
+    ours.cloned()
+}
+
+// Merges the changes from `base` to `ours` and from `base` to `theirs`, keeping ours on conflicts
+pub fn merge_projects(base: &Project, ours: &Project, theirs: &Project) -> Result<MergeResult, Box<dyn Error>> {
+    let (base, ours, theirs) = (normalize(base), normalize(ours), normalize(theirs));
+    let mut conflicts = vec![];
+
+    let merged = merge_values("", Some(&base), Some(&ours), Some(&theirs), &mut conflicts).unwrap_or(ours);
+    for conflict in conflicts.iter_mut() {
+        conflict.path = conflict.path.trim_start_matches('/').to_string();
+    }
+
+    let project = project_file::from_json(&denormalize(merged))?;
+
+    Ok(MergeResult {
+        project,
+        conflicts,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use na::{Isometry2, Vector2};
+    use crate::{
+        category::BoxCategory,
+        data::{DataValue, RecordId},
+        frame::{AnimationData, Frame},
+        shapes::{AABB, BoundingBox, Cuboid},
+    };
+    use super::*;
+
+    // `Punch` with three frames, a `Hit` box active on the first and a `sound` marker
+    fn base() -> Project {
+        let mut animation = AnimationData::new("Punch".to_string(), 60);
+        for _ in 0..3 {
+            animation.push_frame(Frame::new(AABB::new(0, 0, 8, 8)));
+        }
+
+        animation.new_data_type("Hit".to_string());
+        animation.data_mut().get_storage_mut("Hit").unwrap().add_field("damage".to_string(), DataType::F32).unwrap();
+
+        let shape = BoundingBox::new(Cuboid::new(4.0, 2.0), Isometry2::new(Vector2::new(1.0, 2.0), 0.0));
+        animation.new_data_box("Hit", shape, BoxCategory::Hitbox).unwrap();
+        animation.set_box_active(0, 0, true);
+        animation.new_marker("sound".to_string(), "Hit", 0, 1).unwrap();
+
+        let mut project = Project::new();
+        project.add_animation(animation);
+        project
+    }
+
+    fn animation(project: &mut Project) -> &mut AnimationData {
+        &mut project.animations[0]
+    }
+
+    fn box_key(project: &Project) -> RecordId {
+        project.animations[0].data_boxes()[0].data.1
+    }
+
+    fn set_damage(project: &mut Project, value: &str) {
+        let key = box_key(project);
+        animation(project).data_mut().get_storage_mut("Hit").unwrap().set_value(&key, 0, value).unwrap();
+    }
+
+    fn active_frames(project: &Project) -> Vec<usize> {
+        let animation = &project.animations[0];
+        (0..animation.frames().len()).filter(|x| animation.is_box_active(0, *x)).collect()
+    }
+
+    fn paths(result: &MergeResult) -> Vec<&str> {
+        result.conflicts.iter().map(|x| x.path.as_str()).collect()
+    }
+
+    #[test]
+    fn changes_to_different_items_merge_cleanly() {
+        let base = base();
+        let mut ours = base();
+        let mut theirs = base();
+
+        animation(&mut ours).frame_mut(0).unwrap().set_duration(3);
+        set_damage(&mut theirs, "12");
+        animation(&mut theirs).markers_mut()[0].start = 2;
+
+        let result = merge_projects(&base, &ours, &theirs).unwrap();
+        assert!(result.conflicts.is_empty(), "{:?}", result.conflicts);
+
+        let animation = &result.project.animations[0];
+        assert_eq!(animation.frames()[0].duration(), 3);
+        assert_eq!(animation.markers()[0].start, 2);
+        let key = box_key(&result.project);
+        assert!(matches!(animation.data().get("Hit", &key).unwrap()[0], DataValue::F32(x) if x == 12.0));
+    }
+
+    #[test]
+    fn both_sides_changing_a_value_is_a_conflict_that_keeps_ours() {
+        let base = base();
+        let mut ours = base();
+        let mut theirs = base();
+
+        animation(&mut ours).frame_mut(0).unwrap().set_duration(3);
+        animation(&mut theirs).frame_mut(0).unwrap().set_duration(4);
+
+        let result = merge_projects(&base, &ours, &theirs).unwrap();
+        assert_eq!(paths(&result), ["animations/Punch/frames/0/duration"]);
+        assert_eq!(result.conflicts[0].theirs, Some(json!(4)));
+        assert_eq!(result.project.animations[0].frames()[0].duration(), 3);
+    }
+
+    #[test]
+    fn a_field_renamed_on_one_side_keeps_the_other_sides_changes() {
+        let base = base();
+        let mut ours = base();
+        let mut theirs = base();
+
+        animation(&mut ours).frame_mut(1).unwrap().set_duration(2);
+        animation(&mut theirs).data_mut().get_storage_mut("Hit").unwrap().rename_field(0, "power".to_string()).unwrap();
+
+        let result = merge_projects(&base, &ours, &theirs).unwrap();
+        assert!(result.conflicts.is_empty(), "{:?}", result.conflicts);
+
+        let animation = &result.project.animations[0];
+        let storage = animation.data().get_storage("Hit").unwrap();
+        assert_eq!(storage.get_desc().iter().map(|x| x.name.as_str()).collect::<Vec<_>>(), ["power"]);
+        assert_eq!(animation.frames()[1].duration(), 2);
+    }
+
+    #[test]
+    fn deleting_what_the_other_side_modified_is_a_conflict() {
+        let base = base();
+        let mut ours = base();
+        let mut theirs = base();
+
+        animation(&mut ours).remove_marker(0);
+        animation(&mut theirs).markers_mut()[0].start = 2;
+
+        let result = merge_projects(&base, &ours, &theirs).unwrap();
+        let key = base.animations[0].markers()[0].data.1;
+        assert_eq!(paths(&result), [format!("animations/Punch/markers/Hit:{}", key)]);
+        assert_eq!(result.conflicts[0].ours, None);
+        assert!(result.project.animations[0].markers().is_empty());
+    }
+
+    #[test]
+    fn active_frames_merge_as_sets() {
+        let base = base();
+        let mut ours = base();
+        let mut theirs = base();
+
+        animation(&mut ours).set_box_active(0, 1, true);
+        animation(&mut theirs).set_box_active(0, 0, false);
+        animation(&mut theirs).set_box_active(0, 2, true);
+
+        let result = merge_projects(&base, &ours, &theirs).unwrap();
+        assert!(result.conflicts.is_empty(), "{:?}", result.conflicts);
+        assert_eq!(active_frames(&result.project), [1, 2]);
+    }
+}
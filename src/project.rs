@@ -0,0 +1,64 @@
+use std::collections::BTreeMap;
+use crate::{
+    category::Categories,
+    export::{profile::ExportProfile, registry::ExportTarget},
+    frame::AnimationData,
+    lint::LintSettings,
+};
+
+#[derive(Clone, Debug)]
+pub struct Project {
+    pub animations: Vec<AnimationData>,
+    pub categories: Categories,
+    pub lint: LintSettings,
+    // Source of each script by name
+    pub scripts: BTreeMap<String, String>,
+    pub export_profiles: BTreeMap<String, ExportProfile>,
+    // Name of the profile each exporter uses by exporter name, the rest use the default profile
+    pub exporter_profiles: BTreeMap<String, String>,
+    pub export_targets: Vec<ExportTarget>,
+}
+
+impl Project {
+    pub fn new() -> Project {
+        Project {
+            animations: vec![],
+            categories: Categories::default(),
+            lint: LintSettings::default(),
+            scripts: BTreeMap::new(),
+            export_profiles: BTreeMap::new(),
+            exporter_profiles: BTreeMap::new(),
+            export_targets: vec![],
+        }
+    }
+
+    pub fn get_animation(&self, name: &str) -> Option<&AnimationData> {
+        self.animations.iter().find(|x| x.name == name)
+    }
+
+    pub fn get_animation_mut(&mut self, name: &str) -> Option<&mut AnimationData> {
+        self.animations.iter_mut().find(|x| x.name == name)
+    }
+
+    pub fn animation_index(&self, name: &str) -> Option<usize> {
+        self.animations.iter().position(|x| x.name == name)
+    }
+
+    // The default profile when none is picked or the picked one no longer exists
+    pub fn exporter_profile(&self, exporter: &str) -> ExportProfile {
+        self.exporter_profiles.get(exporter)
+            .and_then(|name| self.export_profiles.get(name))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn add_animation(&mut self, animation: AnimationData) {
+        self.animations.push(animation);
+    }
+}
+
+impl Default for Project {
+    fn default() -> Self {
+        Self::new()
+    }
+}
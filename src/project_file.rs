@@ -0,0 +1,636 @@
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fs,
+    path::Path,
+};
+use na::{Isometry2, Vector2};
+use serde_json::{Map, Value, json};
+use crate::{
+    category::{BoxCategory, Categories, LAYER_COUNT},
+    data::{DataStorage, DataType, DataTypeStorage, DataValue, RecordId},
+    export::{
+        json,
+        profile::ExportProfile,
+        registry::{ExportTarget, FormatOptions, OptionValue},
+    },
+    frame::{AnimationData, DataBox, Frame, Marker},
+    lint::{LintSettings, Severity},
+    project::Project,
+    shapes::{AABB, BoundingBox, Cuboid},
+};
+
+// Bumped whenever the layout of saved projects changes
+pub const FORMAT_VERSION: u64 = 1;
+
+type LoadResult<T> = Result<T, Box<dyn Error>>;
+
+fn get<'a>(value: &'a Value, key: &str) -> LoadResult<&'a Value> {
+    value.get(key).ok_or_else(|| format!("missing '{}'", key).into())
+}
+
+fn get_u64(value: &Value, key: &str) -> LoadResult<u64> {
+    get(value, key)?.as_u64().ok_or_else(|| format!("'{}' is not a positive integer", key).into())
+}
+
+fn get_u32(value: &Value, key: &str) -> LoadResult<u32> {
+    let number = get_u64(value, key)?;
+    if number > u32::max_value() as u64 {
+        return Err(format!("'{}' is too large", key).into());
+    }
+
+    Ok(number as u32)
+}
+
+fn get_f32(value: &Value, key: &str) -> LoadResult<f32> {
+    get(value, key)?.as_f64().map(|x| x as f32).ok_or_else(|| format!("'{}' is not a number", key).into())
+}
+
+fn get_str<'a>(value: &'a Value, key: &str) -> LoadResult<&'a str> {
+    get(value, key)?.as_str().ok_or_else(|| format!("'{}' is not a string", key).into())
+}
+
+fn get_array<'a>(value: &'a Value, key: &str) -> LoadResult<&'a Vec<Value>> {
+    get(value, key)?.as_array().ok_or_else(|| format!("'{}' is not an array", key).into())
+}
+
+fn get_optional_str<'a>(value: &'a Value, key: &str) -> LoadResult<Option<&'a str>> {
+    match value.get(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(_) => get_str(value, key).map(Some),
+    }
+}
+
+fn get_bools(value: &Value, key: &str) -> LoadResult<[bool; 2]> {
+    let array = get_array(value, key)?;
+
+    match (array.get(0).and_then(|x| x.as_bool()), array.get(1).and_then(|x| x.as_bool())) {
+        (Some(a), Some(b)) => Ok([a, b]),
+        _ => Err(format!("'{}' is not a pair of bools", key).into()),
+    }
+}
+
+// Exports write non-finite floats as null, projects keep them as strings so they load back unchanged
+pub fn data_value_to_json(value: &DataValue) -> Value {
+    match value {
+        DataValue::F32(val) if val.is_nan() => json!("NaN"),
+        DataValue::F32(val) if val.is_infinite() => json!(if *val > 0.0 { "inf" } else { "-inf" }),
+        _ => json::data_value_to_json(value),
+    }
+}
+
+pub fn data_value_from_json(value: &Value, dtype: DataType) -> LoadResult<DataValue> {
+    let result = match dtype {
+        DataType::F32 => value.as_f64().map(|x| x as f32)
+            .or_else(|| match value.as_str() {
+                Some("NaN") => Some(f32::NAN),
+                Some("inf") => Some(f32::INFINITY),
+                Some("-inf") => Some(f32::NEG_INFINITY),
+                _ => None,
+            })
+            .map(DataValue::F32),
+        DataType::I32 => value.as_i64()
+            .filter(|x| *x >= i32::min_value() as i64 && *x <= i32::max_value() as i64)
+            .map(|x| DataValue::I32(x as i32)),
+        DataType::U32 => value.as_u64()
+            .filter(|x| *x <= u32::max_value() as u64)
+            .map(|x| DataValue::U32(x as u32)),
+        DataType::Char => value.as_str().and_then(|x| {
+            let mut chars = x.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Some(DataValue::Char(c)),
+                _ => None,
+            }
+        }),
+        DataType::Bool => value.as_bool().map(DataValue::Bool),
+        DataType::OwnedString => value.as_str().map(|x| DataValue::OwnedString(x.to_string())),
+    };
+
+    result.ok_or_else(|| format!("{} is not a valid {}", value, dtype.name()).into())
+}
+
+fn shape_to_json(shape: &BoundingBox<f32>, value: &mut Map<String, Value>) {
+    let translation = shape.get_translation();
+
+    value.insert("x".to_string(), json!(translation.x));
+    value.insert("y".to_string(), json!(translation.y));
+    value.insert("width".to_string(), json!(shape.rect.width));
+    value.insert("height".to_string(), json!(shape.rect.height));
+    value.insert("angle".to_string(), json!(shape.get_rotation()));
+}
+
+fn shape_from_json(value: &Value) -> LoadResult<BoundingBox<f32>> {
+    let translation = Vector2::new(get_f32(value, "x")?, get_f32(value, "y")?);
+    let rect = Cuboid::new(get_f32(value, "width")?, get_f32(value, "height")?);
+
+    Ok(BoundingBox::new(rect, Isometry2::new(translation, get_f32(value, "angle")?)))
+}
+
+fn storage_to_json(storage: &DataTypeStorage<RecordId>) -> Value {
+    let fields = storage.get_desc().iter().map(|x| {
+        let mut field = json!({ "name": x.name, "type": x.dtype.name() });
+        if let Some(expression) = x.expression() {
+            field["expression"] = json!(expression.source());
+        }
+
+        field
+    }).collect::<Vec<_>>();
+
+    let mut records = Map::new();
+    for key in storage.keys() {
+        let values = storage.get(key).unwrap_or(&[]);
+        records.insert(key.to_string(), values.iter().map(data_value_to_json).collect());
+    }
+
+    json!({
+        "fields": fields,
+        "records": records,
+    })
+}
+
+fn storage_from_json(value: &Value) -> LoadResult<DataTypeStorage<RecordId>> {
+    let mut storage = DataTypeStorage::new();
+
+    for field in get_array(value, "fields")? {
+        let name = get_str(field, "name")?;
+        let dtype_name = get_str(field, "type")?;
+        let dtype = DataType::from_name(dtype_name).ok_or_else(|| format!("unknown field type '{}'", dtype_name))?;
+
+        storage.add_field(name.to_string(), dtype)?;
+    }
+
+    // Set once every field exists since expressions can use fields that come after them
+    for (i, field) in get_array(value, "fields")?.iter().enumerate() {
+        if let Some(expression) = get_optional_str(field, "expression")? {
+            storage.set_expression(i, expression)?;
+        }
+    }
+
+    let records = get(value, "records")?.as_object().ok_or("'records' is not an object")?;
+    for (key, values) in records.iter() {
+        let key = key.parse::<RecordId>().map_err(|_| format!("record key '{}' is not an integer", key))?;
+        let values = values.as_array().ok_or_else(|| format!("record {} is not an array", key))?;
+
+        if values.len() != storage.get_desc().len() {
+            return Err(format!("record {} has {} values but the type has {} fields", key, values.len(), storage.get_desc().len()).into());
+        }
+
+        let dtypes = storage.get_desc().iter().map(|x| x.dtype).collect::<Vec<_>>();
+        let record = storage.gen_new(key);
+        for (i, value) in values.iter().enumerate() {
+            record[i] = data_value_from_json(value, dtypes[i]).map_err(|error| format!("record {}: {}", key, error))?;
+        }
+    }
+
+    storage.refresh_all();
+    Ok(storage)
+}
+
+fn frame_to_json(frame: &Frame) -> Value {
+    let rect = frame.rect();
+    let anchors = frame.anchors().iter().map(|anchor| {
+        let mut value = Map::new();
+        let translation = anchor.transformation.translation.vector;
+
+        value.insert("name".to_string(), json!(anchor.name));
+        value.insert("x".to_string(), json!(translation.x));
+        value.insert("y".to_string(), json!(translation.y));
+        value.insert("angle".to_string(), json!(anchor.transformation.rotation.angle()));
+        Value::Object(value)
+    }).collect::<Vec<_>>();
+
+    json!({
+        "rect": {
+            "x": rect.translation.vector.x,
+            "y": rect.translation.vector.y,
+            "width": rect.rect.width,
+            "height": rect.rect.height,
+        },
+        "pivot": frame.centre(),
+        "duration": frame.duration(),
+        "flipped": frame.flipped(),
+        "active_boxes": frame.active_hitboxes(),
+        "anchors": anchors,
+    })
+}
+
+fn frame_from_json(value: &Value) -> LoadResult<(Frame, Vec<usize>)> {
+    let rect = get(value, "rect")?;
+    let rect = AABB::new(get_u32(rect, "x")?, get_u32(rect, "y")?, get_u32(rect, "width")?, get_u32(rect, "height")?);
+    let mut frame = Frame::new(rect);
+
+    let pivot = get_array(value, "pivot")?;
+    match (pivot.get(0).and_then(|x| x.as_u64()), pivot.get(1).and_then(|x| x.as_u64())) {
+        (Some(x), Some(y)) => frame.set_centre([x as u32, y as u32]),
+        _ => return Err("'pivot' is not a pair of integers".into()),
+    }
+
+    let duration = get_u64(value, "duration")?;
+    frame.set_duration(duration.min(u16::max_value() as u64) as u16);
+    frame.set_flipped(get_bools(value, "flipped")?);
+
+    for anchor in get_array(value, "anchors")? {
+        let translation = Vector2::new(get_f32(anchor, "x")?, get_f32(anchor, "y")?);
+        frame.set_anchor(get_str(anchor, "name")?, Isometry2::new(translation, get_f32(anchor, "angle")?));
+    }
+
+    let active = get_array(value, "active_boxes")?.iter()
+        .map(|x| x.as_u64().map(|x| x as usize).ok_or("'active_boxes' has a value that is not an index"))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok((frame, active))
+}
+
+fn animation_to_json(animation: &AnimationData) -> Value {
+    let mut types = Map::new();
+    for (name, storage) in animation.data().data.iter() {
+        types.insert(name.clone(), storage_to_json(storage));
+    }
+
+    let boxes = animation.data_boxes().iter().map(|data_box| {
+        let mut value = Map::new();
+
+        value.insert("category".to_string(), json!(data_box.category.name()));
+        value.insert("type".to_string(), json!(data_box.data.0));
+        value.insert("key".to_string(), json!(data_box.data.1));
+        value.insert("parent".to_string(), json!(data_box.parent));
+        shape_to_json(&data_box.shape, &mut value);
+        Value::Object(value)
+    }).collect::<Vec<_>>();
+
+    let markers = animation.markers().iter().map(|marker| json!({
+        "name": marker.name,
+        "start": marker.start,
+        "length": marker.length,
+        "type": marker.data.0,
+        "key": marker.data.1,
+    })).collect::<Vec<_>>();
+
+    json!({
+        "name": animation.name,
+        "fps": animation.fps(),
+        "sprite_sheet": animation.sprite_sheet,
+        "types": types,
+        "frames": animation.frames().iter().map(frame_to_json).collect::<Vec<_>>(),
+        "boxes": boxes,
+        "markers": markers,
+    })
+}
+
+fn animation_from_json(value: &Value) -> LoadResult<AnimationData> {
+    let name = get_str(value, "name")?;
+    let fps = get_u64(value, "fps")?.min(u16::max_value() as u64) as u16;
+    let mut animation = AnimationData::new(name.to_string(), fps);
+    animation.set_fps(fps);
+    animation.sprite_sheet = get_optional_str(value, "sprite_sheet")?.map(|x| x.to_string());
+
+    let types = get(value, "types")?.as_object().ok_or("'types' is not an object")?;
+    let mut data = DataStorage::new();
+    for (name, storage) in types.iter() {
+        let storage = storage_from_json(storage).map_err(|error| format!("type '{}': {}", name, error))?;
+        data.insert(name.clone(), storage);
+    }
+    *animation.data_mut() = data;
+
+    let mut active_boxes = vec![];
+    for (i, frame) in get_array(value, "frames")?.iter().enumerate() {
+        let (frame, active) = frame_from_json(frame).map_err(|error| format!("frame {}: {}", i, error))?;
+        animation.push_frame(frame);
+        active_boxes.push(active);
+    }
+
+    for (i, data_box) in get_array(value, "boxes")?.iter().enumerate() {
+        let load = || -> LoadResult<DataBox<(String, RecordId)>> {
+            let category = get_str(data_box, "category")?;
+            let category = BoxCategory::from_name(category).ok_or_else(|| format!("unknown category '{}'", category))?;
+            let data = (get_str(data_box, "type")?.to_string(), get_u64(data_box, "key")?);
+
+            let mut value = DataBox::new(shape_from_json(data_box)?, data, category);
+            value.parent = get_optional_str(data_box, "parent")?.map(|x| x.to_string());
+            Ok(value)
+        };
+
+        animation.push_data_box(load().map_err(|error| format!("box {}: {}", i, error))?);
+    }
+
+    // Boxes have to exist before they can be activated
+    for (frame, active) in active_boxes.into_iter().enumerate() {
+        for data_box in active {
+            if data_box >= animation.data_boxes().len() {
+                return Err(format!("frame {}: active box {} does not exist", frame, data_box).into());
+            }
+            animation.set_box_active(data_box, frame, true);
+        }
+    }
+
+    for (i, marker) in get_array(value, "markers")?.iter().enumerate() {
+        let load = || -> LoadResult<Marker<(String, RecordId)>> {
+            let data = (get_str(marker, "type")?.to_string(), get_u64(marker, "key")?);
+            Ok(Marker::new(get_str(marker, "name")?.to_string(), get_u32(marker, "start")?, get_u32(marker, "length")?, data))
+        };
+
+        animation.push_marker(load().map_err(|error| format!("marker {}: {}", i, error))?);
+    }
+
+    Ok(animation)
+}
+
+fn categories_to_json(categories: &Categories) -> Value {
+    let mut settings = Map::new();
+    for category in BoxCategory::ALL.iter() {
+        let value = categories.get(*category);
+        settings.insert(category.name().to_string(), json!({
+            "colour": value.colour,
+            "layer": value.layer,
+        }));
+    }
+
+    let masks = (0..LAYER_COUNT).map(|x| categories.layers.mask(x)).collect::<Vec<_>>();
+
+    json!({
+        "settings": settings,
+        "layers": masks,
+    })
+}
+
+fn categories_from_json(value: &Value) -> LoadResult<Categories> {
+    let mut categories = Categories::default();
+    let settings = get(value, "settings")?;
+
+    for category in BoxCategory::ALL.iter() {
+        let setting = match settings.get(category.name()) {
+            Some(setting) => setting,
+            None => continue,
+        };
+
+        let colour = get_array(setting, "colour")?.iter().filter_map(|x| x.as_f64()).collect::<Vec<_>>();
+        if colour.len() != 4 {
+            return Err(format!("colour of '{}' is not 4 numbers", category.name()).into());
+        }
+
+        let layer = get_u64(setting, "layer")?;
+        if layer >= LAYER_COUNT as u64 {
+            return Err(format!("layer of '{}' is out of range", category.name()).into());
+        }
+
+        let value = categories.get_mut(*category);
+        value.colour = [colour[0] as f32, colour[1] as f32, colour[2] as f32, colour[3] as f32];
+        value.layer = layer as u8;
+    }
+
+    for (a, mask) in get_array(value, "layers")?.iter().enumerate().take(LAYER_COUNT as usize) {
+        let mask = mask.as_u64().ok_or("layer masks must be integers")?;
+
+        for b in 0..LAYER_COUNT {
+            categories.layers.set(a as u8, b, mask & (1 << b) != 0);
+        }
+    }
+
+    Ok(categories)
+}
+
+fn lint_to_json(settings: &LintSettings) -> Value {
+    let severities = settings.severities.iter()
+        .map(|(rule, severity)| (rule.clone(), json!(severity.name())))
+        .collect::<Map<_, _>>();
+
+    Value::Object(severities)
+}
+
+// Older projects have no lint settings
+fn lint_from_json(value: Option<&Value>) -> LoadResult<LintSettings> {
+    let mut settings = LintSettings::default();
+    let severities = match value {
+        Some(value) => value.as_object().ok_or("'lint' is not an object")?,
+        None => return Ok(settings),
+    };
+
+    for (rule, severity) in severities.iter() {
+        let name = severity.as_str().unwrap_or("");
+        let severity = Severity::from_name(name).ok_or_else(|| format!("unknown severity '{}' for '{}'", name, rule))?;
+        settings.severities.insert(rule.clone(), severity);
+    }
+
+    Ok(settings)
+}
+
+fn scripts_to_json(scripts: &BTreeMap<String, String>) -> Value {
+    Value::Object(scripts.iter().map(|(name, source)| (name.clone(), json!(source))).collect())
+}
+
+// Older projects have no scripts
+fn scripts_from_json(value: Option<&Value>) -> LoadResult<BTreeMap<String, String>> {
+    let scripts = match value {
+        Some(value) => value.as_object().ok_or("'scripts' is not an object")?,
+        None => return Ok(BTreeMap::new()),
+    };
+
+    scripts.iter().map(|(name, source)| {
+        let source = source.as_str().ok_or_else(|| format!("script '{}' is not a string", name))?;
+        Ok((name.clone(), source.to_string()))
+    }).collect()
+}
+
+fn profile_to_json(profile: &ExportProfile) -> Value {
+    json!({
+        "flip_y": profile.flip_y,
+        "scale": profile.scale,
+        "box_step": profile.box_step,
+        "field_step": profile.field_step,
+        "fps": profile.fps,
+        "renamed": profile.renamed,
+        "omitted": profile.omitted,
+    })
+}
+
+fn profile_from_json(value: &Value) -> LoadResult<ExportProfile> {
+    let fps = match value.get("fps") {
+        None | Some(Value::Null) => None,
+        Some(_) => Some(get_u64(value, "fps")?.min(u16::max_value() as u64).max(1) as u16),
+    };
+
+    let renamed = get(value, "renamed")?.as_object().ok_or("'renamed' is not an object")?.iter()
+        .map(|(field, name)| match name.as_str() {
+            Some(name) => Ok((field.clone(), name.to_string())),
+            None => Err(format!("new name of '{}' is not a string", field).into()),
+        })
+        .collect::<LoadResult<_>>()?;
+
+    let omitted = get_array(value, "omitted")?.iter()
+        .map(|field| field.as_str().map(|x| x.to_string()).ok_or_else(|| "'omitted' has a field that is not a string".into()))
+        .collect::<LoadResult<_>>()?;
+
+    Ok(ExportProfile {
+        flip_y: get(value, "flip_y")?.as_bool().ok_or("'flip_y' is not a bool")?,
+        scale: get_f32(value, "scale")?,
+        box_step: get_f32(value, "box_step")?,
+        field_step: get_f32(value, "field_step")?,
+        fps,
+        renamed,
+        omitted,
+    })
+}
+
+fn target_to_json(target: &ExportTarget) -> Value {
+    let options = target.options.iter().map(|(name, value)| {
+        let value = match value {
+            OptionValue::Flag(value) => json!(value),
+            OptionValue::Text(value) => json!(value),
+        };
+
+        (name.to_string(), value)
+    }).collect();
+
+    json!({
+        "exporter": target.exporter,
+        "directory": target.directory,
+        "options": Value::Object(options),
+    })
+}
+
+fn target_from_json(value: &Value) -> LoadResult<ExportTarget> {
+    let mut options = FormatOptions::default();
+    for (name, value) in get(value, "options")?.as_object().ok_or("'options' is not an object")?.iter() {
+        let value = match value {
+            Value::Bool(value) => OptionValue::Flag(*value),
+            Value::String(value) => OptionValue::Text(value.clone()),
+            _ => return Err(format!("option '{}' is not a bool or string", name).into()),
+        };
+
+        options.set(name, value);
+    }
+
+    Ok(ExportTarget {
+        exporter: get_str(value, "exporter")?.to_string(),
+        directory: get_str(value, "directory")?.to_string(),
+        options,
+    })
+}
+
+fn profiles_to_json(project: &Project) -> Value {
+    json!({
+        "profiles": Value::Object(project.export_profiles.iter().map(|(name, x)| (name.clone(), profile_to_json(x))).collect()),
+        "exporters": project.exporter_profiles,
+        "targets": project.export_targets.iter().map(target_to_json).collect::<Vec<_>>(),
+    })
+}
+
+// Older projects have no export profiles
+fn profiles_from_json(project: &mut Project, value: Option<&Value>) -> LoadResult<()> {
+    let value = match value {
+        Some(value) => value,
+        None => return Ok(()),
+    };
+
+    let profiles = get(value, "profiles")?.as_object().ok_or("'profiles' is not an object")?;
+    for (name, profile) in profiles.iter() {
+        let profile = profile_from_json(profile).map_err(|error| format!("export profile '{}': {}", name, error))?;
+        project.export_profiles.insert(name.clone(), profile);
+    }
+
+    let exporters = get(value, "exporters")?.as_object().ok_or("'exporters' is not an object")?;
+    for (exporter, profile) in exporters.iter() {
+        let profile = profile.as_str().ok_or_else(|| format!("export profile of '{}' is not a string", exporter))?;
+        project.exporter_profiles.insert(exporter.clone(), profile.to_string());
+    }
+
+    // Projects saved before export targets existed have none
+    if let Some(targets) = value.get("targets") {
+        let targets = targets.as_array().ok_or("'targets' is not an array")?;
+        for (i, target) in targets.iter().enumerate() {
+            let target = target_from_json(target).map_err(|error| format!("export target {}: {}", i, error))?;
+            project.export_targets.push(target);
+        }
+    }
+
+    Ok(())
+}
+
+pub fn to_json(project: &Project) -> Value {
+    json!({
+        "format_version": FORMAT_VERSION,
+        "categories": categories_to_json(&project.categories),
+        "lint": lint_to_json(&project.lint),
+        "scripts": scripts_to_json(&project.scripts),
+        "export": profiles_to_json(project),
+        "animations": project.animations.iter().map(animation_to_json).collect::<Vec<_>>(),
+    })
+}
+
+pub fn from_json(value: &Value) -> LoadResult<Project> {
+    let version = get_u64(value, "format_version")?;
+    if version > FORMAT_VERSION {
+        return Err(format!("project format version {} is newer than the supported version {}", version, FORMAT_VERSION).into());
+    }
+
+    let mut project = Project::new();
+    project.categories = categories_from_json(get(value, "categories")?).map_err(|error| format!("categories: {}", error))?;
+    project.lint = lint_from_json(value.get("lint"))?;
+    project.scripts = scripts_from_json(value.get("scripts"))?;
+    profiles_from_json(&mut project, value.get("export"))?;
+
+    for (i, animation) in get_array(value, "animations")?.iter().enumerate() {
+        let animation = animation_from_json(animation).map_err(|error| format!("animation {}: {}", i, error))?;
+        project.add_animation(animation);
+    }
+
+    Ok(project)
+}
+
+pub fn to_string(project: &Project) -> String {
+    serde_json::to_string_pretty(&to_json(project)).unwrap_or_default()
+}
+
+pub fn from_str(text: &str) -> LoadResult<Project> {
+    from_json(&serde_json::from_str(text)?)
+}
+
+pub fn save(project: &Project, path: &Path) -> LoadResult<()> {
+    fs::write(path, to_string(project))?;
+    Ok(())
+}
+
+pub fn load(path: &Path) -> LoadResult<Project> {
+    let text = fs::read_to_string(path).map_err(|error| format!("{}: {}", path.display(), error))?;
+    from_str(&text).map_err(|error| format!("{}: {}", path.display(), error).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn non_finite_floats_survive_a_save_and_load() {
+        let mut animation = AnimationData::new("Idle".to_string(), 12);
+        animation.new_data_type("Hit".to_string());
+
+        let storage = animation.data_mut().get_storage_mut("Hit").unwrap();
+        storage.add_field("damage".to_string(), DataType::F32).unwrap();
+        for (key, value) in ["NaN", "inf", "-inf", "1.5"].iter().enumerate() {
+            storage.gen_new(key as RecordId);
+            storage.set_value(&(key as RecordId), 0, value).unwrap();
+        }
+
+        let mut project = Project::new();
+        project.add_animation(animation);
+
+        let loaded = from_str(&to_string(&project)).unwrap();
+        let storage = loaded.animations[0].data().get_storage("Hit").unwrap();
+        let value = |key: RecordId| match storage.get(&key).unwrap()[0] {
+            DataValue::F32(value) => value,
+            ref other => panic!("expected an f32, found {:?}", other),
+        };
+
+        assert!(value(0).is_nan());
+        assert_eq!(value(1), f32::INFINITY);
+        assert_eq!(value(2), f32::NEG_INFINITY);
+        assert_eq!(value(3), 1.5);
+    }
+
+    #[test]
+    fn only_known_strings_load_as_floats() {
+        assert!(data_value_from_json(&json!("nan"), DataType::F32).is_err());
+        assert!(data_value_from_json(&json!("1.5"), DataType::F32).is_err());
+        assert!(matches!(data_value_from_json(&json!("-inf"), DataType::F32), Ok(DataValue::F32(x)) if x == f32::NEG_INFINITY));
+    }
+}
@@ -0,0 +1,464 @@
+use std::{
+    cmp::Ordering,
+    error::Error,
+    fmt,
+    hash::Hash,
+    ops::Range,
+};
+use crate::data::{DataStorage, DataTypeStorage, DataValue};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    // Substring match on the text of the value
+    Contains,
+}
+
+impl Comparison {
+    // Longest first so `<=` is not read as `<`
+    const SYMBOLS: [(&'static str, Comparison); 8] = [
+        ("==", Comparison::Equal),
+        ("!=", Comparison::NotEqual),
+        ("<=", Comparison::LessOrEqual),
+        (">=", Comparison::GreaterOrEqual),
+        ("=", Comparison::Equal),
+        ("<", Comparison::Less),
+        (">", Comparison::Greater),
+        ("~", Comparison::Contains),
+    ];
+
+    fn matches(&self, value: &DataValue, operand: &DataValue) -> bool {
+        if *self == Comparison::Contains {
+            return value.to_string().contains(&operand.to_string());
+        }
+
+        let ordering = match compare(value, operand) {
+            Some(ordering) => ordering,
+            None => return false,
+        };
+
+        match self {
+            Comparison::Equal => ordering == Ordering::Equal,
+            Comparison::NotEqual => ordering != Ordering::Equal,
+            Comparison::Less => ordering == Ordering::Less,
+            Comparison::LessOrEqual => ordering != Ordering::Greater,
+            Comparison::Greater => ordering == Ordering::Greater,
+            Comparison::GreaterOrEqual => ordering != Ordering::Less,
+            Comparison::Contains => unreachable!(),
+        }
+    }
+}
+
+// Numbers and bools compare by value whatever their type, chars and strings by their text
+fn compare(a: &DataValue, b: &DataValue) -> Option<Ordering> {
+    match (a.as_f64(), b.as_f64()) {
+        (Some(a), Some(b)) => a.partial_cmp(&b),
+        (None, None) => Some(a.to_string().cmp(&b.to_string())),
+        _ => None,
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Predicate {
+    pub field: String,
+    pub comparison: Comparison,
+    pub value: DataValue,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum QueryError {
+    UnknownType(String),
+    UnknownField(String),
+    Syntax(String),
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            QueryError::UnknownType(name) => write!(f, "no type named '{}'", name),
+            QueryError::UnknownField(name) => write!(f, "no field named '{}'", name),
+            QueryError::Syntax(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl Error for QueryError {}
+
+// Records whose fields match every predicate, such as
+// `Query::new().of_type("Strike").filter("damage", Comparison::Greater, DataValue::F32(100.0))`
+#[derive(Clone, Debug)]
+pub struct Query {
+    // Every type that has all the fields used when None
+    pub data_type: Option<String>,
+    pub predicates: Vec<Predicate>,
+    // Records are ordered by key when None
+    pub sort: Option<(String, SortOrder)>,
+    // Fields in each row, all of them when None
+    pub fields: Option<Vec<String>>,
+}
+
+// A matching record with the selected fields in the order they were asked for
+#[derive(Clone, Debug)]
+pub struct Row<'a, K> {
+    pub key: &'a K,
+    pub values: Vec<(&'a str, &'a DataValue)>,
+}
+
+impl Query {
+    pub fn new() -> Query {
+        Query {
+            data_type: None,
+            predicates: vec![],
+            sort: None,
+            fields: None,
+        }
+    }
+
+    pub fn of_type(mut self, data_type: &str) -> Query {
+        self.data_type = Some(data_type.to_string());
+        self
+    }
+
+    pub fn filter(mut self, field: &str, comparison: Comparison, value: DataValue) -> Query {
+        self.predicates.push(Predicate {
+            field: field.to_string(),
+            comparison,
+            value,
+        });
+        self
+    }
+
+    pub fn sort_by(mut self, field: &str, order: SortOrder) -> Query {
+        self.sort = Some((field.to_string(), order));
+        self
+    }
+
+    pub fn select(mut self, fields: &[&str]) -> Query {
+        self.fields = Some(fields.iter().map(|x| x.to_string()).collect());
+        self
+    }
+
+    // `[Type:] field op value [and field op value]...` where op is one of `= == != < <= > >= ~`,
+    // a lone word is a type name and quoted values are always strings
+    pub fn parse(text: &str) -> Result<Query, QueryError> {
+        let text = text.trim();
+        let mut query = Query::new();
+
+        // Only a name at the very start is the type, so a `:` in a value is left alone
+        let name_end = text.find(|x: char| !x.is_alphanumeric() && x != '_').unwrap_or(text.len());
+        let conditions = match text[name_end..].trim_start().strip_prefix(':') {
+            Some(rest) if name_end > 0 => {
+                query.data_type = Some(text[..name_end].to_string());
+                rest.trim()
+            }
+            _ => text,
+        };
+
+        let tokens = tokenize(conditions)?;
+        if tokens.is_empty() {
+            return Ok(query);
+        }
+        if query.data_type.is_none() && !tokens.iter().any(|(_, x)| matches!(x, Token::Comparison(_))) {
+            query.data_type = Some(conditions.to_string());
+            return Ok(query);
+        }
+
+        for condition in tokens.split(|(_, x)| *x == Token::Word("and")) {
+            let source = match (condition.first(), condition.last()) {
+                (Some(first), Some(last)) => &conditions[first.0.start..last.0.end],
+                _ => return Err(QueryError::Syntax("'and' needs a condition on both sides".to_string())),
+            };
+
+            let comparisons = condition.iter().enumerate()
+                .filter_map(|(i, (_, x))| match x {
+                    Token::Comparison(comparison) => Some((i, *comparison)),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+
+            let (index, comparison) = match comparisons.as_slice() {
+                [] => return Err(QueryError::Syntax(format!("'{}' has no comparison", source))),
+                [comparison] => *comparison,
+                _ => return Err(QueryError::Syntax(format!("'{}' has more than one comparison", source))),
+            };
+
+            match (operand(conditions, &condition[..index]), operand(conditions, &condition[index + 1..])) {
+                (Some((field, _)), Some((value, true))) => query = query.filter(field, comparison, DataValue::OwnedString(value.to_string())),
+                (Some((field, _)), Some((value, false))) => query = query.filter(field, comparison, parse_value(value)),
+                _ => return Err(QueryError::Syntax(format!("'{}' needs a field and a value", source))),
+            }
+        }
+
+        Ok(query)
+    }
+
+    fn used_fields<'a>(&'a self) -> impl Iterator<Item = &'a String> + 'a {
+        self.predicates.iter().map(|x| &x.field)
+            .chain(self.sort.iter().map(|x| &x.0))
+            .chain(self.fields.iter().flat_map(|x| x.iter()))
+    }
+}
+
+impl Default for Query {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token<'a> {
+    Word(&'a str),
+    // Without the quotes
+    Quoted(&'a str),
+    Comparison(Comparison),
+}
+
+fn comparison(text: &str) -> Option<(&'static str, Comparison)> {
+    Comparison::SYMBOLS.iter().find(|(symbol, _)| text.starts_with(symbol)).cloned()
+}
+
+// Tokens with where they are in `text`. Quoted text is one token whatever it contains
+fn tokenize(text: &str) -> Result<Vec<(Range<usize>, Token)>, QueryError> {
+    let mut tokens = vec![];
+    let mut start = 0;
+
+    while let Some(c) = text[start..].chars().next() {
+        let rest = &text[start..];
+        let (len, token) = if c.is_whitespace() {
+            start += c.len_utf8();
+            continue;
+        }
+        else if c == '"' {
+            let end = rest[1..].find('"').ok_or_else(|| QueryError::Syntax(format!("{} has no closing quote", rest)))?;
+            (end + 2, Token::Quoted(&rest[1..end + 1]))
+        }
+        else if let Some((symbol, comparison)) = comparison(rest) {
+            (symbol.len(), Token::Comparison(comparison))
+        }
+        else {
+            let len = rest.char_indices()
+                .map(|(i, _)| i)
+                .skip(1)
+                .find(|i| rest[*i..].starts_with(|x: char| x.is_whitespace() || x == '"') || comparison(&rest[*i..]).is_some())
+                .unwrap_or(rest.len());
+
+            (len, Token::Word(&rest[..len]))
+        };
+
+        tokens.push((start..start + len, token));
+        start += len;
+    }
+
+    Ok(tokens)
+}
+
+// A field name or value, either one quoted string or unquoted words, and whether it was quoted
+fn operand<'a>(text: &'a str, tokens: &[(Range<usize>, Token<'a>)]) -> Option<(&'a str, bool)> {
+    match tokens {
+        [(_, Token::Quoted(quoted))] => Some((quoted, true)),
+        [first, ..] if tokens.iter().all(|(_, x)| matches!(x, Token::Word(_))) => {
+            Some((&text[first.0.start..tokens[tokens.len() - 1].0.end], false))
+        }
+        _ => None,
+    }
+}
+
+// Unquoted text, which is a string when it is not a bool or a number
+fn parse_value(text: &str) -> DataValue {
+    if let Ok(value) = text.parse::<bool>() {
+        DataValue::Bool(value)
+    }
+    else if let Ok(value) = text.parse::<i32>() {
+        DataValue::I32(value)
+    }
+    else if let Ok(value) = text.parse::<f32>() {
+        DataValue::F32(value)
+    }
+    else {
+        DataValue::OwnedString(text.to_string())
+    }
+}
+
+// A record that passed the predicates along with the storage it came from
+struct Found<'a, K> {
+    data_type: &'a str,
+    storage: &'a DataTypeStorage<K>,
+    key: &'a K,
+    values: &'a [DataValue],
+}
+
+impl<'a, K> Found<'a, K> {
+    fn field(&self, name: &str) -> Option<&'a DataValue> {
+        let index = self.storage.get_desc().iter().position(|x| x.name == name)?;
+        self.values.get(index)
+    }
+}
+
+fn has_fields<K: Hash + Eq>(storage: &DataTypeStorage<K>, query: &Query) -> bool {
+    query.used_fields().all(|x| storage.has_field_with_name(x))
+}
+
+fn run<'a, K: Hash + Eq + Ord>(storages: &[(&'a str, &'a DataTypeStorage<K>)], query: &Query) -> Vec<(&'a str, Row<'a, K>)> {
+    let mut found = vec![];
+
+    for &(data_type, storage) in storages {
+        for key in storage.keys() {
+            let values = storage.get(key).unwrap_or(&[]);
+            let record = Found { data_type, storage, key, values };
+
+            let matches = query.predicates.iter().all(|predicate| {
+                record.field(&predicate.field).map(|x| predicate.comparison.matches(x, &predicate.value)).unwrap_or(false)
+            });
+
+            if matches {
+                found.push(record);
+            }
+        }
+    }
+
+    found.sort_by(|a, b| {
+        let by_field = match &query.sort {
+            Some((field, order)) => {
+                let ordering = match (a.field(field), b.field(field)) {
+                    (Some(a), Some(b)) => compare(a, b).unwrap_or(Ordering::Equal),
+                    _ => Ordering::Equal,
+                };
+
+                match order {
+                    SortOrder::Ascending => ordering,
+                    SortOrder::Descending => ordering.reverse(),
+                }
+            }
+            None => Ordering::Equal,
+        };
+
+        by_field.then(a.data_type.cmp(b.data_type)).then(a.key.cmp(b.key))
+    });
+
+    found.into_iter().map(|record| {
+        let values = match &query.fields {
+            Some(fields) => fields.iter().filter_map(|name| {
+                let desc = record.storage.get_desc().iter().find(|x| x.name == *name)?;
+                record.field(name).map(|value| (desc.name.as_str(), value))
+            }).collect(),
+            None => record.storage.get_desc().iter().map(|x| x.name.as_str()).zip(record.values.iter()).collect(),
+        };
+
+        (record.data_type, Row { key: record.key, values })
+    }).collect()
+}
+
+impl<K: Hash + Eq + Ord> DataTypeStorage<K> {
+    // Ignores `Query::data_type`, the storage is already a single type
+    pub fn query<'a>(&'a self, query: &Query) -> Result<Vec<Row<'a, K>>, QueryError> {
+        if let Some(field) = query.used_fields().find(|x| !self.has_field_with_name(x)) {
+            return Err(QueryError::UnknownField(field.clone()));
+        }
+
+        Ok(run(&[("", self)], query).into_iter().map(|(_, row)| row).collect())
+    }
+}
+
+impl<K: Hash + Eq + Ord> DataStorage<K> {
+    // Rows with the name of their type
+    pub fn query<'a>(&'a self, query: &Query) -> Result<Vec<(&'a str, Row<'a, K>)>, QueryError> {
+        let storages = match &query.data_type {
+            Some(name) => {
+                let (name, storage) = self.data.get_key_value(name).ok_or_else(|| QueryError::UnknownType(name.clone()))?;
+                if let Some(field) = query.used_fields().find(|x| !storage.has_field_with_name(x)) {
+                    return Err(QueryError::UnknownField(field.clone()));
+                }
+
+                vec![(name.as_str(), storage)]
+            }
+            None => {
+                let unknown = query.used_fields().find(|field| !self.data.values().any(|x| x.has_field_with_name(field)));
+                if let Some(field) = unknown {
+                    return Err(QueryError::UnknownField(field.clone()));
+                }
+
+                self.data.iter().filter(|(_, x)| has_fields(x, query)).map(|(name, x)| (name.as_str(), x)).collect()
+            }
+        };
+
+        Ok(run(&storages, query))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn predicates(query: &Query) -> Vec<(&str, Comparison, String)> {
+        query.predicates.iter().map(|x| (x.field.as_str(), x.comparison, format!("{:?}", x.value))).collect()
+    }
+
+    fn syntax_error(text: &str) -> String {
+        match Query::parse(text) {
+            Err(QueryError::Syntax(message)) => message,
+            other => panic!("expected a syntax error for '{}', found {:?}", text, other),
+        }
+    }
+
+    #[test]
+    fn a_lone_word_is_a_type() {
+        let query = Query::parse(" Strike ").unwrap();
+
+        assert_eq!(query.data_type.as_deref(), Some("Strike"));
+        assert!(query.predicates.is_empty());
+        assert_eq!(Query::parse("Strike:").unwrap().data_type.as_deref(), Some("Strike"));
+    }
+
+    #[test]
+    fn conditions_follow_the_type() {
+        let query = Query::parse("Strike: damage>=10 and hitstun != 3 and name = jab").unwrap();
+
+        assert_eq!(query.data_type.as_deref(), Some("Strike"));
+        assert_eq!(predicates(&query), [
+            ("damage", Comparison::GreaterOrEqual, "I32(10)".to_string()),
+            ("hitstun", Comparison::NotEqual, "I32(3)".to_string()),
+            ("name", Comparison::Equal, "OwnedString(\"jab\")".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn only_a_leading_name_is_the_type() {
+        let query = Query::parse("label ~ \"a:b\"").unwrap();
+
+        assert_eq!(query.data_type, None);
+        assert_eq!(predicates(&query), [("label", Comparison::Contains, "OwnedString(\"a:b\")".to_string())]);
+    }
+
+    #[test]
+    fn quoted_values_keep_and_and_comparisons() {
+        let query = Query::parse("Sound: name = \"hit and run\" and file == \"a<=b\" and volume < 0.5").unwrap();
+
+        assert_eq!(predicates(&query), [
+            ("name", Comparison::Equal, "OwnedString(\"hit and run\")".to_string()),
+            ("file", Comparison::Equal, "OwnedString(\"a<=b\")".to_string()),
+            ("volume", Comparison::Less, "F32(0.5)".to_string()),
+        ]);
+        assert!(matches!(Query::parse("flag = \"true\"").unwrap().predicates[0].value, DataValue::OwnedString(_)));
+    }
+
+    #[test]
+    fn malformed_conditions_are_syntax_errors() {
+        assert_eq!(syntax_error("Strike: damage"), "'damage' has no comparison");
+        assert_eq!(syntax_error("damage > 1 and"), "'and' needs a condition on both sides");
+        assert_eq!(syntax_error("> 1"), "'> 1' needs a field and a value");
+        assert_eq!(syntax_error("damage >"), "'damage >' needs a field and a value");
+        assert_eq!(syntax_error("1 < damage < 5"), "'1 < damage < 5' has more than one comparison");
+        assert_eq!(syntax_error("name = \"open"), "\"open has no closing quote");
+    }
+}
@@ -0,0 +1,160 @@
+use glium::Rect;
+use glm::{Vec2, vec2};
+
+// Scales are in screen pixels per sprite pixel, anything at or above 1.0 is a whole
+// number so the sprite stays crisp when sampled with `SamplerType::Nearest`
+pub const ZOOM_LEVELS: [f32; 12] = [0.25, 0.5, 1.0, 2.0, 3.0, 4.0, 6.0, 8.0, 12.0, 16.0, 24.0, 32.0];
+const DEFAULT_ZOOM: usize = 3;
+
+// Everything needed to draw sprite space into a render window
+#[derive(Copy, Clone, Debug)]
+pub struct View {
+    pub matrix: [[f32; 4]; 4],
+    pub viewport: Rect,
+    // Position of the sprite space origin in pixels from the top left of the viewport
+    pub origin: [f32; 2],
+    pub scale: f32,
+}
+
+impl View {
+    // View for drawing something that has its origin at `x` in sprite space, flipped if `mirrored`
+    pub fn placed(&self, x: f32, mirrored: bool) -> View {
+        let mut placed = *self;
+        let [column_x, _, _, column_w] = self.matrix;
+        let sign = if mirrored { -1.0 } else { 1.0 };
+
+        for i in 0..4 {
+            placed.matrix[0][i] = column_x[i] * sign;
+            placed.matrix[3][i] = column_x[i] * x + column_w[i];
+        }
+
+        placed
+    }
+}
+
+// Sprite space is in pixels with (0, 0) at the top left of the sprite sheet and y going down.
+// Screen space is the render window local space, (-1, -1) bottom left to (1, 1) top right
+#[derive(Clone, Debug)]
+pub struct Camera {
+    // Centre of the view, this is in sprite space with `mirror` applied
+    pub centre: Vec2,
+    // Flips sprite space [horizontally, vertically] about the origin
+    pub mirror: [bool; 2],
+    zoom: usize,
+    viewport: (f32, f32),
+}
+
+impl Camera {
+    pub fn new() -> Camera {
+        Camera {
+            centre: vec2(0.0, 0.0),
+            mirror: [false, false],
+            zoom: DEFAULT_ZOOM,
+            viewport: (1.0, 1.0),
+        }
+    }
+
+    pub fn scale(&self) -> f32 {
+        ZOOM_LEVELS[self.zoom]
+    }
+
+    pub fn zoom_level(&self) -> usize {
+        self.zoom
+    }
+
+    pub fn set_zoom_level(&mut self, level: usize) {
+        self.zoom = level.min(ZOOM_LEVELS.len() - 1);
+    }
+
+    pub fn set_viewport(&mut self, (width, height): (f32, f32)) {
+        self.viewport = (width.max(1.0), height.max(1.0));
+    }
+
+    pub fn viewport(&self) -> (f32, f32) {
+        self.viewport
+    }
+
+    pub fn look_at(&mut self, point: Vec2) {
+        self.centre = self.mirror_vector(point);
+    }
+
+    // Applying this twice gives back the original vector
+    pub fn mirror_vector(&self, vector: Vec2) -> Vec2 {
+        vec2(
+            if self.mirror[0] { -vector.x } else { vector.x },
+            if self.mirror[1] { -vector.y } else { vector.y },
+        )
+    }
+
+    // Rounded so sprite pixels always start on a screen pixel
+    fn origin(&self) -> Vec2 {
+        let scale = self.scale();
+        let x = (self.viewport.0 * 0.5 - self.centre.x * scale).round();
+        let y = (self.viewport.1 * 0.5 - self.centre.y * scale).round();
+
+        vec2(x, y)
+    }
+
+    fn screen_to_pixels(&self, (x, y): (f32, f32)) -> Vec2 {
+        vec2((x + 1.0) * 0.5 * self.viewport.0, (1.0 - y) * 0.5 * self.viewport.1)
+    }
+
+    pub fn screen_to_sprite(&self, screen: (f32, f32)) -> Vec2 {
+        self.mirror_vector((self.screen_to_pixels(screen) - self.origin()) / self.scale())
+    }
+
+    pub fn sprite_to_screen(&self, sprite: Vec2) -> (f32, f32) {
+        let pixels = self.origin() + self.mirror_vector(sprite) * self.scale();
+        let x = pixels.x / self.viewport.0 * 2.0 - 1.0;
+        let y = 1.0 - pixels.y / self.viewport.1 * 2.0;
+
+        (x, y)
+    }
+
+    // Movement is in window pixels as given by `Mouse::get_movement`
+    pub fn pan(&mut self, movement: Vec2) {
+        self.centre -= movement / self.scale();
+    }
+
+    pub fn zoom_towards(&mut self, steps: i32, screen: (f32, f32)) {
+        let level = (self.zoom as i32 + steps).max(0) as usize;
+        let anchor = self.mirror_vector(self.screen_to_sprite(screen));
+        let pixels = self.screen_to_pixels(screen);
+
+        self.set_zoom_level(level);
+
+        let half_viewport = vec2(self.viewport.0 * 0.5, self.viewport.1 * 0.5);
+        self.centre = anchor - (pixels - half_viewport) / self.scale();
+    }
+
+    pub fn view(&self, viewport: Rect) -> View {
+        let scale = self.scale();
+        let origin = self.origin();
+        let (width, height) = self.viewport;
+
+        let x_sign = if self.mirror[0] { -1.0 } else { 1.0 };
+        let y_sign = if self.mirror[1] { -1.0 } else { 1.0 };
+        let x_scale = x_sign * 2.0 * scale / width;
+        let y_scale = -y_sign * 2.0 * scale / height;
+        let x_offset = origin.x * 2.0 / width - 1.0;
+        let y_offset = 1.0 - origin.y * 2.0 / height;
+
+        View {
+            matrix: [
+                [x_scale, 0.0, 0.0, 0.0],
+                [0.0, y_scale, 0.0, 0.0],
+                [0.0, 0.0, 1.0, 0.0],
+                [x_offset, y_offset, 0.0, 1.0],
+            ],
+            viewport,
+            origin: [origin.x, origin.y],
+            scale,
+        }
+    }
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,49 @@
+use glium::{
+    Program, Surface, VertexBuffer,
+    backend::glutin::Display,
+    index::{NoIndices, PrimitiveType},
+    uniform,
+};
+use super::{Vertex, shape_gen, draw_parameters, camera::View};
+
+// Below this scale the lines would cover most of the sprite
+pub const MIN_GRID_SCALE: f32 = 4.0;
+
+pub struct GridRenderer {
+    program: Program,
+    buffer: VertexBuffer<Vertex>,
+}
+
+impl GridRenderer {
+    pub fn new(display: &Display) -> Result<GridRenderer, Box<dyn std::error::Error>> {
+        let program = Program::from_source(
+            display,
+            include_str!("../../shaders/grid-vert.glsl"),
+            include_str!("../../shaders/grid-frag.glsl"),
+            None
+        )?;
+
+        Ok(GridRenderer {
+            program,
+            buffer: shape_gen::gen_box(display),
+        })
+    }
+
+    pub fn render<S: Surface + ?Sized>(&self, target: &mut S, view: &View, colour: [f32; 4]) -> Result<(), Box<dyn std::error::Error>> {
+        if view.scale < MIN_GRID_SCALE {
+            return Ok(());
+        }
+
+        let uniforms = uniform!(
+            viewport_origin: [view.viewport.left as f32, view.viewport.bottom as f32],
+            viewport_height: view.viewport.height as f32,
+            origin: view.origin,
+            scale: view.scale,
+            colour: colour,
+        );
+
+        target.draw(&self.buffer, &NoIndices(PrimitiveType::TriangleStrip), &self.program, &uniforms, &draw_parameters(view))?;
+
+        Ok(())
+    }
+}
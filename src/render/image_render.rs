@@ -1,8 +1,12 @@
 use glium::{
+    Program, Surface, VertexBuffer,
     backend::glutin::Display,
+    index::{NoIndices, PrimitiveType},
     texture::{texture2d::Texture2d, RawImage2d},
     uniforms::{Sampler, MagnifySamplerFilter},
+    uniform,
 };
+use super::{Vertex, shape_gen, model_matrix, draw_parameters, camera::View};
 use std::{
     path::Path,
     collections::HashMap,
@@ -36,6 +40,14 @@ impl Textures {
         }
     }
 
+    pub fn get(&self, name: &str) -> Option<&Texture2d> {
+        self.images.get(name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.images.contains_key(name)
+    }
+
     pub fn get_sampler<'a>(&'a self, name: &str, sample: SamplerType) -> Option<Sampler<'a, Texture2d>> {
         self.images.get(name).map(|texture| {
             let mut sampler = Sampler::new(texture);
@@ -61,4 +73,68 @@ impl Textures {
 
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+impl Default for Textures {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// A region of a texture in pixels, with (0, 0) being the top left of the image
+#[derive(Copy, Clone, Debug)]
+pub struct ImageRegion {
+    pub left: f32,
+    pub top: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+pub struct ImageRenderer {
+    program: Program,
+    buffer: VertexBuffer<Vertex>,
+}
+
+impl ImageRenderer {
+    pub fn new(display: &Display) -> Result<ImageRenderer, Box<dyn Error>> {
+        let program = Program::from_source(
+            display,
+            include_str!("../../shaders/image-vert.glsl"),
+            include_str!("../../shaders/image-frag.glsl"),
+            None
+        )?;
+
+        Ok(ImageRenderer {
+            program,
+            buffer: shape_gen::gen_box(display),
+        })
+    }
+
+    // Draws the region of the texture centred on `position` in sprite space, `flipped` is [horizontal, vertical]
+    pub fn render_region<S: Surface + ?Sized>(&self, target: &mut S, view: &View, sampler: Sampler<Texture2d>, region: ImageRegion, position: [f32; 2], flipped: [bool; 2], tint: [f32; 4]) -> Result<(), Box<dyn Error>> {
+        let (width, height) = sampler.0.dimensions();
+        let (width, height) = (width as f32, height as f32);
+        let uv_rect = [
+            region.left / width,
+            region.top / height,
+            (region.left + region.width) / width,
+            (region.top + region.height) / height,
+        ];
+
+        let uniforms = uniform!(
+            dims: [
+                if flipped[0] { -region.width } else { region.width },
+                if flipped[1] { -region.height } else { region.height },
+            ],
+            model: model_matrix(position, 0.0),
+            view: view.matrix,
+            uv_rect: uv_rect,
+            image: sampler,
+            tint: tint,
+        );
+
+        target.draw(&self.buffer, &NoIndices(PrimitiveType::TriangleStrip), &self.program, &uniforms, &draw_parameters(view))?;
+
+        Ok(())
+    }
+}
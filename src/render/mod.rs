@@ -1,10 +1,12 @@
 pub mod shape_gen;
 pub mod render_windows;
 pub mod image_render;
+pub mod camera;
+pub mod grid_render;
 
 use glium::*;
-use na::geometry::*;
-use na::Vector2;
+use glium::backend::glutin::Display;
+use self::camera::View;
 
 #[derive(Copy, Clone, Debug)]
 pub struct Vertex {
@@ -13,20 +15,81 @@ pub struct Vertex {
 
 implement_vertex!(Vertex, pos);
 
+pub fn model_matrix([x, y]: [f32; 2], angle: f32) -> [[f32; 4]; 4] {
+    let (sin, cos) = angle.sin_cos();
+
+    [
+        [cos, sin, 0.0, 0.0],
+        [-sin, cos, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 0.0],
+        [x, y, 0.0, 1.0],
+    ]
+}
+
+pub fn draw_parameters<'a>(view: &View) -> DrawParameters<'a> {
+    DrawParameters {
+        viewport: Some(view.viewport),
+        blend: Blend::alpha_blending(),
+        ..Default::default()
+    }
+}
+
 pub struct BoxRenderer {
     program: Program,
     box_buffer: VertexBuffer<Vertex>,
-
+    outline_buffer: VertexBuffer<Vertex>,
 }
 
 impl BoxRenderer {
-    pub fn render_box<S: Surface + ?Sized>(&self, target: &mut S, box_dims: [f32; 2], [x, y]: [f32; 2], angle: f32) -> Result<(), Box<dyn std::error::Error>> {
-        let translation = Translation::from(Vector2::new(x, y));
-        let rotation = UnitComplex::new(angle);
+    pub fn new(display: &Display) -> Result<BoxRenderer, Box<dyn std::error::Error>> {
+        let program = Program::from_source(
+            display,
+            include_str!("../../shaders/box-vert.glsl"),
+            include_str!("../../shaders/box-frag.glsl"),
+            None
+        )?;
+
+        Ok(BoxRenderer {
+            program,
+            box_buffer: shape_gen::gen_box(display),
+            outline_buffer: shape_gen::gen_box_outline(display),
+        })
+    }
 
-        let uniforms = uniform!(dims: box_dims);
+    fn draw<S: Surface + ?Sized>(&self, target: &mut S, view: &View, buffer: &VertexBuffer<Vertex>, primitive: index::PrimitiveType, box_dims: [f32; 2], position: [f32; 2], angle: f32, colour: [f32; 4]) -> Result<(), Box<dyn std::error::Error>> {
+        let uniforms = uniform!(
+            dims: box_dims,
+            model: model_matrix(position, angle),
+            view: view.matrix,
+            colour: colour,
+        );
+
+        target.draw(buffer, &index::NoIndices(primitive), &self.program, &uniforms, &draw_parameters(view))?;
 
         Ok(())
     }
+
+    pub fn render_box<S: Surface + ?Sized>(&self, target: &mut S, view: &View, box_dims: [f32; 2], position: [f32; 2], angle: f32, colour: [f32; 4]) -> Result<(), Box<dyn std::error::Error>> {
+        self.draw(target, view, &self.box_buffer, index::PrimitiveType::TriangleStrip, box_dims, position, angle, colour)
+    }
+
+    pub fn render_outline<S: Surface + ?Sized>(&self, target: &mut S, view: &View, box_dims: [f32; 2], position: [f32; 2], angle: f32, colour: [f32; 4]) -> Result<(), Box<dyn std::error::Error>> {
+        self.draw(target, view, &self.outline_buffer, index::PrimitiveType::LineLoop, box_dims, position, angle, colour)
+    }
 }
 
+pub struct CanvasRenderer {
+    pub boxes: BoxRenderer,
+    pub images: image_render::ImageRenderer,
+    pub grid: grid_render::GridRenderer,
+}
+
+impl CanvasRenderer {
+    pub fn new(display: &Display) -> Result<CanvasRenderer, Box<dyn std::error::Error>> {
+        Ok(CanvasRenderer {
+            boxes: BoxRenderer::new(display)?,
+            images: image_render::ImageRenderer::new(display)?,
+            grid: grid_render::GridRenderer::new(display)?,
+        })
+    }
+}
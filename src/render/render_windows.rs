@@ -23,7 +23,7 @@ impl RenderWindow {
         let width = self.width * 0.5 * x;
         let height = self.height * 0.5 * y;
         let left = (self.left + 1.0) * 0.5 * x;
-        let bottom = (-self.bottom + 1.0) * 0.5 * y;
+        let bottom = (self.bottom + 1.0) * 0.5 * y;
 
         Rect {
             left: left as u32,
@@ -32,6 +32,22 @@ impl RenderWindow {
             height: height as u32,
         }
     }
+
+    pub fn pixel_size(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        (self.width * 0.5 * x, self.height * 0.5 * y)
+    }
+
+    pub fn contains(&self, (x, y): (f32, f32)) -> bool {
+        x >= self.left && x <= self.left + self.width && y >= self.bottom && y <= self.bottom + self.height
+    }
+
+    // Converts from screen space into this windows local space, with the window covering (-1, -1, 2, 2)
+    pub fn to_local(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        let x = (x - self.left) / self.width * 2.0 - 1.0;
+        let y = (y - self.bottom) / self.height * 2.0 - 1.0;
+
+        (x, y)
+    }
 }
 
 pub struct RenderWindows {
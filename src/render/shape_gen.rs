@@ -14,3 +14,14 @@ pub fn gen_box(display: &Display) -> VertexBuffer<Vertex> {
 
     VertexBuffer::new(display, &data).unwrap()
 }
+
+pub fn gen_box_outline(display: &Display) -> VertexBuffer<Vertex> {
+    let data = [
+        Vertex { pos: [-0.5, -0.5 ]},
+        Vertex { pos: [ 0.5, -0.5 ]},
+        Vertex { pos: [ 0.5, 0.5 ]},
+        Vertex { pos: [-0.5, 0.5 ]},
+    ];
+
+    VertexBuffer::new(display, &data).unwrap()
+}
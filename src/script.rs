@@ -0,0 +1,451 @@
+use std::{
+    cell::RefCell,
+    rc::Rc,
+};
+use rhai::{Array, Dynamic, Engine, EvalAltResult, Map};
+use na::{Isometry2, Vector2};
+use crate::{
+    category::BoxCategory,
+    data::{DataType, DataValue, RecordId},
+    frame::AnimationData,
+    frame_data::FrameDataSummary,
+    project::Project,
+    shapes::{BoundingBox, Cuboid},
+};
+
+// Stops runaway loops from freezing the editor
+const MAX_OPERATIONS: u64 = 50_000_000;
+
+type Shared = Rc<RefCell<Project>>;
+type ScriptResult<T> = Result<T, Box<EvalAltResult>>;
+
+// What a script printed and warned about, and the error that stopped it if any.
+// Changes made by a script that fails are undone
+#[derive(Clone, Debug, Default)]
+pub struct ScriptOutput {
+    pub printed: Vec<String>,
+    pub warnings: Vec<String>,
+    pub error: Option<String>,
+}
+
+impl ScriptOutput {
+    pub fn succeeded(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+#[derive(Clone)]
+struct AnimationRef {
+    project: Shared,
+    index: usize,
+}
+
+#[derive(Clone)]
+struct BoxRef {
+    project: Shared,
+    animation: usize,
+    index: usize,
+}
+
+#[derive(Clone)]
+struct MarkerRef {
+    project: Shared,
+    animation: usize,
+    index: usize,
+}
+
+#[derive(Clone)]
+struct RecordRef {
+    project: Shared,
+    animation: usize,
+    dtype: String,
+    key: RecordId,
+}
+
+fn to_dynamic(value: &DataValue) -> Dynamic {
+    match value {
+        DataValue::F32(value) => Dynamic::from_float(*value as f64),
+        DataValue::I32(value) => Dynamic::from_int(*value as i64),
+        DataValue::U32(value) => Dynamic::from_int(*value as i64),
+        DataValue::Char(value) => Dynamic::from_char(*value),
+        DataValue::Bool(value) => Dynamic::from_bool(*value),
+        DataValue::OwnedString(value) => Dynamic::from(value.clone()),
+    }
+}
+
+fn number(value: &Dynamic) -> ScriptResult<f64> {
+    value.as_float()
+        .or_else(|_| value.as_int().map(|x| x as f64))
+        .map_err(|_| format!("expected a number but got {}", value.type_name()).into())
+}
+
+fn from_dynamic(value: Dynamic, dtype: DataType) -> ScriptResult<DataValue> {
+    let mismatch = |value: &Dynamic| -> Box<EvalAltResult> {
+        format!("expected {} but got {}", dtype.name(), value.type_name()).into()
+    };
+
+    let value = match dtype {
+        DataType::F32 => DataValue::F32(number(&value)? as f32),
+        DataType::I32 => DataValue::I32(number(&value)?.round() as i32),
+        DataType::U32 => DataValue::U32(number(&value)?.round().max(0.0) as u32),
+        DataType::Bool => DataValue::Bool(value.as_bool().map_err(|_| mismatch(&value))?),
+        DataType::Char => match value.as_char() {
+            Ok(value) => DataValue::Char(value),
+            Err(_) => value.to_string().parse().map(DataValue::Char).map_err(|_| mismatch(&value))?,
+        },
+        DataType::OwnedString => DataValue::OwnedString(value.to_string()),
+    };
+
+    Ok(value)
+}
+
+impl AnimationRef {
+    fn read<R>(&self, f: impl FnOnce(&AnimationData) -> R) -> R {
+        f(&self.project.borrow().animations[self.index])
+    }
+
+    fn write<R>(&self, f: impl FnOnce(&mut AnimationData) -> R) -> R {
+        f(&mut self.project.borrow_mut().animations[self.index])
+    }
+
+    fn boxes(&mut self) -> Array {
+        let count = self.read(|x| x.data_boxes().len());
+        (0..count).map(|index| Dynamic::from(BoxRef { project: self.project.clone(), animation: self.index, index })).collect()
+    }
+
+    fn boxes_in_category(&mut self, category: &str) -> ScriptResult<Array> {
+        let category = BoxCategory::from_name(category).ok_or_else(|| format!("no category named '{}'", category))?;
+        let indices = self.read(|x| x.boxes_in_category(category).map(|(i, _)| i).collect::<Vec<_>>());
+
+        Ok(indices.into_iter().map(|index| Dynamic::from(BoxRef { project: self.project.clone(), animation: self.index, index })).collect())
+    }
+
+    fn markers(&mut self) -> Array {
+        let count = self.read(|x| x.markers().len());
+        (0..count).map(|index| Dynamic::from(MarkerRef { project: self.project.clone(), animation: self.index, index })).collect()
+    }
+
+    fn types(&mut self) -> Array {
+        let mut names = self.read(|x| x.data().data.keys().cloned().collect::<Vec<_>>());
+        names.sort();
+        names.into_iter().map(Dynamic::from).collect()
+    }
+
+    // Sorted by key
+    fn records(&mut self, dtype: &str) -> ScriptResult<Array> {
+        let mut keys = self.read(|x| x.data().get_storage(dtype).map(|x| x.keys().cloned().collect::<Vec<_>>()))
+            .ok_or_else(|| format!("no type named '{}'", dtype))?;
+        keys.sort();
+
+        Ok(keys.into_iter().map(|key| Dynamic::from(RecordRef {
+            project: self.project.clone(),
+            animation: self.index,
+            dtype: dtype.to_string(),
+            key,
+        })).collect())
+    }
+
+    fn summary(&mut self) -> Map {
+        let summary = self.read(FrameDataSummary::summarize);
+        let optional = |x: Option<i64>| x.map(Dynamic::from_int).unwrap_or(Dynamic::UNIT);
+
+        let mut map = Map::new();
+        map.insert("total".into(), Dynamic::from_int(summary.total as i64));
+        map.insert("startup".into(), optional(summary.startup.map(|x| x as i64)));
+        map.insert("active".into(), optional(summary.active.map(|x| x as i64)));
+        map.insert("recovery".into(), optional(summary.recovery.map(|x| x as i64)));
+        map.insert("on_hit".into(), optional(summary.on_hit.map(|x| x as i64)));
+        map.insert("on_block".into(), optional(summary.on_block.map(|x| x as i64)));
+        map
+    }
+
+    fn frame_duration(&mut self, frame: i64) -> ScriptResult<i64> {
+        self.read(|x| x.frame(frame.max(0) as usize).map(|x| x.duration() as i64))
+            .ok_or_else(|| format!("no frame {}", frame).into())
+    }
+
+    fn set_frame_duration(&mut self, frame: i64, duration: i64) -> ScriptResult<()> {
+        let duration = duration.max(1).min(u16::max_value() as i64) as u16;
+        self.write(|x| x.frame_mut(frame.max(0) as usize).map(|x| x.set_duration(duration)))
+            .ok_or_else(|| format!("no frame {}", frame).into())
+    }
+}
+
+impl BoxRef {
+    fn shape(&self) -> BoundingBox<f32> {
+        self.project.borrow().animations[self.animation].data_boxes()[self.index].shape
+    }
+
+    // Rebuilds the shape from the current values with one of them replaced
+    fn set_shape(&mut self, property: &str, value: &Dynamic) -> ScriptResult<()> {
+        let value = number(value)? as f32;
+        let shape = self.shape();
+        let translation = shape.get_translation();
+        let (mut x, mut y, mut width, mut height, mut angle) = (translation.x, translation.y, shape.rect.width, shape.rect.height, shape.get_rotation());
+
+        match property {
+            "x" => x = value,
+            "y" => y = value,
+            "width" => width = value.max(0.0),
+            "height" => height = value.max(0.0),
+            _ => angle = value,
+        }
+
+        let shape = BoundingBox::new(Cuboid::new(width, height), Isometry2::new(Vector2::new(x, y), angle));
+        self.project.borrow_mut().animations[self.animation].data_boxes_mut()[self.index].shape = shape;
+        Ok(())
+    }
+
+    fn record(&mut self) -> RecordRef {
+        let (dtype, key) = self.project.borrow().animations[self.animation].data_boxes()[self.index].data.clone();
+        RecordRef { project: self.project.clone(), animation: self.animation, dtype, key }
+    }
+
+    fn active_frames(&mut self) -> Array {
+        let project = self.project.borrow();
+        let animation = &project.animations[self.animation];
+
+        (0..animation.frames().len())
+            .filter(|frame| animation.is_box_active(self.index, *frame))
+            .map(|frame| Dynamic::from_int(frame as i64))
+            .collect()
+    }
+}
+
+impl MarkerRef {
+    fn record(&mut self) -> RecordRef {
+        let (dtype, key) = self.project.borrow().animations[self.animation].markers()[self.index].data.clone();
+        RecordRef { project: self.project.clone(), animation: self.animation, dtype, key }
+    }
+}
+
+impl RecordRef {
+    fn get(&mut self, field: &str) -> ScriptResult<Dynamic> {
+        let project = self.project.borrow();
+        let storage = project.animations[self.animation].data().get_storage(&self.dtype)
+            .ok_or_else(|| format!("no type named '{}'", self.dtype))?;
+
+        storage.get_field(&self.key, field)
+            .map(to_dynamic)
+            .ok_or_else(|| format!("'{}' has no field '{}'", self.dtype, field).into())
+    }
+
+    fn set(&mut self, field: &str, value: Dynamic) -> ScriptResult<()> {
+        let mut project = self.project.borrow_mut();
+        let storage = project.animations[self.animation].data_mut().get_storage_mut(&self.dtype)
+            .ok_or_else(|| format!("no type named '{}'", self.dtype))?;
+        let index = storage.field_index(field).ok_or_else(|| format!("'{}' has no field '{}'", self.dtype, field))?;
+
+        let value = from_dynamic(value, storage.get_desc()[index].dtype).map_err(|e| format!("'{}': {}", field, e))?;
+        storage.set(&self.key, index, value).map_err(|e| format!("'{}': {}", self.dtype, e).into())
+    }
+
+    fn fields(&mut self) -> Array {
+        let project = self.project.borrow();
+        let storage = project.animations[self.animation].data().get_storage(&self.dtype);
+
+        storage.map(|x| x.get_desc().iter().map(|x| Dynamic::from(x.name.clone())).collect()).unwrap_or_default()
+    }
+}
+
+fn register_animation(engine: &mut Engine) {
+    engine.register_type_with_name::<AnimationRef>("Animation")
+        .register_get("name", |x: &mut AnimationRef| x.read(|x| x.name.clone()))
+        .register_get("fps", |x: &mut AnimationRef| x.read(|x| x.fps() as i64))
+        .register_set("fps", |x: &mut AnimationRef, fps: i64| x.write(|x| x.set_fps(fps.max(1).min(u16::max_value() as i64) as u16)))
+        .register_get("frame_count", |x: &mut AnimationRef| x.read(|x| x.frames().len() as i64))
+        .register_get("total_ticks", |x: &mut AnimationRef| x.read(|x| x.total_ticks() as i64))
+        .register_fn("boxes", AnimationRef::boxes)
+        .register_fn("boxes", AnimationRef::boxes_in_category)
+        .register_fn("markers", AnimationRef::markers)
+        .register_fn("types", AnimationRef::types)
+        .register_fn("records", AnimationRef::records)
+        .register_fn("summary", AnimationRef::summary)
+        .register_fn("frame_duration", AnimationRef::frame_duration)
+        .register_fn("set_frame_duration", AnimationRef::set_frame_duration)
+        .register_fn("to_string", |x: &mut AnimationRef| x.read(|x| x.name.clone()));
+}
+
+fn register_box(engine: &mut Engine) {
+    engine.register_type_with_name::<BoxRef>("Box")
+        .register_get("index", |x: &mut BoxRef| x.index as i64)
+        .register_get("category", |x: &mut BoxRef| x.project.borrow().animations[x.animation].data_boxes()[x.index].category.name().to_string())
+        .register_get("type", |x: &mut BoxRef| x.record().dtype)
+        .register_get("key", |x: &mut BoxRef| x.record().key as i64)
+        .register_get("record", BoxRef::record)
+        .register_get("x", |x: &mut BoxRef| x.shape().get_translation().x as f64)
+        .register_get("y", |x: &mut BoxRef| x.shape().get_translation().y as f64)
+        .register_get("width", |x: &mut BoxRef| x.shape().rect.width as f64)
+        .register_get("height", |x: &mut BoxRef| x.shape().rect.height as f64)
+        .register_get("angle", |x: &mut BoxRef| x.shape().get_rotation() as f64)
+        .register_set("x", |x: &mut BoxRef, value: Dynamic| x.set_shape("x", &value))
+        .register_set("y", |x: &mut BoxRef, value: Dynamic| x.set_shape("y", &value))
+        .register_set("width", |x: &mut BoxRef, value: Dynamic| x.set_shape("width", &value))
+        .register_set("height", |x: &mut BoxRef, value: Dynamic| x.set_shape("height", &value))
+        .register_set("angle", |x: &mut BoxRef, value: Dynamic| x.set_shape("angle", &value))
+        .register_fn("active_frames", BoxRef::active_frames)
+        .register_indexer_get(|x: &mut BoxRef, field: &str| x.record().get(field))
+        .register_indexer_set(|x: &mut BoxRef, field: &str, value: Dynamic| x.record().set(field, value))
+        .register_fn("to_string", |x: &mut BoxRef| {
+            let record = x.record();
+            format!("box {} ({} {})", x.index, record.dtype, record.key)
+        });
+}
+
+fn register_marker(engine: &mut Engine) {
+    engine.register_type_with_name::<MarkerRef>("Marker")
+        .register_get("name", |x: &mut MarkerRef| x.project.borrow().animations[x.animation].markers()[x.index].name.clone())
+        .register_get("start", |x: &mut MarkerRef| x.project.borrow().animations[x.animation].markers()[x.index].start as i64)
+        .register_get("length", |x: &mut MarkerRef| x.project.borrow().animations[x.animation].markers()[x.index].length as i64)
+        .register_set("start", |x: &mut MarkerRef, value: i64| {
+            x.project.borrow_mut().animations[x.animation].markers_mut()[x.index].start = value.max(0).min(u32::max_value() as i64) as u32;
+        })
+        .register_set("length", |x: &mut MarkerRef, value: i64| {
+            x.project.borrow_mut().animations[x.animation].markers_mut()[x.index].length = value.max(0).min(u32::max_value() as i64) as u32;
+        })
+        .register_get("type", |x: &mut MarkerRef| x.record().dtype)
+        .register_get("key", |x: &mut MarkerRef| x.record().key as i64)
+        .register_get("record", MarkerRef::record)
+        .register_indexer_get(|x: &mut MarkerRef, field: &str| x.record().get(field))
+        .register_indexer_set(|x: &mut MarkerRef, field: &str, value: Dynamic| x.record().set(field, value))
+        .register_fn("to_string", |x: &mut MarkerRef| {
+            let record = x.record();
+            format!("marker {} ({} {})", x.index, record.dtype, record.key)
+        });
+}
+
+fn register_record(engine: &mut Engine) {
+    engine.register_type_with_name::<RecordRef>("Record")
+        .register_get("type", |x: &mut RecordRef| x.dtype.clone())
+        .register_get("key", |x: &mut RecordRef| x.key as i64)
+        .register_fn("fields", RecordRef::fields)
+        .register_indexer_get(RecordRef::get)
+        .register_indexer_set(RecordRef::set)
+        .register_fn("to_string", |x: &mut RecordRef| format!("{} {}", x.dtype, x.key));
+}
+
+fn engine(project: &Shared, output: &Rc<RefCell<ScriptOutput>>, max_operations: u64) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(max_operations);
+
+    let printed = output.clone();
+    engine.on_print(move |text| printed.borrow_mut().printed.push(text.to_string()));
+    let warnings = output.clone();
+    engine.register_fn("warn", move |message: &str| warnings.borrow_mut().warnings.push(message.to_string()));
+
+    let shared = project.clone();
+    engine.register_fn("animations", move || -> Array {
+        let count = shared.borrow().animations.len();
+        (0..count).map(|index| Dynamic::from(AnimationRef { project: shared.clone(), index })).collect()
+    });
+
+    let shared = project.clone();
+    engine.register_fn("animation", move |name: &str| -> ScriptResult<AnimationRef> {
+        let index = shared.borrow().animation_index(name).ok_or_else(|| format!("no animation named '{}'", name))?;
+        Ok(AnimationRef { project: shared.clone(), index })
+    });
+
+    register_animation(&mut engine);
+    register_box(&mut engine);
+    register_marker(&mut engine);
+    register_record(&mut engine);
+
+    engine
+}
+
+// Runs `source` over the project, undoing its changes if it fails
+pub fn run(project: &mut Project, source: &str) -> ScriptOutput {
+    run_limited(project, source, MAX_OPERATIONS)
+}
+
+fn run_limited(project: &mut Project, source: &str, max_operations: u64) -> ScriptOutput {
+    let backup = project.clone();
+    let shared = Rc::new(RefCell::new(std::mem::replace(project, Project::new())));
+    let output = Rc::new(RefCell::new(ScriptOutput::default()));
+
+    let result = engine(&shared, &output, max_operations).run(source);
+    *project = shared.replace(Project::new());
+
+    let mut output = output.replace(ScriptOutput::default());
+    if let Err(error) = result {
+        output.error = Some(error.to_string());
+        *project = backup;
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        frame::Frame,
+        shapes::AABB,
+    };
+    use super::*;
+
+    // `Punch` with two frames and one `Hit` record whose damage is infinite
+    fn project() -> Project {
+        let mut animation = AnimationData::new("Punch".to_string(), 60);
+        animation.push_frame(Frame::new(AABB::new(0, 0, 8, 8)));
+        animation.push_frame(Frame::new(AABB::new(8, 0, 8, 8)));
+        animation.new_data_type("Hit".to_string());
+        animation.new_marker("hit".to_string(), "Hit", 0, 1).unwrap();
+
+        let storage = animation.data_mut().get_storage_mut("Hit").unwrap();
+        storage.add_field("damage".to_string(), DataType::F32).unwrap();
+        let key = *storage.keys().next().unwrap();
+        storage.set_value(&key, 0, "inf").unwrap();
+
+        let mut project = Project::new();
+        project.add_animation(animation);
+        project
+    }
+
+    fn damage(project: &Project) -> f32 {
+        let storage = project.animations[0].data().get_storage("Hit").unwrap();
+        match storage.get_field(storage.keys().next().unwrap(), "damage") {
+            Some(DataValue::F32(value)) => *value,
+            other => panic!("expected an f32, found {:?}", other),
+        }
+    }
+
+    const EDIT: &str = r#"
+        let punch = animation("Punch");
+        punch.set_frame_duration(0, 5);
+        let hit = punch.records("Hit")[0];
+        hit["damage"] = 12.5;
+        print(punch.name);
+    "#;
+
+    #[test]
+    fn edits_are_kept_when_the_script_succeeds() {
+        let mut project = project();
+        let output = run(&mut project, EDIT);
+
+        assert!(output.succeeded(), "{:?}", output.error);
+        assert_eq!(output.printed, ["Punch"]);
+        assert_eq!(project.animations[0].frames()[0].duration(), 5);
+        assert_eq!(damage(&project), 12.5);
+    }
+
+    #[test]
+    fn edits_are_undone_when_the_script_fails() {
+        let mut project = project();
+        let output = run(&mut project, &format!("{}\nthrow \"stop\";", EDIT));
+
+        assert!(!output.succeeded());
+        assert_eq!(output.printed, ["Punch"]);
+        assert_eq!(project.animations[0].frames()[0].duration(), 1);
+        assert_eq!(damage(&project), f32::INFINITY);
+    }
+
+    #[test]
+    fn runaway_scripts_are_stopped_and_undone() {
+        let mut project = project();
+        let output = run_limited(&mut project, "animation(\"Punch\").set_frame_duration(0, 5); loop {}", 1_000);
+
+        assert!(!output.succeeded());
+        assert_eq!(project.animations[0].frames()[0].duration(), 1);
+        assert_eq!(project.animations.len(), 1);
+    }
+}
@@ -1,7 +1,8 @@
 use num::Num;
 use na::{
     base::dimension::U2,
-    geometry::{Isometry2, Translation, UnitComplex},
+    geometry::{Isometry2, Point2, Translation, UnitComplex},
+    Vector2,
 };
 
 pub type Translation2<T> = Translation<T, U2>;
@@ -21,12 +22,20 @@ impl<T> Cuboid<T> {
     }
 }
 
+#[derive(Copy, Clone, Debug)]
 pub struct AABB<T: na::base::Scalar> {
     pub rect: Cuboid<T>,
     pub translation: Translation2<T>
 }
 
 impl<T: na::base::Scalar + Num> AABB<T> {
+    pub fn new(x: T, y: T, width: T, height: T) -> AABB<T> {
+        AABB {
+            rect: Cuboid::new(width, height),
+            translation: Translation::from(na::Vector2::new(x, y)),
+        }
+    }
+
     pub fn translate(&mut self, translation: na::Vector2<T>) {
         self.translation.vector.x = self.translation.vector.x + translation.x;
         self.translation.vector.y = self.translation.vector.y + translation.y;
@@ -37,12 +46,28 @@ impl<T: na::base::Scalar + Num> AABB<T> {
     }
 }
 
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum MirrorAxis {
+    // Flips left to right, reflecting across the vertical line through the origin
+    Horizontal,
+    // Flips top to bottom, reflecting across the horizontal line through the origin
+    Vertical,
+}
+
+#[derive(Copy, Clone, Debug)]
 pub struct BoundingBox<T: na::Real> {
     pub rect: Cuboid<T>,
     pub transformation: Isometry2<T>
 }
 
 impl<T: na::Real> BoundingBox<T> {
+    pub fn new(rect: Cuboid<T>, transformation: Isometry2<T>) -> BoundingBox<T> {
+        BoundingBox {
+            rect,
+            transformation,
+        }
+    }
+
     pub fn translate(&mut self, translation: na::Vector2<T>) {
         self.transformation.append_translation_mut(&Translation::from(translation));
     }
@@ -59,4 +84,117 @@ impl<T: na::Real> BoundingBox<T> {
     pub fn get_rotation(&self) -> T {
         self.transformation.rotation.angle()
     }
-}
\ No newline at end of file
+
+    // Reflects about the origin, which for boxes on a frame is the frames pivot
+    pub fn mirror(&mut self, axis: MirrorAxis) {
+        let angle = self.get_rotation();
+        let translation = &mut self.transformation.translation.vector;
+
+        match axis {
+            MirrorAxis::Horizontal => translation.x = -translation.x,
+            MirrorAxis::Vertical => translation.y = -translation.y,
+        }
+
+        self.transformation.rotation = UnitComplex::from_angle(-angle);
+    }
+
+    // Places the box in the space `transformation` maps into
+    pub fn transformed(&self, transformation: &Isometry2<T>) -> BoundingBox<T> {
+        BoundingBox {
+            rect: self.rect,
+            transformation: transformation * self.transformation,
+        }
+    }
+
+    pub fn corners(&self) -> [Point2<T>; 4] {
+        let half: T = na::convert(0.5);
+        let (x, y) = (self.rect.width * half, self.rect.height * half);
+
+        [
+            self.transformation * Point2::new(-x, -y),
+            self.transformation * Point2::new(x, -y),
+            self.transformation * Point2::new(x, y),
+            self.transformation * Point2::new(-x, y),
+        ]
+    }
+
+    fn axes(&self) -> [Vector2<T>; 2] {
+        [
+            self.transformation.rotation * Vector2::x(),
+            self.transformation.rotation * Vector2::y(),
+        ]
+    }
+
+    fn project(points: &[Point2<T>; 4], axis: &Vector2<T>) -> (T, T) {
+        let first = points[0].coords.dot(axis);
+
+        points.iter().skip(1).fold((first, first), |(min, max), point| {
+            let projected = point.coords.dot(axis);
+            (if projected < min { projected } else { min }, if projected > max { projected } else { max })
+        })
+    }
+
+    // Separating axis test between the two oriented boxes, touching edges count as overlapping
+    pub fn intersects(&self, other: &BoundingBox<T>) -> bool {
+        let (corners, other_corners) = (self.corners(), other.corners());
+
+        for axis in self.axes().iter().chain(other.axes().iter()) {
+            let (min, max) = Self::project(&corners, axis);
+            let (other_min, other_max) = Self::project(&other_corners, axis);
+
+            if max < other_min || other_max < min {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    pub fn mirrored(&self, axis: MirrorAxis) -> BoundingBox<T> {
+        let mut mirrored = *self;
+        mirrored.mirror(axis);
+
+        mirrored
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AXES: [MirrorAxis; 2] = [MirrorAxis::Horizontal, MirrorAxis::Vertical];
+
+    fn assert_close(a: &BoundingBox<f32>, b: &BoundingBox<f32>) {
+        assert!((a.get_translation() - b.get_translation()).norm() < 1e-4, "{:?} != {:?}", a, b);
+        // Compared by sine and cosine so angles either side of pi are equal
+        let (r, s) = (a.transformation.rotation, b.transformation.rotation);
+        assert!((r.cos_angle() - s.cos_angle()).abs() < 1e-4 && (r.sin_angle() - s.sin_angle()).abs() < 1e-4, "{:?} != {:?}", a, b);
+        assert_eq!((a.rect.width, a.rect.height), (b.rect.width, b.rect.height));
+    }
+
+    #[test]
+    fn mirroring_a_box_twice_is_the_identity() {
+        for angle in [0.0, 0.4, -2.5, 3.1].iter() {
+            let shape = BoundingBox::new(Cuboid::new(12.0, 5.0), Isometry2::new(Vector2::new(7.5, -3.25), *angle));
+
+            for axis in AXES.iter() {
+                assert_close(&shape.mirrored(*axis).mirrored(*axis), &shape);
+            }
+
+            let both = shape.mirrored(MirrorAxis::Horizontal).mirrored(MirrorAxis::Vertical);
+            assert_close(&both.mirrored(MirrorAxis::Vertical).mirrored(MirrorAxis::Horizontal), &shape);
+        }
+    }
+
+    #[test]
+    fn mirroring_reflects_the_corners() {
+        let shape = BoundingBox::new(Cuboid::new(12.0, 5.0), Isometry2::new(Vector2::new(7.5, -3.25), 0.4));
+        let mirrored = shape.mirrored(MirrorAxis::Horizontal);
+
+        // Reflecting reverses the winding of the corners, so they are matched in any order
+        for corner in shape.corners().iter() {
+            let reflected = Point2::new(-corner.x, corner.y);
+            assert!(mirrored.corners().iter().any(|x| (x - reflected).norm() < 1e-4));
+        }
+    }
+}
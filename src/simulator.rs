@@ -0,0 +1,84 @@
+use na::{Isometry2, Vector2};
+use crate::{
+    category::BoxCategory,
+    frame::AnimationData,
+    shapes::{BoundingBox, MirrorAxis},
+};
+
+// Where a character stands in the sandbox, boxes are authored facing right
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Placement {
+    pub x: f32,
+    pub facing_right: bool,
+}
+
+impl Placement {
+    pub fn new(x: f32, facing_right: bool) -> Placement {
+        Placement {
+            x,
+            facing_right,
+        }
+    }
+
+    pub fn place(&self, shape: &BoundingBox<f32>) -> BoundingBox<f32> {
+        let shape = if self.facing_right { *shape } else { shape.mirrored(MirrorAxis::Horizontal) };
+
+        shape.transformed(&Isometry2::new(Vector2::new(self.x, 0.0), 0.0))
+    }
+}
+
+// Boxes of the category active on the tick, in sandbox space. Ticks past the end wrap around
+pub fn placed_boxes(animation: &AnimationData, tick: u32, placement: Placement, category: BoxCategory) -> Vec<BoundingBox<f32>> {
+    let total = animation.total_ticks();
+    if total == 0 {
+        return vec![];
+    }
+
+    let frame = match animation.frame_at_tick(tick % total) {
+        Some(frame) => frame,
+        None => return vec![],
+    };
+
+    animation.active_boxes(frame)
+        .filter(|x| x.category == category)
+        .map(|x| placement.place(&animation.world_shape(x, frame)))
+        .collect()
+}
+
+pub fn hits_on_tick(attacker: &AnimationData, attacker_placement: Placement, defender: &AnimationData, defender_placement: Placement, tick: u32) -> bool {
+    let hitboxes = placed_boxes(attacker, tick, attacker_placement, BoxCategory::Hitbox);
+    if hitboxes.is_empty() {
+        return false;
+    }
+
+    let hurtboxes = placed_boxes(defender, tick, defender_placement, BoxCategory::Hurtbox);
+
+    hitboxes.iter().any(|hitbox| hurtboxes.iter().any(|hurtbox| hitbox.intersects(hurtbox)))
+}
+
+// Both characters start on tick 0, the defenders animation loops for as long as the attackers lasts
+pub fn first_hit(attacker: &AnimationData, attacker_placement: Placement, defender: &AnimationData, defender_placement: Placement) -> Option<u32> {
+    (0..attacker.total_ticks()).find(|tick| hits_on_tick(attacker, attacker_placement, defender, defender_placement, *tick))
+}
+
+// The attacker stands at 0 facing the defender, returns the furthest distance that still hits
+pub fn max_range(attacker: &AnimationData, defender: &AnimationData, step: f32, max_distance: f32) -> Option<f32> {
+    if step <= 0.0 {
+        return None;
+    }
+
+    let attacker_placement = Placement::new(0.0, true);
+    let steps = (max_distance / step).floor() as u32;
+    let mut furthest = None;
+
+    for i in 0..=steps {
+        let distance = i as f32 * step;
+        let defender_placement = Placement::new(distance, false);
+
+        if first_hit(attacker, attacker_placement, defender, defender_placement).is_some() {
+            furthest = Some(distance);
+        }
+    }
+
+    furthest
+}
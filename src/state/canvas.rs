@@ -0,0 +1,290 @@
+use glium::Surface;
+use super::{onion_skin::OnionSkin, sandbox::Sandbox};
+use glm::{Vec2, vec2};
+use crate::{
+    category::{Categories, CATEGORY_COUNT},
+    data::RecordId,
+    frame::AnimationData,
+    input::mouse::Mouse,
+    project::Project,
+    render::{
+        CanvasRenderer,
+        camera::{Camera, View},
+        render_windows::RenderWindow,
+        image_render::{Textures, SamplerType, ImageRegion},
+    },
+};
+
+pub struct Canvas {
+    pub window: RenderWindow,
+    pub camera: Camera,
+    pub show_grid: bool,
+    pub onion_skin: OnionSkin,
+    // Indexed by `BoxCategory::index`
+    pub visible_categories: [bool; CATEGORY_COUNT],
+
+    pub background: (f32, f32, f32, f32),
+    pub grid_colour: [f32; 4],
+    pub pivot_colour: [f32; 4],
+    pub show_pivot: bool,
+    pub anchor_colour: [f32; 4],
+    pub show_anchors: bool,
+    // Records of boxes drawn with an extra outline, such as the matches of a search
+    pub highlighted_records: Vec<(String, RecordId)>,
+    pub highlight_colour: [f32; 4],
+
+    panning: bool,
+}
+
+impl Canvas {
+    pub fn new(window: RenderWindow) -> Canvas {
+        Canvas {
+            window,
+            camera: Camera::new(),
+            show_grid: false,
+            onion_skin: OnionSkin::new(),
+            visible_categories: [true; CATEGORY_COUNT],
+
+            background: (0.2, 0.2, 0.2, 1.0),
+            grid_colour: [0.0, 0.0, 0.0, 0.25],
+            pivot_colour: [0.0, 0.6, 1.0, 1.0],
+            show_pivot: true,
+            anchor_colour: [1.0, 0.8, 0.0, 1.0],
+            show_anchors: true,
+            highlighted_records: vec![],
+            highlight_colour: [1.0, 1.0, 1.0, 1.0],
+
+            panning: false,
+        }
+    }
+
+    pub fn hovered(&self, mouse: &Mouse, dims: (f32, f32)) -> bool {
+        self.window.contains(mouse.screen_space_coords(dims))
+    }
+
+    pub fn mouse_screen_position(&self, mouse: &Mouse, dims: (f32, f32)) -> (f32, f32) {
+        self.window.to_local(mouse.screen_space_coords(dims))
+    }
+
+    // All tools should go through this so they agree with what is drawn
+    pub fn mouse_sprite_position(&self, mouse: &Mouse, dims: (f32, f32)) -> Vec2 {
+        self.camera.screen_to_sprite(self.mouse_screen_position(mouse, dims))
+    }
+
+    // Centres the camera on a point in sprite space
+    pub fn focus(&mut self, x: f32, y: f32) {
+        self.camera.look_at(vec2(x, y));
+    }
+
+    // `mouse_captured` should be true when the ui is using the mouse
+    pub fn update(&mut self, mouse: &Mouse, dims: (f32, f32), mouse_captured: bool) {
+        self.camera.set_viewport(self.window.pixel_size(dims));
+        let hovered = !mouse_captured && self.hovered(mouse, dims);
+
+        if mouse.middle.pressed() && hovered {
+            self.panning = true;
+        }
+
+        if !mouse.middle.down() {
+            self.panning = false;
+        }
+
+        if self.panning {
+            self.camera.pan(mouse.get_movement());
+        }
+
+        if let (true, Some((_, y))) = (hovered, mouse.wheel.delta) {
+            if y != 0.0 {
+                let position = self.mouse_screen_position(mouse, dims);
+                self.camera.zoom_towards(y.signum() as i32, position);
+            }
+        }
+    }
+
+    pub fn view(&self, dims: (f32, f32)) -> View {
+        self.camera.view(self.window.to_rect(dims))
+    }
+
+    fn render_frame<S: Surface + ?Sized>(
+        &self,
+        target: &mut S,
+        view: &View,
+        renderer: &CanvasRenderer,
+        textures: &Textures,
+        animation: &AnimationData,
+        frame: usize,
+        tint: [f32; 4],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let current = match animation.frame(frame) {
+            Some(current) => current,
+            None => return Ok(()),
+        };
+        let sheet = animation.sprite_sheet.as_ref().and_then(|name| textures.get_sampler(name, SamplerType::Nearest));
+        let sampler = match sheet {
+            Some(sampler) => sampler,
+            None => return Ok(()),
+        };
+
+        let rect = current.rect();
+        let region = ImageRegion {
+            left: rect.translation.vector.x as f32,
+            top: rect.translation.vector.y as f32,
+            width: rect.rect.width as f32,
+            height: rect.rect.height as f32,
+        };
+        let offset = current.rect_offset();
+        let position = [offset[0] + region.width * 0.5, offset[1] + region.height * 0.5];
+
+        renderer.images.render_region(target, view, sampler, region, position, current.flipped(), tint)
+    }
+
+    fn render_boxes<S: Surface + ?Sized>(
+        &self,
+        target: &mut S,
+        view: &View,
+        renderer: &CanvasRenderer,
+        categories: &Categories,
+        animation: &AnimationData,
+        frame: usize,
+        tint: Option<[f32; 4]>,
+        outline_only: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        for data_box in animation.active_boxes(frame) {
+            if !self.visible_categories[data_box.category.index()] {
+                continue;
+            }
+
+            let colour = tint.unwrap_or_else(|| categories.colour(data_box.category));
+            let outline = [colour[0], colour[1], colour[2], 1.0_f32.min(colour[3] * 3.0)];
+            // Parented boxes are drawn where their anchor puts them on this frame
+            let shape = animation.world_shape(data_box, frame);
+            let box_dims = [shape.rect.width, shape.rect.height];
+            let translation = shape.get_translation();
+            let position = [translation.x, translation.y];
+            let angle = shape.get_rotation();
+
+            if !outline_only {
+                renderer.boxes.render_box(target, view, box_dims, position, angle, colour)?;
+            }
+            renderer.boxes.render_outline(target, view, box_dims, position, angle, outline)?;
+
+            if tint.is_none() && self.highlighted_records.contains(&data_box.data) {
+                let margin = 4.0 / view.scale;
+                let highlight_dims = [box_dims[0] + margin, box_dims[1] + margin];
+                renderer.boxes.render_outline(target, view, highlight_dims, position, angle, self.highlight_colour)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Drawn at a constant size on screen regardless of zoom
+    fn render_pivot<S: Surface + ?Sized>(&self, target: &mut S, view: &View, renderer: &CanvasRenderer) -> Result<(), Box<dyn std::error::Error>> {
+        let length = 16.0 / view.scale;
+        let thickness = 1.0 / view.scale;
+
+        renderer.boxes.render_box(target, view, [length, thickness], [0.0, 0.0], 0.0, self.pivot_colour)?;
+        renderer.boxes.render_box(target, view, [thickness, length], [0.0, 0.0], 0.0, self.pivot_colour)
+    }
+
+    // Small box at each anchor with a line showing its rotation
+    fn render_anchors<S: Surface + ?Sized>(&self, target: &mut S, view: &View, renderer: &CanvasRenderer, animation: &AnimationData, frame: usize) -> Result<(), Box<dyn std::error::Error>> {
+        let anchors = match animation.frame(frame) {
+            Some(frame) => frame.anchors(),
+            None => return Ok(()),
+        };
+        let size = 6.0 / view.scale;
+        let length = 12.0 / view.scale;
+
+        for anchor in anchors {
+            let translation = anchor.transformation.translation.vector;
+            let angle = anchor.transformation.rotation.angle();
+            let direction = anchor.transformation.rotation * na::Vector2::new(length * 0.5, 0.0);
+
+            renderer.boxes.render_outline(target, view, [size, size], [translation.x, translation.y], angle, self.anchor_colour)?;
+            renderer.boxes.render_box(target, view, [length, 1.0 / view.scale], [translation.x + direction.x, translation.y + direction.y], angle, self.anchor_colour)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn render<S: Surface + ?Sized>(
+        &self,
+        target: &mut S,
+        renderer: &CanvasRenderer,
+        textures: &Textures,
+        categories: &Categories,
+        animation: &AnimationData,
+        frame: usize,
+        dims: (f32, f32),
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let view = self.view(dims);
+        target.clear(Some(&view.viewport), Some(self.background), false, None, None);
+
+        for (onion_frame, colour) in self.onion_skin.frames(frame, animation.frames().len()) {
+            self.render_frame(target, &view, renderer, textures, animation, onion_frame, colour)?;
+
+            if self.onion_skin.show_boxes {
+                self.render_boxes(target, &view, renderer, categories, animation, onion_frame, Some(colour), true)?;
+            }
+        }
+
+        self.render_frame(target, &view, renderer, textures, animation, frame, [1.0; 4])?;
+        self.render_boxes(target, &view, renderer, categories, animation, frame, None, false)?;
+
+        if self.show_grid {
+            renderer.grid.render(target, &view, self.grid_colour)?;
+        }
+
+        if self.show_pivot {
+            self.render_pivot(target, &view, renderer)?;
+        }
+
+        if self.show_anchors {
+            self.render_anchors(target, &view, renderer, animation, frame)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn render_sandbox<S: Surface + ?Sized>(
+        &self,
+        target: &mut S,
+        renderer: &CanvasRenderer,
+        textures: &Textures,
+        project: &Project,
+        sandbox: &Sandbox,
+        dims: (f32, f32),
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let view = self.view(dims);
+        target.clear(Some(&view.viewport), Some(self.background), false, None, None);
+
+        let sides = [
+            (sandbox.attacker, sandbox.attacker_placement()),
+            (sandbox.defender, sandbox.defender_placement()),
+        ];
+
+        for (index, placement) in sides.iter() {
+            let animation = match project.animations.get(*index) {
+                Some(animation) => animation,
+                None => continue,
+            };
+            let total = animation.total_ticks();
+            if total == 0 {
+                continue;
+            }
+
+            let frame = animation.frame_at_tick(sandbox.tick % total).unwrap_or(0);
+            let placed = view.placed(placement.x, !placement.facing_right);
+
+            self.render_frame(target, &placed, renderer, textures, animation, frame, [1.0; 4])?;
+            self.render_boxes(target, &placed, renderer, &project.categories, animation, frame, None, false)?;
+        }
+
+        if self.show_grid {
+            renderer.grid.render(target, &view, self.grid_colour)?;
+        }
+
+        Ok(())
+    }
+}
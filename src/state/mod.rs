@@ -1,9 +1,79 @@
+pub mod canvas;
+pub mod onion_skin;
+pub mod playback;
+pub mod tools;
+pub mod sandbox;
 
-use crate::input::UserInput;
+use crate::{
+    input::UserInput,
+    frame::AnimationData,
+};
+use self::{canvas::Canvas, playback::Playback, tools::Tool, sandbox::Sandbox};
 
 pub struct State {
-    input: UserInput,
+    pub canvas: Canvas,
+    pub current_animation: usize,
+    pub current_frame: usize,
+    // Box shown in the box editor, an index into the current animations boxes
+    pub selected_box: Option<usize>,
+    pub playback: Playback,
+    pub tool: Tool,
+    pub sandbox: Sandbox,
     pressed_on_ui: bool,
+    mouse_captured: bool,
+}
 
-    
-}
\ No newline at end of file
+impl State {
+    pub fn new(canvas: Canvas) -> State {
+        State {
+            canvas,
+            current_animation: 0,
+            current_frame: 0,
+            selected_box: None,
+            playback: Playback::new(),
+            tool: Tool::None,
+            sandbox: Sandbox::new(),
+            pressed_on_ui: false,
+            mouse_captured: false,
+        }
+    }
+
+    // A press that starts on the ui keeps the mouse until every button is released
+    pub fn update(&mut self, input: &UserInput, dims: (f32, f32), ui_wants_mouse: bool) {
+        let mouse = &input.mouse;
+        let any_pressed = mouse.button_iter().any(|(_, button)| button.pressed());
+        let any_down = mouse.button_iter().any(|(_, button)| button.down());
+
+        if any_pressed && ui_wants_mouse {
+            self.pressed_on_ui = true;
+        }
+        else if !any_down {
+            self.pressed_on_ui = false;
+        }
+
+        self.mouse_captured = ui_wants_mouse || self.pressed_on_ui;
+        self.canvas.update(mouse, dims, self.mouse_captured);
+    }
+
+    pub fn update_tools(&mut self, input: &UserInput, dims: (f32, f32), animation: &mut AnimationData) {
+        let mouse = &input.mouse;
+        if self.mouse_captured || !self.canvas.hovered(mouse, dims) {
+            return;
+        }
+
+        match self.tool {
+            Tool::Pivot => tools::pivot_tool(&mut self.canvas, mouse, dims, animation, self.current_frame),
+            Tool::None => {}
+        }
+    }
+
+    pub fn select_frame(&mut self, animation: &AnimationData, frame: usize) {
+        self.playback.seek_frame(animation, frame);
+        self.current_frame = self.playback.current_frame(animation);
+    }
+
+    pub fn update_playback(&mut self, animation: &AnimationData, dt: f32) {
+        self.playback.update(animation, dt);
+        self.current_frame = self.playback.current_frame(animation);
+    }
+}
@@ -0,0 +1,70 @@
+#[derive(Copy, Clone, Debug)]
+pub struct OnionDirection {
+    pub count: usize,
+    pub opacity: f32,
+    pub tint: [f32; 3],
+}
+
+impl OnionDirection {
+    pub fn new(count: usize, opacity: f32, tint: [f32; 3]) -> OnionDirection {
+        OnionDirection {
+            count,
+            opacity,
+            tint,
+        }
+    }
+
+    // The nearest frame uses the full opacity, fading out linearly with distance
+    pub fn colour(&self, distance: usize) -> [f32; 4] {
+        let fade = 1.0 - (distance - 1) as f32 / self.count as f32;
+
+        [self.tint[0], self.tint[1], self.tint[2], self.opacity * fade]
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct OnionSkin {
+    pub enabled: bool,
+    pub before: OnionDirection,
+    pub after: OnionDirection,
+    pub show_boxes: bool,
+}
+
+impl OnionSkin {
+    pub fn new() -> OnionSkin {
+        OnionSkin {
+            enabled: false,
+            before: OnionDirection::new(1, 0.4, [1.0, 0.3, 0.3]),
+            after: OnionDirection::new(1, 0.4, [0.3, 1.0, 0.3]),
+            show_boxes: false,
+        }
+    }
+
+    // Frames to draw under `current` along with their colour, furthest first
+    pub fn frames(&self, current: usize, frame_count: usize) -> Vec<(usize, [f32; 4])> {
+        let mut frames = vec![];
+        if !self.enabled {
+            return frames;
+        }
+
+        for distance in (1..=self.before.count).rev() {
+            if distance <= current {
+                frames.push((current - distance, self.before.colour(distance)));
+            }
+        }
+
+        for distance in (1..=self.after.count).rev() {
+            if current + distance < frame_count {
+                frames.push((current + distance, self.after.colour(distance)));
+            }
+        }
+
+        frames
+    }
+}
+
+impl Default for OnionSkin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
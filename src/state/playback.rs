@@ -0,0 +1,121 @@
+use crate::frame::AnimationData;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PlaybackMode {
+    Loop,
+    PingPong,
+    Once,
+}
+
+pub struct Playback {
+    pub playing: bool,
+    pub mode: PlaybackMode,
+    pub speed: f32,
+
+    tick: u32,
+    // Fraction of a tick that has passed since the last one
+    accumulator: f32,
+    reversed: bool,
+}
+
+impl Playback {
+    pub fn new() -> Playback {
+        Playback {
+            playing: false,
+            mode: PlaybackMode::Loop,
+            speed: 1.0,
+
+            tick: 0,
+            accumulator: 0.0,
+            reversed: false,
+        }
+    }
+
+    pub fn tick(&self) -> u32 {
+        self.tick
+    }
+
+    pub fn toggle(&mut self) {
+        self.playing = !self.playing;
+        self.accumulator = 0.0;
+    }
+
+    pub fn current_frame(&self, animation: &AnimationData) -> usize {
+        animation.frame_at_tick(self.tick).unwrap_or(0)
+    }
+
+    pub fn seek_tick(&mut self, animation: &AnimationData, tick: u32) {
+        let total = animation.total_ticks();
+        self.tick = if total == 0 { 0 } else { tick.min(total - 1) };
+        self.accumulator = 0.0;
+    }
+
+    pub fn seek_frame(&mut self, animation: &AnimationData, frame: usize) {
+        let tick = animation.frame_start_tick(frame);
+        self.seek_tick(animation, tick);
+    }
+
+    // Moves a single tick, applying the playback mode at either end of the animation
+    pub fn step_tick(&mut self, animation: &AnimationData, forward: bool) {
+        let total = animation.total_ticks();
+        if total == 0 {
+            self.tick = 0;
+            return;
+        }
+
+        let last = total - 1;
+        let forward = forward != self.reversed;
+
+        match (forward, self.mode) {
+            (true, _) if self.tick < last => self.tick += 1,
+            (false, _) if self.tick > 0 => self.tick -= 1,
+            (true, PlaybackMode::Loop) => self.tick = 0,
+            (false, PlaybackMode::Loop) => self.tick = last,
+            (_, PlaybackMode::PingPong) => {
+                self.reversed = !self.reversed;
+                if last > 0 {
+                    self.tick = if forward { last - 1 } else { 1 };
+                }
+            }
+            (_, PlaybackMode::Once) => self.playing = false,
+        }
+    }
+
+    pub fn step_frame(&mut self, animation: &AnimationData, forward: bool) {
+        let frame_count = animation.frames().len();
+        if frame_count == 0 {
+            return;
+        }
+
+        let current = self.current_frame(animation);
+        let frame = match (forward, self.mode) {
+            (true, _) if current + 1 < frame_count => current + 1,
+            (false, _) if current > 0 => current - 1,
+            (true, PlaybackMode::Once) | (false, PlaybackMode::Once) => current,
+            (true, _) => 0,
+            (false, _) => frame_count - 1,
+        };
+
+        self.seek_frame(animation, frame);
+    }
+
+    // `dt` is in seconds as given to the `support::run` closure
+    pub fn update(&mut self, animation: &AnimationData, dt: f32) {
+        if !self.playing {
+            return;
+        }
+
+        self.accumulator += dt * self.speed * animation.fps() as f32;
+
+        while self.accumulator >= 1.0 && self.playing {
+            self.accumulator -= 1.0;
+            self.step_tick(animation, true);
+        }
+    }
+}
+
+impl Default for Playback {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,81 @@
+use crate::{
+    project::Project,
+    simulator::{self, Placement},
+};
+
+// Places two animations of the project next to each other to check their interactions
+pub struct Sandbox {
+    pub enabled: bool,
+    pub attacker: usize,
+    pub defender: usize,
+    pub distance: f32,
+    pub attacker_facing_right: bool,
+    pub defender_facing_right: bool,
+    pub tick: u32,
+
+    pub first_hit: Option<u32>,
+    pub max_range: Option<f32>,
+}
+
+impl Sandbox {
+    pub fn new() -> Sandbox {
+        Sandbox {
+            enabled: false,
+            attacker: 0,
+            defender: 0,
+            distance: 64.0,
+            attacker_facing_right: true,
+            defender_facing_right: false,
+            tick: 0,
+
+            first_hit: None,
+            max_range: None,
+        }
+    }
+
+    pub fn attacker_placement(&self) -> Placement {
+        Placement::new(0.0, self.attacker_facing_right)
+    }
+
+    pub fn defender_placement(&self) -> Placement {
+        Placement::new(self.distance, self.defender_facing_right)
+    }
+
+    pub fn step(&mut self, project: &Project, forward: bool) {
+        let total = project.animations.get(self.attacker).map(|x| x.total_ticks()).unwrap_or(0);
+        if total == 0 {
+            self.tick = 0;
+            return;
+        }
+
+        self.tick = match forward {
+            true => (self.tick + 1) % total,
+            false => (self.tick + total - 1) % total,
+        };
+    }
+
+    pub fn hitting(&self, project: &Project) -> bool {
+        match (project.animations.get(self.attacker), project.animations.get(self.defender)) {
+            (Some(attacker), Some(defender)) => {
+                simulator::hits_on_tick(attacker, self.attacker_placement(), defender, self.defender_placement(), self.tick)
+            }
+            _ => false,
+        }
+    }
+
+    pub fn simulate(&mut self, project: &Project, range_step: f32, max_distance: f32) {
+        let (attacker, defender) = match (project.animations.get(self.attacker), project.animations.get(self.defender)) {
+            (Some(attacker), Some(defender)) => (attacker, defender),
+            _ => return,
+        };
+
+        self.first_hit = simulator::first_hit(attacker, self.attacker_placement(), defender, self.defender_placement());
+        self.max_range = simulator::max_range(attacker, defender, range_step, max_distance);
+    }
+}
+
+impl Default for Sandbox {
+    fn default() -> Self {
+        Self::new()
+    }
+}
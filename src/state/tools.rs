@@ -0,0 +1,38 @@
+use glm::vec2;
+use crate::{
+    frame::AnimationData,
+    input::mouse::Mouse,
+};
+use super::canvas::Canvas;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Tool {
+    None,
+    Pivot,
+}
+
+// Moves the pivot of the frame to the pixel under the mouse while the left button is held.
+// The camera is moved with it so the sprite stays still on screen
+pub fn pivot_tool(canvas: &mut Canvas, mouse: &Mouse, dims: (f32, f32), animation: &mut AnimationData, frame: usize) {
+    if !mouse.left.down() {
+        return;
+    }
+
+    let frame = match animation.frame_mut(frame) {
+        Some(frame) => frame,
+        None => return,
+    };
+
+    let position = canvas.mouse_sprite_position(mouse, dims);
+    let centre = frame.centre();
+    let x = (centre[0] as i64 + position.x.floor() as i64).max(0);
+    let y = (centre[1] as i64 + position.y.floor() as i64).max(0);
+
+    let delta = vec2((x - centre[0] as i64) as f32, (y - centre[1] as i64) as f32);
+    if delta.x == 0.0 && delta.y == 0.0 {
+        return;
+    }
+
+    frame.set_centre([x as u32, y as u32]);
+    canvas.camera.centre -= canvas.camera.mirror_vector(delta);
+}
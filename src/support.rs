@@ -87,7 +87,7 @@ fn set_special_keys(imgui: &mut ImGui) {
 
 pub fn run<F>(window: &mut Window, events_loop: &mut EventsLoop, mut func: F)
 where
-    F: FnMut(&mut Frame, &Ui, &Vec<Event>, f32, bool) -> bool,
+    F: FnMut(&mut Frame, &Ui, &UserInput, f32, bool) -> bool,
 {
     let hdp = window.display().gl_window().get_hidpi_factor();
     let (mut imgui, mut renderer) = build_imgui(window, hdp as f32);
@@ -96,7 +96,6 @@ where
     let mut user_input = UserInput::new();
 
     let mut last_frame = Instant::now();
-    let mut events = vec![];
 
     loop {
         let delta_s = get_time_and_reset(&mut last_frame);
@@ -115,7 +114,6 @@ where
         };
 
         user_input.frame_reset();
-        events.clear();
         events_loop.poll_events(|event| {
             window.handle_event(&event);
             handle_special_keys(&mut imgui, &event);
@@ -131,7 +129,7 @@ where
         let ui = imgui.frame(frame_size, delta_s);
 
         let mut target = window.display().draw();
-        if !func(&mut target, &ui, &events, delta_s, no_render) {
+        if !func(&mut target, &ui, &user_input, delta_s, no_render) {
             break;
         }
 
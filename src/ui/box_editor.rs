@@ -0,0 +1,218 @@
+use imgui::*;
+use na::{Isometry2, Vector2};
+use crate::{
+    category::BoxCategory,
+    frame::AnimationData,
+    shapes::{BoundingBox, Cuboid},
+    state::State,
+    ui::data_editor::DataEditor,
+};
+
+pub struct BoxEditor {
+    anchor_name: ImString,
+    box_type: i32,
+    box_category: i32,
+    type_names: Vec<ImString>,
+    category_names: Vec<ImString>,
+    data_editor: DataEditor,
+    error_message: Option<String>,
+}
+
+impl BoxEditor {
+    pub fn new() -> Self {
+        Self {
+            anchor_name: ImString::with_capacity(20),
+            box_type: 0,
+            box_category: 0,
+            type_names: vec![],
+            category_names: BoxCategory::ALL.iter().map(|x| ImString::new(x.name())).collect(),
+            data_editor: DataEditor::new(),
+            error_message: None,
+        }
+    }
+
+    pub fn anchor_list(&mut self, state: &mut State, animation: &mut AnimationData, ui: &Ui) {
+        let frame = match animation.frame_mut(state.current_frame) {
+            Some(frame) => frame,
+            None => return,
+        };
+
+        ui.input_text(im_str!("Anchor Name"), &mut self.anchor_name).build();
+        if ui.small_button(im_str!("Add Anchor")) && !self.anchor_name.to_str().is_empty() {
+            frame.set_anchor(self.anchor_name.to_str(), Isometry2::identity());
+            self.anchor_name.clear();
+        }
+
+        let mut to_remove = None;
+        let names = frame.anchors().iter().map(|x| x.name.clone()).collect::<Vec<_>>();
+
+        for (i, name) in names.iter().enumerate() {
+            let anchor = frame.anchor_mut(name).unwrap();
+            let translation = anchor.transformation.translation.vector;
+            let mut x = translation.x;
+            let mut y = translation.y;
+            let mut angle = anchor.transformation.rotation.angle().to_degrees();
+
+            ui.text(name);
+            ui.same_line(0.0);
+            if ui.small_button(im_str!("Remove##Anchor{}", i)) {
+                to_remove = Some(name.clone());
+            }
+
+            let mut changed = ui.input_float(im_str!("X##Anchor{}", i), &mut x).build();
+            changed |= ui.input_float(im_str!("Y##Anchor{}", i), &mut y).build();
+            changed |= ui.input_float(im_str!("Angle##Anchor{}", i), &mut angle).build();
+
+            if changed {
+                anchor.transformation = Isometry2::new(Vector2::new(x, y), angle.to_radians());
+            }
+        }
+
+        if let Some(name) = to_remove {
+            frame.remove_anchor(&name);
+        }
+    }
+
+    pub fn box_adder(&mut self, state: &mut State, animation: &mut AnimationData, ui: &Ui) {
+        let mut names = animation.data().data.keys().cloned().collect::<Vec<_>>();
+        names.sort();
+        self.type_names = names.into_iter().map(ImString::new).collect();
+
+        let types = self.type_names.iter().map(|x| x.as_ref()).collect::<Vec<&ImStr>>();
+        let categories = self.category_names.iter().map(|x| x.as_ref()).collect::<Vec<&ImStr>>();
+        ui.combo(im_str!("Box Type"), &mut self.box_type, &types[..], 5);
+        ui.combo(im_str!("Category"), &mut self.box_category, &categories[..], 6);
+
+        if !ui.small_button(im_str!("Add Box")) {
+            return;
+        }
+
+        let dtype = match self.type_names.get(self.box_type.max(0) as usize) {
+            Some(dtype) => dtype.to_str().to_string(),
+            None => {
+                self.error_message = Some(String::from("Select a type for the box"));
+                return;
+            }
+        };
+        let category = BoxCategory::ALL[(self.box_category.max(0) as usize).min(BoxCategory::ALL.len() - 1)];
+        let shape = BoundingBox::new(Cuboid::new(16.0, 16.0), Isometry2::identity());
+
+        self.error_message = None;
+        state.selected_box = animation.new_data_box(&dtype, shape, category);
+        if let Some(index) = state.selected_box {
+            animation.set_box_active(index, state.current_frame, true);
+        }
+    }
+
+    fn box_parent(&mut self, state: &mut State, animation: &mut AnimationData, index: usize, ui: &Ui) {
+        let frame = state.current_frame;
+        let anchors = match animation.frame(frame) {
+            Some(value) => value.anchors().iter().map(|x| ImString::new(x.name.clone())).collect::<Vec<_>>(),
+            None => return,
+        };
+        let parent = animation.data_boxes()[index].parent.clone();
+
+        let mut items = vec![im_str!("None")];
+        items.extend(anchors.iter().map(|x| x.as_ref()));
+
+        let mut selected = parent.as_ref()
+            .and_then(|parent| anchors.iter().position(|x| x.to_str() == parent))
+            .map(|x| x as i32 + 1)
+            .unwrap_or(0);
+
+        if ui.combo(im_str!("Parent##Box{}", index), &mut selected, &items[..], 5) {
+            match selected {
+                0 => animation.unparent_box(index, frame),
+                i => animation.reparent_box(index, frame, anchors[i as usize - 1].to_str()),
+            }
+        }
+    }
+
+    pub fn box_list(&mut self, state: &mut State, animation: &mut AnimationData, ui: &Ui) {
+        let frame = state.current_frame;
+        let mut to_remove = None;
+
+        for index in 0..animation.data_boxes().len() {
+            let text = {
+                let data_box = &animation.data_boxes()[index];
+                format!("{}: {} {} ({})", index, data_box.category.name(), data_box.data.0, data_box.data.1)
+            };
+            ui.separator();
+            ui.text(&text);
+
+            ui.same_line(0.0);
+            if ui.small_button(im_str!("Edit##Box{}", index)) {
+                state.selected_box = Some(index);
+            }
+
+            ui.same_line(0.0);
+            if ui.small_button(im_str!("Remove##Box{}", index)) {
+                to_remove = Some(index);
+            }
+
+            let mut active = animation.is_box_active(index, frame);
+            if ui.checkbox(im_str!("Active##Box{}", index), &mut active) {
+                animation.set_box_active(index, frame, active);
+            }
+
+            self.box_parent(state, animation, index, ui);
+        }
+
+        if let Some(index) = to_remove {
+            animation.remove_data_box(index);
+            state.selected_box = None;
+        }
+    }
+
+    pub fn box_properties(&mut self, state: &mut State, animation: &mut AnimationData, ui: &Ui) {
+        let index = match state.selected_box {
+            Some(index) if index < animation.data_boxes().len() => index,
+            _ => return,
+        };
+
+        let (dtype, key) = animation.data_boxes()[index].data.clone();
+        let shape = animation.data_boxes()[index].shape;
+        let translation = shape.get_translation();
+        let mut x = translation.x;
+        let mut y = translation.y;
+        let mut width = shape.rect.width;
+        let mut height = shape.rect.height;
+        let mut angle = shape.get_rotation().to_degrees();
+
+        ui.separator();
+        let mut changed = ui.input_float(im_str!("X"), &mut x).build();
+        changed |= ui.input_float(im_str!("Y"), &mut y).build();
+        changed |= ui.input_float(im_str!("Width"), &mut width).build();
+        changed |= ui.input_float(im_str!("Height"), &mut height).build();
+        changed |= ui.input_float(im_str!("Angle"), &mut angle).build();
+
+        if changed {
+            let rect = Cuboid::new(width.max(0.0), height.max(0.0));
+            animation.data_boxes_mut()[index].shape = BoundingBox::new(rect, Isometry2::new(Vector2::new(x, y), angle.to_radians()));
+        }
+
+        if let Some(storage) = animation.data_mut().get_storage_mut(&dtype) {
+            self.data_editor.create_ui(&key, storage, ui);
+        }
+    }
+
+    pub fn create_ui(&mut self, state: &mut State, animation: &mut AnimationData, ui: &Ui) {
+        self.anchor_list(state, animation, ui);
+
+        ui.separator();
+        self.box_adder(state, animation, ui);
+        self.box_list(state, animation, ui);
+        self.box_properties(state, animation, ui);
+
+        if let Some(message) = &self.error_message {
+            ui.separator();
+            ui.text(message);
+        }
+    }
+}
+
+impl Default for BoxEditor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,183 @@
+use imgui::*;
+use glium::backend::glutin::Display;
+use crate::{
+    frame::{AnimationData, Frame, PivotAnchor},
+    render::{camera::ZOOM_LEVELS, image_render::Textures},
+    shapes::AABB,
+    state::{State, onion_skin::OnionDirection, tools::Tool},
+};
+
+pub struct CanvasEditor {
+    sheet_path: ImString,
+    error_message: Option<String>,
+
+    pivot_range: (i32, i32),
+    pivot_anchor: i32,
+    anchor_names: [ImString; 3],
+}
+
+impl CanvasEditor {
+    pub fn new() -> Self {
+        Self {
+            sheet_path: ImString::with_capacity(128),
+            error_message: None,
+
+            pivot_range: (0, 0),
+            pivot_anchor: 2,
+            anchor_names: [
+                ImString::new("Top Left"),
+                ImString::new("Centre"),
+                ImString::new("Bottom Centre"),
+            ],
+        }
+    }
+
+    pub fn sheet_loader(&mut self, state: &mut State, animation: &mut AnimationData, textures: &mut Textures, display: &Display, ui: &Ui) {
+        ui.input_text(im_str!("Sprite Sheet"), &mut self.sheet_path).build();
+        if !ui.small_button(im_str!("Load")) {
+            return;
+        }
+
+        let path = self.sheet_path.to_str().to_string();
+        if let Err(error) = textures.load_new(display, &path) {
+            self.error_message = Some(format!("{}", error));
+            return;
+        }
+
+        self.error_message = None;
+        let (width, height) = match textures.get(&path) {
+            Some(texture) => texture.dimensions(),
+            None => return,
+        };
+
+        // A sheet without any frames yet is treated as a single frame
+        if animation.frames().is_empty() {
+            animation.push_frame(Frame::new(AABB::new(0, 0, width, height)));
+        }
+
+        animation.sprite_sheet = Some(path);
+        state.select_frame(animation, 0);
+        state.canvas.focus(0.0, 0.0);
+    }
+
+    pub fn camera_controls(&mut self, state: &mut State, ui: &Ui) {
+        let camera = &mut state.canvas.camera;
+        let zoom = ImString::new(format!("Zoom: {}x", ZOOM_LEVELS[camera.zoom_level()]));
+        ui.text(&zoom);
+
+        ui.same_line(0.0);
+        if ui.small_button(im_str!("-")) {
+            let level = camera.zoom_level();
+            camera.set_zoom_level(level.saturating_sub(1));
+        }
+
+        ui.same_line(0.0);
+        if ui.small_button(im_str!("+")) {
+            let level = camera.zoom_level();
+            camera.set_zoom_level(level + 1);
+        }
+
+        ui.checkbox(im_str!("Pixel Grid"), &mut state.canvas.show_grid);
+
+        // Keep the same part of the sprite in view when flipping
+        let centre = camera.mirror_vector(camera.centre);
+        let mut changed = ui.checkbox(im_str!("Mirror Horizontal"), &mut camera.mirror[0]);
+        changed |= ui.checkbox(im_str!("Mirror Vertical"), &mut camera.mirror[1]);
+
+        if changed {
+            camera.look_at(centre);
+        }
+    }
+
+    fn onion_direction(direction: &mut OnionDirection, name: &str, ui: &Ui) {
+        let mut count = direction.count as i32;
+        if ui.slider_int(im_str!("{} Frames", name), &mut count, 0, 8).build() {
+            direction.count = count.max(0) as usize;
+        }
+
+        ui.slider_float(im_str!("{} Opacity", name), &mut direction.opacity, 0.0, 1.0).build();
+    }
+
+    pub fn onion_skin_controls(&mut self, state: &mut State, ui: &Ui) {
+        let onion_skin = &mut state.canvas.onion_skin;
+
+        ui.checkbox(im_str!("Onion Skin"), &mut onion_skin.enabled);
+        if !onion_skin.enabled {
+            return;
+        }
+
+        Self::onion_direction(&mut onion_skin.before, "Before", ui);
+        Self::onion_direction(&mut onion_skin.after, "After", ui);
+        ui.checkbox(im_str!("Onion Boxes"), &mut onion_skin.show_boxes);
+    }
+
+    pub fn pivot_controls(&mut self, state: &mut State, animation: &mut AnimationData, ui: &Ui) {
+        let mut pivot_tool = state.tool == Tool::Pivot;
+        if ui.checkbox(im_str!("Pivot Tool"), &mut pivot_tool) {
+            state.tool = if pivot_tool { Tool::Pivot } else { Tool::None };
+        }
+        ui.checkbox(im_str!("Show Pivot"), &mut state.canvas.show_pivot);
+
+        let current = state.current_frame;
+        let centre = match animation.frame_mut(current) {
+            Some(frame) => {
+                let mut x = frame.centre()[0] as i32;
+                let mut y = frame.centre()[1] as i32;
+                let changed_x = ui.input_int(im_str!("Pivot X"), &mut x).build();
+                let changed_y = ui.input_int(im_str!("Pivot Y"), &mut y).build();
+
+                if changed_x || changed_y {
+                    frame.set_centre([x.max(0) as u32, y.max(0) as u32]);
+                }
+
+                frame.centre()
+            }
+            None => return,
+        };
+
+        let max = animation.frames().len() as i32 - 1;
+        ui.input_int(im_str!("From"), &mut self.pivot_range.0).build();
+        ui.input_int(im_str!("To"), &mut self.pivot_range.1).build();
+        self.pivot_range.0 = self.pivot_range.0.max(0).min(max);
+        self.pivot_range.1 = self.pivot_range.1.max(0).min(max);
+
+        if ui.small_button(im_str!("Apply Pivot To Range")) {
+            animation.set_pivot_range(self.pivot_range.0 as usize, self.pivot_range.1 as usize, centre);
+        }
+
+        let items = [
+            self.anchor_names[0].as_ref(),
+            self.anchor_names[1].as_ref(),
+            self.anchor_names[2].as_ref(),
+        ];
+        ui.combo(im_str!("Anchor"), &mut self.pivot_anchor, &items[..], 3);
+
+        if ui.small_button(im_str!("Align Pivots To Frame")) {
+            let anchor = match self.pivot_anchor {
+                0 => PivotAnchor::TopLeft,
+                1 => PivotAnchor::Centre,
+                _ => PivotAnchor::BottomCentre,
+            };
+
+            animation.align_pivots(current, anchor);
+        }
+    }
+
+    pub fn create_ui(&mut self, state: &mut State, animation: &mut AnimationData, textures: &mut Textures, display: &Display, ui: &Ui) {
+        self.sheet_loader(state, animation, textures, display, ui);
+        self.camera_controls(state, ui);
+        self.onion_skin_controls(state, ui);
+        self.pivot_controls(state, animation, ui);
+
+        if let Some(message) = &self.error_message {
+            ui.separator();
+            ui.text(message);
+        }
+    }
+}
+
+impl Default for CanvasEditor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
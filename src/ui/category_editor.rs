@@ -0,0 +1,62 @@
+use imgui::*;
+use crate::{
+    category::{Categories, BoxCategory, LAYER_COUNT},
+    state::State,
+};
+
+pub struct CategoryEditor {
+    names: Vec<ImString>,
+}
+
+impl CategoryEditor {
+    pub fn new() -> Self {
+        Self {
+            names: BoxCategory::ALL.iter().map(|x| ImString::new(x.name())).collect(),
+        }
+    }
+
+    pub fn category_settings(&mut self, state: &mut State, categories: &mut Categories, ui: &Ui) {
+        for category in BoxCategory::ALL.iter() {
+            let i = category.index();
+            let settings = categories.get_mut(*category);
+
+            ui.checkbox(&self.names[i], &mut state.canvas.visible_categories[i]);
+            ui.color_edit(im_str!("Colour##{}", i), &mut settings.colour).build();
+
+            let mut layer = settings.layer as i32;
+            if ui.input_int(im_str!("Layer##{}", i), &mut layer).build() {
+                settings.layer = layer.max(0).min(LAYER_COUNT as i32 - 1) as u8;
+            }
+        }
+    }
+
+    // Lower triangle of checkboxes since the matrix is symmetric
+    pub fn layer_matrix(&mut self, categories: &mut Categories, ui: &Ui) {
+        ui.separator();
+        ui.text("Interactions");
+
+        for a in BoxCategory::ALL.iter() {
+            ui.text(&self.names[a.index()]);
+
+            for b in BoxCategory::ALL.iter().take(a.index() + 1) {
+                ui.same_line(120.0 + b.index() as f32 * 25.0);
+
+                let mut interacts = categories.interacts(*a, *b);
+                if ui.checkbox(im_str!("##{}_{}", a.index(), b.index()), &mut interacts) {
+                    categories.set_interacts(*a, *b, interacts);
+                }
+            }
+        }
+    }
+
+    pub fn create_ui(&mut self, state: &mut State, categories: &mut Categories, ui: &Ui) {
+        self.category_settings(state, categories, ui);
+        self.layer_matrix(categories, ui);
+    }
+}
+
+impl Default for CategoryEditor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -15,33 +15,49 @@ impl DataEditor {
     }
 
     pub fn create_ui<K: Eq + std::hash::Hash>(&mut self, key: &K, data: &mut DataTypeStorage<K>, ui: &Ui) {
-        let description = std::mem::replace(&mut data.description, vec![]);
-
-        if let Some(values) = data.get_mut(key) {
-            for (i, value) in values.iter_mut().enumerate() {
-                let buffer = {
-                    if i < self.field_buffers.len() {
-                        &mut self.field_buffers[i]
-                    }
-                    else {
-                        self.field_buffers.push(ImString::with_capacity(10));
-                        &mut self.field_buffers[i]
-                    }
-                };
-        
+        let mut changed = None;
+
+        if let (description, Some(values)) = data.get_with_desc(key) {
+            for (i, value) in values.iter().enumerate() {
+                if i >= self.field_buffers.len() {
+                    self.field_buffers.push(ImString::with_capacity(10));
+                }
+
                 let name = format!("{}: {:?}: {}", description[i].name, description[i].dtype, value);
                 ui.text(name);
+
+                // Computed fields show their expression instead of an input
+                if let Some(expression) = description[i].expression() {
+                    ui.same_line(100.0);
+                    ui.text(&format!("= {}", expression.source()));
+                    continue;
+                }
+
                 ui.same_line(100.0);
-                
                 if ui.input_text(im_str!("##Value{}", i), &mut self.field_buffers[i]).enter_returns_true(true).build() {
-                    match value.set_value(self.field_buffers[i].to_str()) {
-                        Ok(_) => self.field_buffers[i].clear(),
-                        Err(_) => {}
-                    }
+                    changed = Some(i);
+                }
+            }
+        }
+
+        if let Some(i) = changed {
+            match data.set_value(key, i, self.field_buffers[i].to_str()) {
+                Ok(_) => {
+                    self.field_buffers[i].clear();
+                    self.error_message = None;
                 }
+                Err(e) => self.error_message = Some(e.to_string()),
             }
-        }    
+        }
+
+        if let Some(message) = &self.error_message {
+            ui.text(message);
+        }
+    }
+}
 
-        std::mem::replace(&mut data.description, description);
+impl Default for DataEditor {
+    fn default() -> Self {
+        Self::new()
     }
-}
\ No newline at end of file
+}
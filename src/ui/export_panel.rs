@@ -0,0 +1,236 @@
+use imgui::*;
+use std::{
+    collections::BTreeMap,
+    path::Path,
+    time::Instant,
+};
+use crate::{
+    export::{
+        type_codegen,
+        registry::{self, ExportTarget, FormatOption, FormatOptions, OptionValue, Registry, TypesExporter},
+    },
+    project::Project,
+    watch::{self, Watcher},
+};
+
+pub struct ExportPanel {
+    directory: ImString,
+    exporter: i32,
+    // Options of each exporter by name, kept when switching between them
+    options: BTreeMap<String, FormatOptions>,
+    import_path: ImString,
+    importer: i32,
+    import_options: BTreeMap<String, FormatOptions>,
+    // Set while watch mode is on
+    watcher: Option<Watcher>,
+    message: Option<String>,
+}
+
+fn names<'a>(names: impl Iterator<Item = &'a str>) -> Vec<ImString> {
+    names.map(|x| ImString::new(x.to_string())).collect()
+}
+
+fn selected<T>(items: &[T], index: i32) -> Option<&T> {
+    items.get(index.max(0) as usize)
+}
+
+// A checkbox or text box for each option
+fn option_controls(id: &str, options: &[FormatOption], values: &mut FormatOptions, ui: &Ui) {
+    for option in options.iter() {
+        ui.text(option.description);
+        match values.get(option.name).cloned().unwrap_or_else(|| option.default.clone()) {
+            OptionValue::Flag(mut value) => {
+                if ui.checkbox(im_str!("{}##{}", option.name, id), &mut value) {
+                    values.set(option.name, OptionValue::Flag(value));
+                }
+            }
+            OptionValue::Text(value) => {
+                let mut buffer = ImString::with_capacity(value.len() + 64);
+                buffer.push_str(&value);
+
+                if ui.input_text(im_str!("{}##{}", option.name, id), &mut buffer).build() {
+                    values.set(option.name, OptionValue::Text(buffer.to_str().to_string()));
+                }
+            }
+        }
+    }
+}
+
+impl ExportPanel {
+    pub fn new() -> Self {
+        Self {
+            directory: ImString::with_capacity(128),
+            exporter: 0,
+            options: BTreeMap::new(),
+            import_path: ImString::with_capacity(128),
+            importer: 0,
+            import_options: BTreeMap::new(),
+            watcher: None,
+            message: None,
+        }
+    }
+
+    // Picks the export profile of the exporter, saved with the project
+    fn profile_ui(exporter: &str, project: &mut Project, ui: &Ui) {
+        let mut items = vec![ImString::new("Default")];
+        items.extend(names(project.export_profiles.keys().map(|x| x.as_str())));
+        let refs = items.iter().map(|x| x.as_ref()).collect::<Vec<&ImStr>>();
+
+        let mut selected = project.exporter_profiles.get(exporter)
+            .and_then(|name| project.export_profiles.keys().position(|x| x == name))
+            .map(|x| x as i32 + 1)
+            .unwrap_or(0);
+
+        if ui.combo(im_str!("Profile"), &mut selected, &refs[..], 6) {
+            match selected {
+                0 => project.exporter_profiles.remove(exporter),
+                i => project.exporter_profiles.insert(exporter.to_string(), items[i as usize].to_str().to_string()),
+            };
+        }
+    }
+
+    fn exporter_ui(&mut self, project: &mut Project, registry: &Registry, ui: &Ui) {
+        let items = names(registry.exporters().iter().map(|x| x.name()));
+        let refs = items.iter().map(|x| x.as_ref()).collect::<Vec<&ImStr>>();
+        ui.combo(im_str!("Format"), &mut self.exporter, &refs[..], 6);
+
+        let exporter = match selected(registry.exporters(), self.exporter) {
+            Some(exporter) => exporter,
+            None => return,
+        };
+
+        ui.text(exporter.description());
+        let options = exporter.options();
+        let values = self.options.entry(exporter.name().to_string()).or_insert_with(|| FormatOptions::defaults(&options));
+        option_controls("Export", &options, values, ui);
+        Self::profile_ui(exporter.name(), project, ui);
+
+        if ui.small_button(im_str!("Export")) {
+            let profile = project.exporter_profile(exporter.name());
+
+            self.message = Some(match registry::run_exporter(exporter.as_ref(), project, Path::new(self.directory.to_str()), values, &profile) {
+                Ok(written) => format!("Wrote {} files", written.len()),
+                Err(error) => format!("Export failed: {}", error),
+            });
+        }
+
+        ui.same_line(0.0);
+        if ui.small_button(im_str!("Add Target")) {
+            project.export_targets.push(ExportTarget {
+                exporter: exporter.name().to_string(),
+                directory: self.directory.to_str().to_string(),
+                options: values.clone(),
+            });
+        }
+
+        if exporter.name() == "types" {
+            ui.same_line(0.0);
+            if ui.small_button(im_str!("Check Types")) {
+                let options = TypesExporter::codegen_options(values);
+
+                self.message = Some(match type_codegen::check_project(project, Path::new(self.directory.to_str()), &options) {
+                    Ok(()) => "Generated types are up to date".to_string(),
+                    Err(error) => format!("Check failed: {}", error),
+                });
+            }
+        }
+    }
+
+    fn report(&mut self, results: Vec<Result<String, String>>) {
+        let lines = results.into_iter().map(|x| x.unwrap_or_else(|error| format!("Export failed: {}", error))).collect::<Vec<_>>();
+        self.message = Some(lines.join("\n"));
+    }
+
+    // Targets saved with the project, run together by `Export Targets` and watch mode
+    fn targets_ui(&mut self, project: &mut Project, registry: &Registry, ui: &Ui) {
+        let mut removed = None;
+        for (i, target) in project.export_targets.iter().enumerate() {
+            if ui.small_button(im_str!("x##Target{}", i)) {
+                removed = Some(i);
+            }
+
+            ui.same_line(0.0);
+            ui.text(&format!("{} to {}", target.exporter, target.directory));
+        }
+
+        if let Some(index) = removed {
+            project.export_targets.remove(index);
+        }
+
+        if ui.small_button(im_str!("Export Targets")) {
+            let results = watch::export_targets(registry, project, &project.export_targets);
+            self.report(results);
+        }
+
+        ui.same_line(0.0);
+        let mut watching = self.watcher.is_some();
+        if ui.checkbox(im_str!("Watch"), &mut watching) {
+            self.watcher = match watching {
+                true => Some(Watcher::new(vec![])),
+                false => None,
+            };
+        }
+    }
+
+    // Exports with the targets when the saved project file or a sprite sheet changes, called every
+    // frame whether the window is open or not
+    pub fn update_watch(&mut self, project: &Project, registry: &Registry, project_path: &str) {
+        let watcher = match self.watcher.as_mut() {
+            Some(watcher) => watcher,
+            None => return,
+        };
+
+        watcher.set_files(watch::watched_files(project, Path::new(project_path)));
+        if watcher.poll(Instant::now()) {
+            let results = watch::export_targets(registry, project, &project.export_targets);
+            self.report(results);
+        }
+    }
+
+    fn importer_ui(&mut self, project: &mut Project, registry: &Registry, ui: &Ui) {
+        let items = names(registry.importers().iter().map(|x| x.name()));
+        let refs = items.iter().map(|x| x.as_ref()).collect::<Vec<&ImStr>>();
+        ui.combo(im_str!("Import Format"), &mut self.importer, &refs[..], 6);
+
+        let importer = match selected(registry.importers(), self.importer) {
+            Some(importer) => importer,
+            None => return,
+        };
+
+        ui.text(importer.description());
+        ui.input_text(im_str!("File"), &mut self.import_path).build();
+
+        let options = importer.options();
+        let values = self.import_options.entry(importer.name().to_string()).or_insert_with(|| FormatOptions::defaults(&options));
+        option_controls("Import", &options, values, ui);
+
+        if ui.small_button(im_str!("Import")) {
+            self.message = Some(match importer.import(project, Path::new(self.import_path.to_str()), values) {
+                Ok(added) => format!("Added {}", added.join(", ")),
+                Err(error) => format!("Import failed: {}", error),
+            });
+        }
+    }
+
+    pub fn create_ui(&mut self, project: &mut Project, registry: &Registry, ui: &Ui) {
+        ui.input_text(im_str!("Directory"), &mut self.directory).build();
+        self.exporter_ui(project, registry, ui);
+
+        ui.separator();
+        self.targets_ui(project, registry, ui);
+
+        ui.separator();
+        self.importer_ui(project, registry, ui);
+
+        if let Some(message) = &self.message {
+            ui.separator();
+            ui.text(message);
+        }
+    }
+}
+
+impl Default for ExportPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,39 @@
+use imgui::*;
+use crate::{
+    frame::AnimationData,
+    frame_data::FrameDataSummary,
+};
+
+pub struct FrameDataPanel;
+
+impl FrameDataPanel {
+    pub fn new() -> Self {
+        FrameDataPanel
+    }
+
+    fn line<T: std::fmt::Display>(name: &str, value: Option<T>, ui: &Ui) {
+        let text = match value {
+            Some(value) => format!("{}: {}", name, value),
+            None => format!("{}: -", name),
+        };
+
+        ui.text(&text);
+    }
+
+    pub fn create_ui(&mut self, animation: &AnimationData, ui: &Ui) {
+        let summary = FrameDataSummary::summarize(animation);
+
+        Self::line("Startup", summary.startup, ui);
+        Self::line("Active", summary.active, ui);
+        Self::line("Recovery", summary.recovery, ui);
+        Self::line("Total", Some(summary.total), ui);
+        Self::line("On Hit", summary.on_hit.map(|x| format!("{:+}", x)), ui);
+        Self::line("On Block", summary.on_block.map(|x| format!("{:+}", x)), ui);
+    }
+}
+
+impl Default for FrameDataPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,5 +1,17 @@
 pub mod storage_editor;
 pub mod data_editor;
+pub mod canvas_editor;
+pub mod timeline_editor;
+pub mod category_editor;
+pub mod frame_data_panel;
+pub mod sandbox_editor;
+pub mod box_editor;
+pub mod export_panel;
+pub mod profile_editor;
+pub mod project_panel;
+pub mod problems_panel;
+pub mod search_panel;
+pub mod script_panel;
 
 use imgui::Ui;
 
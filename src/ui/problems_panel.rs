@@ -0,0 +1,121 @@
+use imgui::*;
+use crate::{
+    lint::{Linter, Location, Problem, Severity},
+    project::Project,
+    state::State,
+};
+
+pub struct ProblemsPanel {
+    linter: Linter,
+    problems: Vec<Problem>,
+    severity_names: Vec<ImString>,
+    show_rules: bool,
+}
+
+impl ProblemsPanel {
+    pub fn new() -> Self {
+        Self {
+            linter: Linter::with_builtin_rules(),
+            problems: vec![],
+            severity_names: Severity::ALL.iter().map(|x| ImString::new(x.name())).collect(),
+            show_rules: false,
+        }
+    }
+
+    pub fn linter_mut(&mut self) -> &mut Linter {
+        &mut self.linter
+    }
+
+    // Shows the animation, frame and box the problem is about
+    fn jump_to(state: &mut State, project: &Project, problem: &Problem) {
+        let animation = match project.animations.get(problem.animation) {
+            Some(animation) => animation,
+            None => return,
+        };
+
+        if state.current_animation != problem.animation {
+            state.current_animation = problem.animation;
+            state.selected_box = None;
+            state.select_frame(animation, 0);
+        }
+
+        let (data_box, frame) = match &problem.location {
+            Location::Frame(frame) => (None, Some(*frame)),
+            Location::Box(data_box) => (Some(*data_box), None),
+            Location::BoxOnFrame { data_box, frame } => (Some(*data_box), Some(*frame)),
+            Location::Record { dtype, key } => {
+                let data_box = animation.data_boxes().iter().position(|x| x.data.0 == *dtype && x.data.1 == *key);
+                (data_box, None)
+            }
+            Location::Animation | Location::Type(_) => (None, None),
+        };
+
+        // Boxes are shown on the first frame they are active on unless the problem names one
+        let frame = frame.or_else(|| {
+            data_box.and_then(|x| animation.activation_ranges(x).first().map(|range| range.start))
+        });
+
+        if let Some(frame) = frame {
+            state.select_frame(animation, frame);
+        }
+
+        if let Some(index) = data_box {
+            state.selected_box = Some(index);
+
+            let data_box = &animation.data_boxes()[index];
+            let translation = animation.world_shape(data_box, state.current_frame).get_translation();
+            state.canvas.focus(translation.x, translation.y);
+        }
+    }
+
+    fn rule_settings(&mut self, project: &mut Project, ui: &Ui) {
+        let names = self.severity_names.iter().map(|x| x.as_ref()).collect::<Vec<&ImStr>>();
+
+        for (i, rule) in self.linter.rules().iter().enumerate() {
+            let mut selected = project.lint.severity(rule) as i32;
+
+            ui.text(rule.description);
+            if ui.combo(im_str!("{}##Rule{}", rule.name, i), &mut selected, &names[..], 4) {
+                let severity = Severity::ALL[(selected.max(0) as usize).min(Severity::ALL.len() - 1)];
+                project.lint.set_severity(rule, severity);
+            }
+        }
+    }
+
+    pub fn create_ui(&mut self, state: &mut State, project: &mut Project, ui: &Ui) {
+        if ui.small_button(im_str!("Check")) {
+            self.problems = self.linter.run(project, &project.lint);
+        }
+
+        ui.same_line(0.0);
+        ui.checkbox(im_str!("Rules"), &mut self.show_rules);
+        if self.show_rules {
+            self.rule_settings(project, ui);
+        }
+
+        ui.separator();
+        if self.problems.is_empty() {
+            ui.text("No problems");
+        }
+
+        let mut jump = None;
+        for (i, problem) in self.problems.iter().enumerate() {
+            if ui.small_button(im_str!("Go##Problem{}", i)) {
+                jump = Some(i);
+            }
+
+            ui.same_line(0.0);
+            ui.text(&problem.describe(project));
+        }
+
+        if let Some(i) = jump {
+            Self::jump_to(state, project, &self.problems[i]);
+        }
+    }
+}
+
+impl Default for ProblemsPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,152 @@
+use imgui::*;
+use crate::{
+    export::profile::ExportProfile,
+    project::Project,
+};
+
+pub struct ProfileEditor {
+    name: ImString,
+    selected: Option<String>,
+    // `Type.field` and its new name
+    field: ImString,
+    new_name: ImString,
+    message: Option<String>,
+}
+
+impl ProfileEditor {
+    pub fn new() -> Self {
+        Self {
+            name: ImString::with_capacity(32),
+            selected: None,
+            field: ImString::with_capacity(64),
+            new_name: ImString::with_capacity(32),
+            message: None,
+        }
+    }
+
+    fn profile_list(&mut self, project: &mut Project, ui: &Ui) {
+        let mut removed = None;
+
+        for (i, name) in project.export_profiles.keys().enumerate() {
+            if ui.small_button(im_str!("Edit##Profile{}", i)) {
+                self.selected = Some(name.clone());
+            }
+
+            ui.same_line(0.0);
+            if ui.small_button(im_str!("Delete##Profile{}", i)) {
+                removed = Some(name.clone());
+            }
+
+            ui.same_line(0.0);
+            match self.selected.as_ref() == Some(name) {
+                true => ui.text(&format!("{} (editing)", name)),
+                false => ui.text(name),
+            }
+        }
+
+        // Exporters using the profile go back to the default
+        if let Some(name) = removed {
+            project.export_profiles.remove(&name);
+            project.exporter_profiles.retain(|_, x| *x != name);
+        }
+
+        ui.input_text(im_str!("Name##NewProfile"), &mut self.name).build();
+        if ui.small_button(im_str!("Add Profile")) {
+            let name = self.name.to_str().trim().to_string();
+
+            self.message = match name.is_empty() || project.export_profiles.contains_key(&name) {
+                true => Some(String::from("Profiles need a new name")),
+                false => {
+                    project.export_profiles.insert(name.clone(), ExportProfile::default());
+                    self.selected = Some(name);
+                    None
+                }
+            };
+        }
+    }
+
+    fn coordinates(profile: &mut ExportProfile, ui: &Ui) {
+        ui.checkbox(im_str!("Y Up"), &mut profile.flip_y);
+        ui.input_float(im_str!("Scale"), &mut profile.scale).build();
+        ui.input_float(im_str!("Box Step"), &mut profile.box_step).build();
+        ui.input_float(im_str!("Field Step"), &mut profile.field_step).build();
+
+        let mut convert = profile.fps.is_some();
+        if ui.checkbox(im_str!("Convert Tick Rate"), &mut convert) {
+            profile.fps = if convert { Some(60) } else { None };
+        }
+
+        if let Some(fps) = profile.fps {
+            let mut value = fps as i32;
+            if ui.input_int(im_str!("Ticks Per Second"), &mut value).build() {
+                profile.fps = Some(value.max(1).min(u16::max_value() as i32) as u16);
+            }
+        }
+    }
+
+    fn fields(&mut self, profile: &mut ExportProfile, ui: &Ui) {
+        let mut restored = None;
+
+        for (i, (field, name)) in profile.renamed.iter().enumerate() {
+            if ui.small_button(im_str!("x##Renamed{}", i)) {
+                restored = Some(field.clone());
+            }
+            ui.same_line(0.0);
+            ui.text(&format!("{} as '{}'", field, name));
+        }
+
+        for (i, field) in profile.omitted.iter().enumerate() {
+            if ui.small_button(im_str!("x##Omitted{}", i)) {
+                restored = Some(field.clone());
+            }
+            ui.same_line(0.0);
+            ui.text(&format!("{} left out", field));
+        }
+
+        if let Some(field) = restored {
+            profile.renamed.remove(&field);
+            profile.omitted.remove(&field);
+        }
+
+        ui.input_text(im_str!("Type.field"), &mut self.field).build();
+        ui.input_text(im_str!("New Name"), &mut self.new_name).build();
+
+        let field = self.field.to_str().trim().to_string();
+        let new_name = self.new_name.to_str().trim().to_string();
+        if ui.small_button(im_str!("Rename")) && !field.is_empty() && !new_name.is_empty() {
+            profile.omitted.remove(&field);
+            profile.renamed.insert(field.clone(), new_name);
+        }
+
+        ui.same_line(0.0);
+        if ui.small_button(im_str!("Leave Out")) && !field.is_empty() {
+            profile.renamed.remove(&field);
+            profile.omitted.insert(field);
+        }
+    }
+
+    pub fn create_ui(&mut self, project: &mut Project, ui: &Ui) {
+        self.profile_list(project, ui);
+
+        if let Some(message) = &self.message {
+            ui.text(message);
+        }
+
+        let profile = match self.selected.as_ref().and_then(|x| project.export_profiles.get_mut(x)) {
+            Some(profile) => profile,
+            None => return,
+        };
+
+        ui.separator();
+        Self::coordinates(profile, ui);
+
+        ui.separator();
+        self.fields(profile, ui);
+    }
+}
+
+impl Default for ProfileEditor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,82 @@
+use imgui::*;
+use std::path::Path;
+use crate::{
+    frame::AnimationData,
+    project::Project,
+    project_file,
+    state::{State, sandbox::Sandbox},
+    validate,
+};
+
+pub struct ProjectPanel {
+    path: ImString,
+    message: Option<String>,
+}
+
+impl ProjectPanel {
+    pub fn new() -> Self {
+        Self {
+            path: ImString::with_capacity(256),
+            message: None,
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        self.path.to_str()
+    }
+
+    // The editor always needs an animation to show
+    fn open(state: &mut State, project: &mut Project, loaded: Project) {
+        *project = loaded;
+        if project.animations.is_empty() {
+            project.add_animation(AnimationData::new("Animation".to_string(), 12));
+        }
+
+        state.current_animation = 0;
+        state.selected_box = None;
+        state.sandbox = Sandbox::new();
+        state.select_frame(&project.animations[0], 0);
+    }
+
+    pub fn create_ui(&mut self, state: &mut State, project: &mut Project, ui: &Ui) {
+        ui.input_text(im_str!("Path"), &mut self.path).build();
+
+        if ui.small_button(im_str!("Save")) {
+            self.message = Some(match project_file::save(project, Path::new(self.path.to_str())) {
+                Ok(()) => "Saved".to_string(),
+                Err(error) => format!("Save failed: {}", error),
+            });
+        }
+
+        ui.same_line(0.0);
+        if ui.small_button(im_str!("Load")) {
+            self.message = Some(match project_file::load(Path::new(self.path.to_str())) {
+                Ok(loaded) => {
+                    Self::open(state, project, loaded);
+                    "Loaded".to_string()
+                }
+                Err(error) => format!("Load failed: {}", error),
+            });
+        }
+
+        ui.same_line(0.0);
+        if ui.small_button(im_str!("Validate")) {
+            let errors = validate::validate_project(project);
+            self.message = Some(match errors.is_empty() {
+                true => "No errors".to_string(),
+                false => errors.iter().map(|x| x.to_string()).collect::<Vec<_>>().join("\n"),
+            });
+        }
+
+        if let Some(message) = &self.message {
+            ui.separator();
+            ui.text(message);
+        }
+    }
+}
+
+impl Default for ProjectPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
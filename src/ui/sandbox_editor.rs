@@ -0,0 +1,107 @@
+use imgui::*;
+use crate::{
+    project::Project,
+    state::State,
+};
+
+pub struct SandboxEditor {
+    animation_names: Vec<ImString>,
+    range_step: f32,
+    max_distance: f32,
+}
+
+impl SandboxEditor {
+    pub fn new() -> Self {
+        Self {
+            animation_names: vec![],
+            range_step: 1.0,
+            max_distance: 512.0,
+        }
+    }
+
+    fn update_names(&mut self, project: &Project) {
+        self.animation_names.truncate(project.animations.len());
+
+        for (i, animation) in project.animations.iter().enumerate() {
+            if i >= self.animation_names.len() {
+                self.animation_names.push(ImString::new(animation.name.clone()));
+            }
+            else if self.animation_names[i].to_str() != animation.name {
+                self.animation_names[i] = ImString::new(animation.name.clone());
+            }
+        }
+    }
+
+    pub fn placement_controls(&mut self, state: &mut State, project: &Project, ui: &Ui) {
+        self.update_names(project);
+        let sandbox = &mut state.sandbox;
+        let items = self.animation_names.iter().map(|x| x.as_ref()).collect::<Vec<&ImStr>>();
+
+        let mut attacker = sandbox.attacker as i32;
+        if ui.combo(im_str!("Attacker"), &mut attacker, &items[..], 5) {
+            sandbox.attacker = attacker.max(0) as usize;
+        }
+        ui.checkbox(im_str!("Attacker Faces Right"), &mut sandbox.attacker_facing_right);
+
+        let mut defender = sandbox.defender as i32;
+        if ui.combo(im_str!("Defender"), &mut defender, &items[..], 5) {
+            sandbox.defender = defender.max(0) as usize;
+        }
+        ui.checkbox(im_str!("Defender Faces Right"), &mut sandbox.defender_facing_right);
+
+        ui.slider_float(im_str!("Distance"), &mut sandbox.distance, 0.0, self.max_distance).build();
+    }
+
+    pub fn step_controls(&mut self, state: &mut State, project: &Project, ui: &Ui) {
+        if ui.small_button(im_str!("<")) {
+            state.sandbox.step(project, false);
+        }
+
+        ui.same_line(0.0);
+        if ui.small_button(im_str!(">")) {
+            state.sandbox.step(project, true);
+        }
+
+        ui.same_line(0.0);
+        let text = format!("Tick: {}{}", state.sandbox.tick, if state.sandbox.hitting(project) { " (Hit)" } else { "" });
+        ui.text(&text);
+    }
+
+    pub fn results(&mut self, state: &mut State, project: &Project, ui: &Ui) {
+        ui.input_float(im_str!("Range Step"), &mut self.range_step).build();
+        ui.input_float(im_str!("Max Distance"), &mut self.max_distance).build();
+
+        if ui.small_button(im_str!("Simulate")) {
+            state.sandbox.simulate(project, self.range_step, self.max_distance);
+        }
+
+        let first_hit = match state.sandbox.first_hit {
+            Some(tick) => format!("First Hit: tick {}", tick),
+            None => String::from("First Hit: -"),
+        };
+        let max_range = match state.sandbox.max_range {
+            Some(range) => format!("Max Range: {}", range),
+            None => String::from("Max Range: -"),
+        };
+
+        ui.text(&first_hit);
+        ui.text(&max_range);
+    }
+
+    pub fn create_ui(&mut self, state: &mut State, project: &Project, ui: &Ui) {
+        ui.checkbox(im_str!("Show Sandbox"), &mut state.sandbox.enabled);
+        if !state.sandbox.enabled {
+            return;
+        }
+
+        self.placement_controls(state, project, ui);
+        self.step_controls(state, project, ui);
+        self.results(state, project, ui);
+    }
+}
+
+impl Default for SandboxEditor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
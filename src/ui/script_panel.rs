@@ -0,0 +1,127 @@
+use imgui::*;
+use crate::{
+    project::Project,
+    script::{self, ScriptOutput},
+};
+
+// Room left for typing after a script is loaded into the editor
+const SOURCE_CAPACITY: usize = 4096;
+
+pub struct ScriptPanel {
+    name: ImString,
+    source: ImString,
+    output: Option<ScriptOutput>,
+    message: Option<String>,
+}
+
+impl ScriptPanel {
+    pub fn new() -> Self {
+        Self {
+            name: ImString::with_capacity(32),
+            source: ImString::with_capacity(SOURCE_CAPACITY),
+            output: None,
+            message: None,
+        }
+    }
+
+    fn open(&mut self, name: &str, source: &str) {
+        self.name = ImString::with_capacity(name.len() + 32);
+        self.name.push_str(name);
+        self.source = ImString::with_capacity(source.len() + SOURCE_CAPACITY);
+        self.source.push_str(source);
+        self.message = None;
+    }
+
+    fn script_list(&mut self, project: &mut Project, ui: &Ui) {
+        let mut opened = None;
+        let mut run = None;
+
+        for (i, (name, source)) in project.scripts.iter().enumerate() {
+            if ui.small_button(im_str!("Open##Script{}", i)) {
+                opened = Some((name.clone(), source.clone()));
+            }
+
+            ui.same_line(0.0);
+            if ui.small_button(im_str!("Run##Script{}", i)) {
+                run = Some(source.clone());
+            }
+
+            ui.same_line(0.0);
+            ui.text(name);
+        }
+
+        if let Some((name, source)) = opened {
+            self.open(&name, &source);
+        }
+
+        if let Some(source) = run {
+            self.output = Some(script::run(project, &source));
+        }
+    }
+
+    fn script_editor(&mut self, project: &mut Project, ui: &Ui) {
+        ui.input_text(im_str!("Name"), &mut self.name).build();
+        ui.input_text_multiline(im_str!("##Source"), &mut self.source, (0.0, 150.0)).build();
+
+        if ui.small_button(im_str!("Run")) {
+            self.output = Some(script::run(project, self.source.to_str()));
+        }
+
+        ui.same_line(0.0);
+        if ui.small_button(im_str!("Save Script")) {
+            let name = self.name.to_str().trim();
+
+            self.message = match name.is_empty() {
+                true => Some(String::from("Scripts need a name")),
+                false => {
+                    project.scripts.insert(name.to_string(), self.source.to_str().to_string());
+                    None
+                }
+            };
+        }
+
+        ui.same_line(0.0);
+        if ui.small_button(im_str!("Delete Script")) {
+            project.scripts.remove(self.name.to_str().trim());
+        }
+    }
+
+    fn script_output(&self, ui: &Ui) {
+        let output = match &self.output {
+            Some(output) => output,
+            None => return,
+        };
+
+        ui.separator();
+        for line in output.printed.iter() {
+            ui.text(line);
+        }
+        for warning in output.warnings.iter() {
+            ui.text(&format!("warning: {}", warning));
+        }
+
+        match &output.error {
+            Some(error) => ui.text(&format!("error, changes were undone: {}", error)),
+            None => ui.text("Done"),
+        }
+    }
+
+    pub fn create_ui(&mut self, project: &mut Project, ui: &Ui) {
+        self.script_list(project, ui);
+
+        ui.separator();
+        self.script_editor(project, ui);
+
+        if let Some(message) = &self.message {
+            ui.text(message);
+        }
+
+        self.script_output(ui);
+    }
+}
+
+impl Default for ScriptPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
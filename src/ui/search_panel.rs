@@ -0,0 +1,100 @@
+use imgui::*;
+use crate::{
+    data::RecordId,
+    frame::AnimationData,
+    query::Query,
+    state::State,
+};
+
+pub struct SearchPanel {
+    text: ImString,
+    // Type, key and a summary of the values of each match
+    results: Vec<(String, RecordId, String)>,
+    error_message: Option<String>,
+}
+
+impl SearchPanel {
+    pub fn new() -> Self {
+        Self {
+            text: ImString::with_capacity(64),
+            results: vec![],
+            error_message: None,
+        }
+    }
+
+    // Runs every frame so the matches follow edits to the data
+    fn search(&mut self, animation: &AnimationData) {
+        self.results.clear();
+        self.error_message = None;
+
+        if self.text.to_str().trim().is_empty() {
+            return;
+        }
+
+        let rows = Query::parse(self.text.to_str()).and_then(|query| animation.data().query(&query));
+        match rows {
+            Ok(rows) => {
+                self.results = rows.into_iter().map(|(dtype, row)| {
+                    let values = row.values.iter().map(|(name, value)| format!("{}={}", name, value)).collect::<Vec<_>>();
+                    (dtype.to_string(), *row.key, values.join(", "))
+                }).collect();
+            }
+            Err(e) => self.error_message = Some(e.to_string()),
+        }
+    }
+
+    // Selects the first box using the record and shows it on the first frame it is active on
+    fn select(state: &mut State, animation: &AnimationData, record: &(String, RecordId)) {
+        let index = match animation.data_boxes().iter().position(|x| x.data == *record) {
+            Some(index) => index,
+            None => return,
+        };
+
+        if let Some(range) = animation.activation_ranges(index).first() {
+            state.select_frame(animation, range.start);
+        }
+
+        state.selected_box = Some(index);
+        let translation = animation.world_shape(&animation.data_boxes()[index], state.current_frame).get_translation();
+        state.canvas.focus(translation.x, translation.y);
+    }
+
+    pub fn create_ui(&mut self, state: &mut State, animation: &AnimationData, ui: &Ui) {
+        ui.input_text(im_str!("Search"), &mut self.text).build();
+        ui.text("Type: field > value and field = value");
+
+        self.search(animation);
+        state.canvas.highlighted_records = self.results.iter().map(|(dtype, key, _)| (dtype.clone(), *key)).collect();
+
+        ui.separator();
+        if let Some(message) = &self.error_message {
+            ui.text(message);
+            return;
+        }
+
+        let mut selected = None;
+        for (i, (dtype, key, values)) in self.results.iter().enumerate() {
+            let record = (dtype.clone(), *key);
+            let boxes = animation.data_boxes().iter().filter(|x| x.data == record).count();
+
+            if boxes > 0 {
+                if ui.small_button(im_str!("Go##Match{}", i)) {
+                    selected = Some(record);
+                }
+                ui.same_line(0.0);
+            }
+
+            ui.text(&format!("{} {} ({} boxes): {}", dtype, key, boxes, values));
+        }
+
+        if let Some(record) = selected {
+            Self::select(state, animation, &record);
+        }
+    }
+}
+
+impl Default for SearchPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
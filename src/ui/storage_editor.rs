@@ -50,6 +50,10 @@ pub struct StorageEditor {
     field_selected: i32,
     header_open: bool,
 
+    // Name of the field whose expression is being edited
+    editing_expression: Option<String>,
+    expression_buffer: ImString,
+
     error_message: Option<String>,
 }   
 
@@ -66,6 +70,9 @@ impl StorageEditor {
             field_selected: 0,
             header_open: false,
 
+            editing_expression: None,
+            expression_buffer: ImString::with_capacity(64),
+
             error_message: None,
         }
     }
@@ -99,13 +106,12 @@ impl StorageEditor {
         };
 
         if ui.small_button(im_str!("Add Field")) {
-            if storage.has_field_with_name(self.field_name.to_str()) {
-                self.error_message = Some(String::from("Already has that field"));
-            }
-            else {
-                self.error_message = None;
-                storage.add_field(self.field_name.to_str().to_string(), self.field_type.1);
-                self.field_name.clear();
+            match storage.add_field(self.field_name.to_str().to_string(), self.field_type.1) {
+                Ok(()) => {
+                    self.error_message = None;
+                    self.field_name.clear();
+                }
+                Err(e) => self.error_message = Some(e.to_string()),
             }
         }
 
@@ -165,7 +171,7 @@ impl StorageEditor {
 
     }
 
-    pub fn type_display<K: Eq + std::hash::Hash>(&self, storage: &mut DataStorage<K>, ui: &Ui) {
+    pub fn type_display<K: Eq + std::hash::Hash>(&mut self, storage: &mut DataStorage<K>, ui: &Ui) {
         if self.selected_type.is_none() {
             return;
         }
@@ -177,6 +183,7 @@ impl StorageEditor {
         };
         let mut to_remove = None;
         let mut to_move = None;
+        let mut to_edit = None;
 
         let (x, _) = ui.get_window_size();
         let child_x = x - self.config.width_padding;
@@ -189,12 +196,21 @@ impl StorageEditor {
             .movable(false)
             .build(|| {
                 for (i, desc)in data_description.get_desc().iter().enumerate() {
-                    let text = format!("{}: {:?}", desc.name, desc.dtype);
+                    let text = match desc.expression() {
+                        Some(expression) => format!("{}: {:?} = {}", desc.name, desc.dtype, expression.source()),
+                        None => format!("{}: {:?}", desc.name, desc.dtype),
+                    };
                     let remove = im_str!("Remove##{}", i);
                     let up = im_str!("u##{}", i);
                     let down = im_str!("d##{}", i);
+                    let edit = im_str!("=##{}", i);
 
                     ui.text(&text);
+                    ui.same_line(child_x - 125.0 - self.config.width_padding);
+                    if ui.small_button(edit) {
+                        to_edit = Some(i);
+                    }
+
                     ui.same_line(child_x - 100.0 - self.config.width_padding);
                     if ui.small_button(up) {
                         to_move = Some((i, true));
@@ -214,14 +230,52 @@ impl StorageEditor {
             });  
         });
 
-        if let Some(index) = to_remove {
-            data_description.remove_field(index);
+        let result = match (to_remove, to_move) {
+            (Some(index), _) => data_description.remove_field(index).map(|_| ()),
+            (_, Some((i, true))) => data_description.move_field_up(i),
+            (_, Some((i, false))) => data_description.move_field_down(i),
+            _ => Ok(()),
+        };
+
+        if let Err(e) = result {
+            self.error_message = Some(e.to_string());
+        }
+
+        if let Some(desc) = to_edit.and_then(|x| data_description.get_desc().get(x)) {
+            self.expression_buffer.clear();
+            self.expression_buffer.push_str(desc.expression().map(|x| x.source()).unwrap_or(""));
+            self.editing_expression = Some(desc.name.clone());
+        }
+
+        self.expression_editor(data_description, ui);
+    }
+
+    // An empty expression makes the field stored again
+    fn expression_editor<K: Eq + std::hash::Hash>(&mut self, storage: &mut DataTypeStorage<K>, ui: &Ui) {
+        let (index, name) = match self.editing_expression.as_ref().and_then(|x| storage.field_index(x).map(|i| (i, x.clone()))) {
+            Some(field) => field,
+            None => {
+                self.editing_expression = None;
+                return;
+            }
+        };
+
+        ui.text(&format!("Expression of {}", name));
+        ui.input_text(im_str!("##Expression"), &mut self.expression_buffer).build();
+
+        if ui.small_button(im_str!("Set Expression")) {
+            match storage.set_expression(index, self.expression_buffer.to_str()) {
+                Ok(()) => {
+                    self.error_message = None;
+                    self.editing_expression = None;
+                }
+                Err(e) => self.error_message = Some(e.to_string()),
+            }
         }
 
-        match to_move {
-            Some((i, true)) => data_description.move_field_up(i),
-            Some((i, false)) => data_description.move_field_down(i),
-            _ => {}
+        ui.same_line(0.0);
+        if ui.small_button(im_str!("Cancel##Expression")) {
+            self.editing_expression = None;
         }
     }
 
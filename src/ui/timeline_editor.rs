@@ -0,0 +1,238 @@
+use imgui::*;
+use std::ops::Range;
+use crate::{
+    frame::AnimationData,
+    state::{State, playback::PlaybackMode},
+    ui::data_editor::DataEditor,
+};
+
+pub struct TimelineEditor {
+    mode_names: [ImString; 3],
+
+    marker_name: ImString,
+    marker_type: i32,
+    type_names: Vec<ImString>,
+    selected_marker: Option<usize>,
+    data_editor: DataEditor,
+    error_message: Option<String>,
+}
+
+impl TimelineEditor {
+    pub fn new() -> Self {
+        Self {
+            mode_names: [
+                ImString::new("Loop"),
+                ImString::new("Ping Pong"),
+                ImString::new("Once"),
+            ],
+
+            marker_name: ImString::with_capacity(20),
+            marker_type: 0,
+            type_names: vec![],
+            selected_marker: None,
+            data_editor: DataEditor::new(),
+            error_message: None,
+        }
+    }
+
+    pub fn transport_controls(&mut self, state: &mut State, animation: &AnimationData, ui: &Ui) {
+        if ui.small_button(im_str!("|<")) {
+            state.playback.step_frame(animation, false);
+        }
+
+        ui.same_line(0.0);
+        if ui.small_button(im_str!("<")) {
+            state.playback.step_tick(animation, false);
+        }
+
+        ui.same_line(0.0);
+        let label = if state.playback.playing { im_str!("Pause") } else { im_str!("Play") };
+        if ui.small_button(label) {
+            state.playback.toggle();
+        }
+
+        ui.same_line(0.0);
+        if ui.small_button(im_str!(">")) {
+            state.playback.step_tick(animation, true);
+        }
+
+        ui.same_line(0.0);
+        if ui.small_button(im_str!(">|")) {
+            state.playback.step_frame(animation, true);
+        }
+
+        let mut mode = match state.playback.mode {
+            PlaybackMode::Loop => 0,
+            PlaybackMode::PingPong => 1,
+            PlaybackMode::Once => 2,
+        };
+        let items = [
+            self.mode_names[0].as_ref(),
+            self.mode_names[1].as_ref(),
+            self.mode_names[2].as_ref(),
+        ];
+
+        if ui.combo(im_str!("Mode"), &mut mode, &items[..], 3) {
+            state.playback.mode = match mode {
+                1 => PlaybackMode::PingPong,
+                2 => PlaybackMode::Once,
+                _ => PlaybackMode::Loop,
+            };
+        }
+
+        ui.slider_float(im_str!("Speed"), &mut state.playback.speed, 0.1, 4.0).build();
+    }
+
+    pub fn frame_selector(&mut self, state: &mut State, animation: &mut AnimationData, ui: &Ui) {
+        if animation.frames().is_empty() {
+            return;
+        }
+
+        let text = format!("Tick: {} / {}", state.playback.tick(), animation.total_ticks());
+        ui.text(&text);
+
+        if !state.canvas.highlighted_records.is_empty() {
+            let ranges = Self::highlighted_ranges(state, animation);
+            let frames = ranges.iter().map(|x| match x.end - x.start {
+                1 => format!("{}", x.start),
+                _ => format!("{}-{}", x.start, x.end - 1),
+            }).collect::<Vec<_>>();
+
+            ui.text(&format!("Matches on frames: {}", if frames.is_empty() { "none".to_string() } else { frames.join(", ") }));
+        }
+
+        let mut frame = state.current_frame as i32;
+        let max = animation.frames().len() as i32 - 1;
+
+        if ui.slider_int(im_str!("Frame"), &mut frame, 0, max).build() {
+            state.select_frame(animation, frame.max(0).min(max) as usize);
+        }
+
+        let mut fps = animation.fps() as i32;
+        if ui.input_int(im_str!("FPS"), &mut fps).build() {
+            animation.set_fps(fps.max(1).min(u16::max_value() as i32) as u16);
+        }
+
+        let current = state.current_frame;
+        if let Some(frame) = animation.frame_mut(current) {
+            let mut duration = frame.duration() as i32;
+
+            if ui.input_int(im_str!("Duration"), &mut duration).build() {
+                frame.set_duration(duration.max(1).min(u16::max_value() as i32) as u16);
+            }
+        }
+    }
+
+    // Frames on which a highlighted box is active, merged into sorted ranges
+    fn highlighted_ranges(state: &State, animation: &AnimationData) -> Vec<Range<usize>> {
+        let frames = (0..animation.frames().len()).filter(|frame| {
+            animation.data_boxes().iter().enumerate().any(|(i, data_box)| {
+                state.canvas.highlighted_records.contains(&data_box.data) && animation.is_box_active(i, *frame)
+            })
+        });
+
+        let mut ranges: Vec<Range<usize>> = vec![];
+        for frame in frames {
+            match ranges.last_mut() {
+                Some(range) if range.end == frame => range.end += 1,
+                _ => ranges.push(frame..frame + 1),
+            }
+        }
+
+        ranges
+    }
+
+    pub fn marker_adder(&mut self, state: &mut State, animation: &mut AnimationData, ui: &Ui) {
+        let mut names = animation.data().data.keys().cloned().collect::<Vec<_>>();
+        names.sort();
+        self.type_names = names.into_iter().map(ImString::new).collect();
+
+        ui.input_text(im_str!("Marker Name"), &mut self.marker_name).build();
+        let items = self.type_names.iter().map(|x| x.as_ref()).collect::<Vec<&ImStr>>();
+        ui.combo(im_str!("Marker Type"), &mut self.marker_type, &items[..], 5);
+
+        if ui.small_button(im_str!("Add Marker")) {
+            let dtype = match self.type_names.get(self.marker_type.max(0) as usize) {
+                Some(dtype) => dtype.to_str().to_string(),
+                None => {
+                    self.error_message = Some(String::from("Select a type for the marker"));
+                    return;
+                }
+            };
+
+            let name = self.marker_name.to_str().to_string();
+            self.selected_marker = animation.new_marker(name, &dtype, state.playback.tick(), 0);
+            self.error_message = None;
+            self.marker_name.clear();
+        }
+    }
+
+    pub fn marker_list(&mut self, state: &mut State, animation: &mut AnimationData, ui: &Ui) {
+        let tick = state.playback.tick();
+        let mut to_remove = None;
+
+        for (i, marker) in animation.markers_mut().iter_mut().enumerate() {
+            let active = if marker.active_on(tick) { "*" } else { " " };
+            let matched = if state.canvas.highlighted_records.contains(&marker.data) { " [match]" } else { "" };
+            let text = format!("{}{} ({}){}", active, marker.name, marker.data.0, matched);
+            ui.text(&text);
+
+            ui.same_line(0.0);
+            if ui.small_button(im_str!("Edit##Marker{}", i)) {
+                self.selected_marker = Some(i);
+            }
+
+            ui.same_line(0.0);
+            if ui.small_button(im_str!("Remove##Marker{}", i)) {
+                to_remove = Some(i);
+            }
+
+            let mut start = marker.start as i32;
+            let mut length = marker.length as i32;
+            if ui.input_int(im_str!("Start##Marker{}", i), &mut start).build() {
+                marker.start = start.max(0) as u32;
+            }
+            if ui.input_int(im_str!("Length##Marker{}", i), &mut length).build() {
+                marker.length = length.max(0) as u32;
+            }
+        }
+
+        if let Some(index) = to_remove {
+            animation.remove_marker(index);
+            self.selected_marker = None;
+        }
+    }
+
+    pub fn marker_data(&mut self, animation: &mut AnimationData, ui: &Ui) {
+        let (dtype, key) = match self.selected_marker.and_then(|x| animation.markers().get(x)) {
+            Some(marker) => marker.data.clone(),
+            None => return,
+        };
+
+        if let Some(storage) = animation.data_mut().get_storage_mut(&dtype) {
+            ui.separator();
+            self.data_editor.create_ui(&key, storage, ui);
+        }
+    }
+
+    pub fn create_ui(&mut self, state: &mut State, animation: &mut AnimationData, ui: &Ui) {
+        self.transport_controls(state, animation, ui);
+        self.frame_selector(state, animation, ui);
+
+        ui.separator();
+        self.marker_adder(state, animation, ui);
+        self.marker_list(state, animation, ui);
+        self.marker_data(animation, ui);
+
+        if let Some(message) = &self.error_message {
+            ui.separator();
+            ui.text(message);
+        }
+    }
+}
+
+impl Default for TimelineEditor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
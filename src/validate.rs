@@ -0,0 +1,84 @@
+use std::fmt;
+use crate::{
+    data::RecordId,
+    frame::AnimationData,
+    project::Project,
+};
+
+// Something that makes the project unusable for export, such as a box pointing at a missing record
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValidationError {
+    pub animation: String,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.animation, self.message)
+    }
+}
+
+fn check_record(animation: &AnimationData, (dtype, key): &(String, RecordId), owner: &str, errors: &mut Vec<String>) {
+    let storage = match animation.data().get_storage(dtype) {
+        Some(storage) => storage,
+        None => return errors.push(format!("{} uses the missing type '{}'", owner, dtype)),
+    };
+
+    match storage.get(key) {
+        Some(values) if values.len() != storage.get_desc().len() => {
+            errors.push(format!("{} record {} of '{}' has {} values for {} fields", owner, key, dtype, values.len(), storage.get_desc().len()));
+        }
+        Some(_) => (),
+        None => errors.push(format!("{} uses the missing record {} of '{}'", owner, key, dtype)),
+    }
+}
+
+pub fn validate_animation(animation: &AnimationData) -> Vec<String> {
+    let mut errors = vec![];
+
+    for (i, data_box) in animation.data_boxes().iter().enumerate() {
+        check_record(animation, &data_box.data, &format!("box {}", i), &mut errors);
+    }
+
+    for (i, frame) in animation.frames().iter().enumerate() {
+        for active in frame.active_hitboxes() {
+            if *active >= animation.data_boxes().len() {
+                errors.push(format!("frame {} activates the missing box {}", i, active));
+            }
+        }
+    }
+
+    let total = animation.total_ticks();
+    for marker in animation.markers() {
+        let owner = format!("marker '{}'", marker.name);
+        check_record(animation, &marker.data, &owner, &mut errors);
+
+        if marker.start >= total && total > 0 {
+            errors.push(format!("{} starts on tick {} after the animation ends", owner, marker.start));
+        }
+    }
+
+    errors
+}
+
+pub fn validate_project(project: &Project) -> Vec<ValidationError> {
+    let mut errors = vec![];
+
+    for (i, animation) in project.animations.iter().enumerate() {
+        if project.animations[..i].iter().any(|x| x.name == animation.name) {
+            errors.push(ValidationError {
+                animation: animation.name.clone(),
+                message: "more than one animation has this name".to_string(),
+            });
+        }
+
+        for message in validate_animation(animation) {
+            errors.push(ValidationError {
+                animation: animation.name.clone(),
+                message,
+            });
+        }
+    }
+
+    errors
+}
@@ -0,0 +1,109 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+use crate::{
+    export::registry::{ExportTarget, Registry},
+    project::Project,
+    validate,
+};
+
+// Modification times are polled, checking this often
+pub const POLL_INTERVAL: Duration = Duration::from_millis(250);
+// Exports once the files have stopped changing for this long, so saving several files or an
+// editor writing a file in steps only exports once
+pub const DEBOUNCE: Duration = Duration::from_millis(500);
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|x| x.modified()).ok()
+}
+
+// The project file and the sprite sheets of its animations, sprite sheets are relative to the
+// working directory like in the editor
+pub fn watched_files(project: &Project, project_path: &Path) -> Vec<PathBuf> {
+    let mut files = vec![project_path.to_path_buf()];
+
+    for sheet in project.animations.iter().filter_map(|x| x.sprite_sheet.as_ref()) {
+        let path = PathBuf::from(sheet);
+        if !files.contains(&path) {
+            files.push(path);
+        }
+    }
+
+    files
+}
+
+pub struct Watcher {
+    // Last seen modification time of each file, None while it is missing
+    files: BTreeMap<PathBuf, Option<SystemTime>>,
+    last_poll: Option<Instant>,
+    // Time of the latest change not exported yet
+    pending: Option<Instant>,
+}
+
+impl Watcher {
+    pub fn new(files: Vec<PathBuf>) -> Watcher {
+        let mut watcher = Watcher {
+            files: BTreeMap::new(),
+            last_poll: None,
+            pending: None,
+        };
+
+        watcher.set_files(files);
+        watcher
+    }
+
+    // Files not watched before start from their current state so adding them is not a change
+    pub fn set_files(&mut self, files: Vec<PathBuf>) {
+        let mut previous = std::mem::take(&mut self.files);
+
+        for path in files {
+            let time = previous.remove(&path).unwrap_or_else(|| modified(&path));
+            self.files.insert(path, time);
+        }
+    }
+
+    pub fn files<'a>(&'a self) -> impl Iterator<Item = &'a Path> + 'a {
+        self.files.keys().map(|x| x.as_path())
+    }
+
+    // True once the files changed and then stayed the same for the debounce time
+    pub fn poll(&mut self, now: Instant) -> bool {
+        if self.last_poll.map(|x| now.duration_since(x) < POLL_INTERVAL).unwrap_or(false) {
+            return false;
+        }
+        self.last_poll = Some(now);
+
+        for (path, time) in self.files.iter_mut() {
+            let current = modified(path);
+            if current != *time {
+                *time = current;
+                self.pending = Some(now);
+            }
+        }
+
+        match self.pending {
+            Some(changed) if now.duration_since(changed) >= DEBOUNCE => {
+                self.pending = None;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+// Runs every target after validating, returning a line to report for each target. Failing targets
+// don't stop the others
+pub fn export_targets(registry: &Registry, project: &Project, targets: &[ExportTarget]) -> Vec<Result<String, String>> {
+    let errors = validate::validate_project(project);
+    if !errors.is_empty() {
+        return errors.iter().map(|x| Err(format!("not exporting a project with errors: {}", x))).collect();
+    }
+
+    targets.iter().map(|target| match target.run(registry, project) {
+        Ok(written) => Ok(format!("{}: wrote {} files to {}", target.exporter, written.len(), target.directory)),
+        Err(error) => Err(format!("{}: {}", target.exporter, error)),
+    }).collect()
+}